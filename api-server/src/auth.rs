@@ -1,17 +1,18 @@
 // This file contains few comments which may feel out of place, but they are here only to explain the concepts of OOP in Rust.
 
 use crate::error::{AppError, AuthErrorCode};
+use crate::mailer::send_mail;
 use argon2::{
     password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
     Algorithm, Argon2, Params, Version,
 };
 use async_graphql::*;
 use chrono::{Duration, TimeDelta, Utc};
+use hmac::{Hmac, Mac};
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
 use lazy_regex::regex;
-use mail_send::mail_builder::MessageBuilder;
-use mail_send::SmtpClientBuilder;
 use serde::{Deserialize, Serialize};
+use sha2::Sha256;
 use std::env;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -126,6 +127,25 @@ impl Auth {
         .map_err(|e| AppError::Internal(format!("Token creation failed: {}", e)))
     }
 
+    // webhook senders sign the raw request body with HMAC-SHA256 using a shared secret
+    pub fn verify_signature(secret: &str, payload: &[u8], signature: &str) -> Result<(), AppError> {
+        let signature =
+            hex::decode(signature.trim_start_matches("sha256=")).map_err(|_| AppError::Auth {
+                message: "Malformed signature".to_string(),
+                code: AuthErrorCode::InvalidCredentials,
+                user_id: None,
+            })?;
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+            .map_err(|e| AppError::Internal(format!("Invalid signing secret: {}", e)))?;
+        mac.update(payload);
+        mac.verify_slice(&signature).map_err(|_| AppError::Auth {
+            message: "Invalid signature".to_string(),
+            code: AuthErrorCode::InvalidCredentials,
+            user_id: None,
+        })
+    }
+
     pub fn check_password_strength(password: &str) -> Result<(), &'static str> {
         if password.len() < 8
             || !regex!(r"[A-Z]").is_match(password)
@@ -157,41 +177,24 @@ impl Auth {
 
         let port = env::var("PORT").map_err(|_| "PORT must be set")?;
 
-        // Build a simple multipart message
-        let message = MessageBuilder::new()
-            .from((
-                "Verify Mail Id Bitte",
-                "postmaster@testing.giripriyadarshan.com",
-            ))
-            .to(email)
-            .subject("Nine11 email verification")
-            .html_body(
-                "<a href=\"http://localhost:".to_string()
-                    + port.as_str()
-                    + "/verify/"
-                    + token.as_str()
-                    + "\">Click here to verify your email</a>",
-            );
-
-        // Connect to the SMTP submissions port, upgrade to TLS and
-        // authenticate using the provided credentials.
-        let smtp_username = env::var("SMTP_USERNAME").expect("SMTP_USERNAME must be set");
-        let smtp_password = env::var("SMTP_PASSWORD").expect("SMTP_PASSWORD must be set");
-        SmtpClientBuilder::new("smtp.mailgun.org", 587)
-            .implicit_tls(false)
-            .credentials((smtp_username.as_str(), smtp_password.as_str()))
-            .connect()
-            .await
-            .expect("Failed to connect to SMTP server")
-            .send(message)
-            .await
-            .expect("Failed to send email");
+        send_mail(
+            email,
+            "Nine11 email verification".to_string(),
+            "<a href=\"http://localhost:".to_string()
+                + port.as_str()
+                + "/verify/"
+                + token.as_str()
+                + "\">Click here to verify your email</a>",
+        )
+        .await
+        .map_err(|_| "Failed to send email")?;
         Ok("Email verification sent".to_string())
     }
 }
 
 pub const ROLE_SUPPLIER: &str = "supplier";
 pub const ROLE_CUSTOMER: &str = "customer";
+pub const ROLE_ADMIN: &str = "admin";
 
 // struct name is equivalent to a class name in OOP
 // it consists of data members
@@ -0,0 +1,185 @@
+use crate::auth::Auth;
+use crate::entity::prelude::{
+    Bills as BillsEntity, Orders as OrdersEntity, PaymentMethods as PaymentMethodsEntity,
+    ShipmentEvents as ShipmentEventsEntity, Shipments as ShipmentsEntity,
+};
+use crate::entity::sea_orm_active_enums::PaymentMethodType;
+use crate::entity::{bills, orders, shipment_events, shipments};
+use crate::error::AppError;
+use crate::models::bills::BILL_STATUS_PAID;
+use crate::models::gift_cards::issue_purchased_gift_cards;
+use crate::models::orders::{
+    ORDER_STATUS_DELIVERED, ORDER_STATUS_PAID, ORDER_STATUS_PENDING, ORDER_STATUS_SHIPPED,
+};
+use crate::models::shipments::{
+    check_shipment_status, CarrierEvent, SHIPMENT_STATUS_DELIVERED, SHIPMENT_STATUS_IN_TRANSIT,
+    SHIPMENT_STATUS_OUT_FOR_DELIVERY,
+};
+use crate::models::supplier_ledger::record_order_sales;
+use axum::body::Bytes;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::IntoResponse;
+use axum::Extension;
+use sea_orm::ActiveValue::Set;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+    TransactionTrait,
+};
+use std::env;
+
+pub async fn carrier_webhook(
+    Extension(postgres): Extension<DatabaseConnection>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> impl IntoResponse {
+    let secret = match env::var("CARRIER_WEBHOOK_SECRET") {
+        Ok(secret) => secret,
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "CARRIER_WEBHOOK_SECRET must be set".to_string(),
+            )
+        }
+    };
+
+    let signature = headers
+        .get("x-carrier-signature")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    if let Err(e) = Auth::verify_signature(&secret, &body, signature) {
+        return (StatusCode::UNAUTHORIZED, e.to_string());
+    }
+
+    let event: CarrierEvent = match serde_json::from_slice(&body) {
+        Ok(event) => event,
+        Err(e) => return (StatusCode::BAD_REQUEST, format!("Invalid payload: {}", e)),
+    };
+
+    if let Err(e) = check_shipment_status(&event.status) {
+        return (StatusCode::BAD_REQUEST, e.to_string());
+    }
+
+    match record_carrier_event(&postgres, event).await {
+        Ok(response) => response,
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+async fn record_carrier_event(
+    postgres: &DatabaseConnection,
+    event: CarrierEvent,
+) -> Result<(StatusCode, String), AppError> {
+    let txn = postgres.begin().await?;
+
+    let shipment = match ShipmentsEntity::find()
+        .filter(shipments::Column::Carrier.eq(&event.carrier))
+        .filter(shipments::Column::TrackingNumber.eq(&event.tracking_number))
+        .one(&txn)
+        .await?
+    {
+        Some(shipment) => shipment,
+        None => return Ok((StatusCode::NOT_FOUND, "Shipment not found".to_string())),
+    };
+
+    // carriers retry deliveries, so an event id we have already stored is acknowledged again
+    if let Some(event_id) = &event.event_id {
+        if ShipmentEventsEntity::find()
+            .filter(shipment_events::Column::CarrierEventId.eq(event_id))
+            .one(&txn)
+            .await?
+            .is_some()
+        {
+            return Ok((StatusCode::OK, "Event already recorded".to_string()));
+        }
+    }
+
+    let latest_event = ShipmentEventsEntity::find()
+        .filter(shipment_events::Column::ShipmentId.eq(shipment.shipment_id))
+        .order_by_desc(shipment_events::Column::OccurredAt)
+        .one(&txn)
+        .await?;
+
+    let shipment_event = shipment_events::ActiveModel {
+        shipment_id: Set(shipment.shipment_id),
+        carrier_event_id: Set(event.event_id),
+        status: Set(event.status.clone()),
+        description: Set(event.description),
+        location: Set(event.location),
+        occurred_at: Set(event.occurred_at),
+        ..Default::default()
+    };
+    ShipmentEventsEntity::insert(shipment_event)
+        .exec(&txn)
+        .await?;
+
+    // events can arrive out of order, older ones only go into the history
+    if latest_event.is_some_and(|latest| latest.occurred_at > event.occurred_at) {
+        txn.commit().await?;
+        return Ok((StatusCode::OK, "Event recorded".to_string()));
+    }
+
+    let order_id = shipment.order_id;
+    let shipment_id = shipment.shipment_id;
+    let mut shipment: shipments::ActiveModel = shipment.into();
+    shipment.status = Set(event.status.clone());
+    if event.status == SHIPMENT_STATUS_DELIVERED {
+        shipment.delivered_at = Set(Some(event.occurred_at));
+    }
+    shipment.update(&txn).await?;
+
+    let order = OrdersEntity::find_by_id(order_id)
+        .one(&txn)
+        .await?
+        .ok_or_else(|| AppError::Internal("Shipment without an order".to_string()))?;
+
+    let new_order_status = match event.status.as_str() {
+        SHIPMENT_STATUS_IN_TRANSIT | SHIPMENT_STATUS_OUT_FOR_DELIVERY
+            if order.status == ORDER_STATUS_PENDING || order.status == ORDER_STATUS_PAID =>
+        {
+            Some(ORDER_STATUS_SHIPPED)
+        }
+        SHIPMENT_STATUS_DELIVERED
+            if order.status == ORDER_STATUS_PENDING
+                || order.status == ORDER_STATUS_PAID
+                || order.status == ORDER_STATUS_SHIPPED =>
+        {
+            // an order is delivered once every shipment it was split into has arrived
+            let undelivered = ShipmentsEntity::find()
+                .filter(shipments::Column::OrderId.eq(order_id))
+                .filter(shipments::Column::ShipmentId.ne(shipment_id))
+                .filter(shipments::Column::Status.ne(SHIPMENT_STATUS_DELIVERED))
+                .one(&txn)
+                .await?;
+            undelivered.is_none().then_some(ORDER_STATUS_DELIVERED)
+        }
+        _ => None,
+    };
+
+    // cash on delivery is collected by the carrier, so the bill is settled on delivery
+    if new_order_status == Some(ORDER_STATUS_DELIVERED)
+        && PaymentMethodsEntity::find_by_id(order.payment_method_id)
+            .one(&txn)
+            .await?
+            .is_some_and(|payment_method| payment_method.payment_type == PaymentMethodType::Cod)
+    {
+        BillsEntity::update_many()
+            .col_expr(bills::Column::PaymentStatus, BILL_STATUS_PAID.into())
+            .filter(bills::Column::OrderId.eq(order_id))
+            .exec(&txn)
+            .await?;
+
+        issue_purchased_gift_cards(&txn, order_id).await?;
+        record_order_sales(&txn, order_id).await?;
+    }
+
+    if let Some(status) = new_order_status {
+        let mut order: orders::ActiveModel = order.into();
+        order.status = Set(status.to_string());
+        order.update(&txn).await?;
+    }
+
+    txn.commit().await?;
+
+    Ok((StatusCode::OK, "Event recorded".to_string()))
+}
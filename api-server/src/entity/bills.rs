@@ -12,11 +12,18 @@ pub struct Model {
     pub bill_date: Option<DateTimeWithTimeZone>,
     #[sea_orm(column_type = "Decimal(Some((10, 2)))")]
     pub total_amount: Decimal,
+    #[sea_orm(column_type = "Decimal(Some((10, 2)))")]
+    pub tax_amount: Decimal,
+    #[sea_orm(column_type = "Decimal(Some((10, 2)))")]
+    pub shipping_amount: Decimal,
+    pub currency: String,
     pub payment_status: String,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::bill_adjustments::Entity")]
+    BillAdjustments,
     #[sea_orm(
         belongs_to = "super::orders::Entity",
         from = "(Column::OrderId, Column::OrderId)",
@@ -25,6 +32,16 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     Orders,
+    #[sea_orm(has_many = "super::payment_intents::Entity")]
+    PaymentIntents,
+    #[sea_orm(has_many = "super::refunds::Entity")]
+    Refunds,
+}
+
+impl Related<super::bill_adjustments::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::BillAdjustments.def()
+    }
 }
 
 impl Related<super::orders::Entity> for Entity {
@@ -33,4 +50,16 @@ impl Related<super::orders::Entity> for Entity {
     }
 }
 
+impl Related<super::payment_intents::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PaymentIntents.def()
+    }
+}
+
+impl Related<super::refunds::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Refunds.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
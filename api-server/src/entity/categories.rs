@@ -9,6 +9,9 @@ pub struct Model {
     pub category_id: i32,
     pub name: String,
     pub parent_category_id: Option<i32>,
+    pub tax_class: String,
+    #[sea_orm(column_type = "Decimal(Some((5, 2)))", nullable)]
+    pub commission_rate: Option<Decimal>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
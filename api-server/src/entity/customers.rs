@@ -12,20 +12,30 @@ pub struct Model {
     pub registration_date: Option<DateTimeWithTimeZone>,
     #[sea_orm(unique)]
     pub user_id: i32,
+    #[sea_orm(column_type = "Decimal(Some((10, 2)))")]
+    pub wallet_balance: Decimal,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
     #[sea_orm(has_many = "super::addresses::Entity")]
     Addresses,
+    #[sea_orm(has_many = "super::gift_cards::Entity")]
+    GiftCards,
     #[sea_orm(has_many = "super::orders::Entity")]
     Orders,
     #[sea_orm(has_one = "super::payment_methods::Entity")]
     PaymentMethods,
+    #[sea_orm(has_many = "super::return_requests::Entity")]
+    ReturnRequests,
     #[sea_orm(has_many = "super::reviews::Entity")]
     Reviews,
     #[sea_orm(has_many = "super::shopping_carts::Entity")]
     ShoppingCarts,
+    #[sea_orm(has_many = "super::stock_reservations::Entity")]
+    StockReservations,
+    #[sea_orm(has_many = "super::stock_subscriptions::Entity")]
+    StockSubscriptions,
     #[sea_orm(
         belongs_to = "super::users::Entity",
         from = "Column::UserId",
@@ -34,6 +44,8 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     Users,
+    #[sea_orm(has_many = "super::wallet_transactions::Entity")]
+    WalletTransactions,
 }
 
 impl Related<super::addresses::Entity> for Entity {
@@ -42,6 +54,12 @@ impl Related<super::addresses::Entity> for Entity {
     }
 }
 
+impl Related<super::gift_cards::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::GiftCards.def()
+    }
+}
+
 impl Related<super::orders::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Orders.def()
@@ -54,6 +72,12 @@ impl Related<super::payment_methods::Entity> for Entity {
     }
 }
 
+impl Related<super::return_requests::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ReturnRequests.def()
+    }
+}
+
 impl Related<super::reviews::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Reviews.def()
@@ -66,10 +90,28 @@ impl Related<super::shopping_carts::Entity> for Entity {
     }
 }
 
+impl Related<super::stock_reservations::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::StockReservations.def()
+    }
+}
+
+impl Related<super::stock_subscriptions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::StockSubscriptions.def()
+    }
+}
+
 impl Related<super::users::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Users.def()
     }
 }
 
+impl Related<super::wallet_transactions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::WalletTransactions.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -4,18 +4,43 @@ pub mod prelude;
 
 pub mod address_types;
 pub mod addresses;
+pub mod bill_adjustments;
 pub mod bills;
 pub mod card_types;
 pub mod cart_items;
 pub mod categories;
 pub mod customers;
 pub mod discounts;
+pub mod exchange_rates;
+pub mod gift_card_transactions;
+pub mod gift_cards;
+pub mod order_item_taxes;
 pub mod order_items;
+pub mod order_shipping_charges;
 pub mod orders;
+pub mod payment_events;
+pub mod payment_intents;
 pub mod payment_methods;
+pub mod product_variant_options;
 pub mod products;
+pub mod refunds;
+pub mod return_items;
+pub mod return_requests;
 pub mod reviews;
 pub mod sea_orm_active_enums;
+pub mod shipment_events;
+pub mod shipments;
+pub mod shipping_profiles;
 pub mod shopping_carts;
+pub mod stock_movements;
+pub mod stock_reservations;
+pub mod stock_subscriptions;
+pub mod supplier_ledger_entries;
+pub mod supplier_notifications;
+pub mod supplier_payouts;
 pub mod suppliers;
+pub mod tax_rules;
 pub mod users;
+pub mod wallet_transactions;
+pub mod warehouse_stock;
+pub mod warehouses;
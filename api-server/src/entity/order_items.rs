@@ -14,10 +14,17 @@ pub struct Model {
     pub unit_price: Decimal,
     #[sea_orm(column_type = "Decimal(Some((10, 2)))")]
     pub discount_amount: Decimal,
+    #[sea_orm(column_type = "Decimal(Some((10, 2)))")]
+    pub tax_amount: Decimal,
+    pub warehouse_id: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::gift_cards::Entity")]
+    GiftCards,
+    #[sea_orm(has_many = "super::order_item_taxes::Entity")]
+    OrderItemTaxes,
     #[sea_orm(
         belongs_to = "super::orders::Entity",
         from = "(Column::OrderId, Column::OrderId)",
@@ -34,6 +41,30 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     Products,
+    #[sea_orm(has_many = "super::return_items::Entity")]
+    ReturnItems,
+    #[sea_orm(has_many = "super::supplier_ledger_entries::Entity")]
+    SupplierLedgerEntries,
+    #[sea_orm(
+        belongs_to = "super::warehouses::Entity",
+        from = "Column::WarehouseId",
+        to = "super::warehouses::Column::WarehouseId",
+        on_update = "NoAction",
+        on_delete = "SetNull"
+    )]
+    Warehouses,
+}
+
+impl Related<super::gift_cards::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::GiftCards.def()
+    }
+}
+
+impl Related<super::order_item_taxes::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::OrderItemTaxes.def()
+    }
 }
 
 impl Related<super::orders::Entity> for Entity {
@@ -48,4 +79,22 @@ impl Related<super::products::Entity> for Entity {
     }
 }
 
+impl Related<super::return_items::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ReturnItems.def()
+    }
+}
+
+impl Related<super::supplier_ledger_entries::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::SupplierLedgerEntries.def()
+    }
+}
+
+impl Related<super::warehouses::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Warehouses.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
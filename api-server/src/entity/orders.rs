@@ -11,10 +11,22 @@ pub struct Model {
     pub order_date: Option<DateTimeWithTimeZone>,
     #[sea_orm(column_type = "Decimal(Some((10, 2)))")]
     pub total_amount: Decimal,
+    #[sea_orm(column_type = "Decimal(Some((10, 2)))")]
+    pub tax_amount: Decimal,
+    #[sea_orm(column_type = "Decimal(Some((10, 2)))")]
+    pub shipping_amount: Decimal,
+    #[sea_orm(column_type = "Decimal(Some((10, 2)))")]
+    pub wallet_amount: Decimal,
+    #[sea_orm(column_type = "Decimal(Some((10, 2)))")]
+    pub gift_card_amount: Decimal,
+    pub currency: String,
+    #[sea_orm(column_type = "Decimal(Some((18, 8)))")]
+    pub exchange_rate: Decimal,
     pub status: String,
     pub shipping_address_id: i32,
     pub payment_method_id: i32,
     pub discount_id: Option<i32>,
+    pub idempotency_key: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -45,8 +57,14 @@ pub enum Relation {
         on_delete = "SetNull"
     )]
     Discounts,
+    #[sea_orm(has_many = "super::gift_card_transactions::Entity")]
+    GiftCardTransactions,
     #[sea_orm(has_many = "super::order_items::Entity")]
     OrderItems,
+    #[sea_orm(has_many = "super::order_shipping_charges::Entity")]
+    OrderShippingCharges,
+    #[sea_orm(has_many = "super::payment_intents::Entity")]
+    PaymentIntents,
     #[sea_orm(
         belongs_to = "super::payment_methods::Entity",
         from = "Column::PaymentMethodId",
@@ -55,6 +73,16 @@ pub enum Relation {
         on_delete = "Restrict"
     )]
     PaymentMethods,
+    #[sea_orm(has_many = "super::return_requests::Entity")]
+    ReturnRequests,
+    #[sea_orm(has_many = "super::shipments::Entity")]
+    Shipments,
+    #[sea_orm(has_many = "super::stock_reservations::Entity")]
+    StockReservations,
+    #[sea_orm(has_many = "super::supplier_ledger_entries::Entity")]
+    SupplierLedgerEntries,
+    #[sea_orm(has_many = "super::wallet_transactions::Entity")]
+    WalletTransactions,
 }
 
 impl Related<super::addresses::Entity> for Entity {
@@ -81,16 +109,64 @@ impl Related<super::discounts::Entity> for Entity {
     }
 }
 
+impl Related<super::gift_card_transactions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::GiftCardTransactions.def()
+    }
+}
+
 impl Related<super::order_items::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::OrderItems.def()
     }
 }
 
+impl Related<super::order_shipping_charges::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::OrderShippingCharges.def()
+    }
+}
+
+impl Related<super::payment_intents::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PaymentIntents.def()
+    }
+}
+
 impl Related<super::payment_methods::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::PaymentMethods.def()
     }
 }
 
+impl Related<super::return_requests::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ReturnRequests.def()
+    }
+}
+
+impl Related<super::shipments::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Shipments.def()
+    }
+}
+
+impl Related<super::stock_reservations::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::StockReservations.def()
+    }
+}
+
+impl Related<super::supplier_ledger_entries::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::SupplierLedgerEntries.def()
+    }
+}
+
+impl Related<super::wallet_transactions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::WalletTransactions.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -14,7 +14,9 @@ pub struct Model {
     pub is_default: Option<bool>,
     pub bank_name: Option<String>,
     pub account_holder_name: Option<String>,
-    pub card_number: Option<String>,
+    pub card_token: Option<String>,
+    pub card_last4: Option<String>,
+    pub card_brand: Option<String>,
     pub card_expiration_date: Option<Date>,
     pub iban: Option<String>,
     pub upi_id: Option<String>,
@@ -43,6 +45,8 @@ pub enum Relation {
     Customers,
     #[sea_orm(has_many = "super::orders::Entity")]
     Orders,
+    #[sea_orm(has_many = "super::refunds::Entity")]
+    Refunds,
 }
 
 impl Related<super::card_types::Entity> for Entity {
@@ -63,4 +67,10 @@ impl Related<super::orders::Entity> for Entity {
     }
 }
 
+impl Related<super::refunds::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Refunds.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -2,17 +2,42 @@
 
 pub use super::address_types::Entity as AddressTypes;
 pub use super::addresses::Entity as Addresses;
+pub use super::bill_adjustments::Entity as BillAdjustments;
 pub use super::bills::Entity as Bills;
 pub use super::card_types::Entity as CardTypes;
 pub use super::cart_items::Entity as CartItems;
 pub use super::categories::Entity as Categories;
 pub use super::customers::Entity as Customers;
 pub use super::discounts::Entity as Discounts;
+pub use super::exchange_rates::Entity as ExchangeRates;
+pub use super::gift_card_transactions::Entity as GiftCardTransactions;
+pub use super::gift_cards::Entity as GiftCards;
+pub use super::order_item_taxes::Entity as OrderItemTaxes;
 pub use super::order_items::Entity as OrderItems;
+pub use super::order_shipping_charges::Entity as OrderShippingCharges;
 pub use super::orders::Entity as Orders;
+pub use super::payment_events::Entity as PaymentEvents;
+pub use super::payment_intents::Entity as PaymentIntents;
 pub use super::payment_methods::Entity as PaymentMethods;
+pub use super::product_variant_options::Entity as ProductVariantOptions;
 pub use super::products::Entity as Products;
+pub use super::refunds::Entity as Refunds;
+pub use super::return_items::Entity as ReturnItems;
+pub use super::return_requests::Entity as ReturnRequests;
 pub use super::reviews::Entity as Reviews;
+pub use super::shipment_events::Entity as ShipmentEvents;
+pub use super::shipments::Entity as Shipments;
+pub use super::shipping_profiles::Entity as ShippingProfiles;
 pub use super::shopping_carts::Entity as ShoppingCarts;
+pub use super::stock_movements::Entity as StockMovements;
+pub use super::stock_reservations::Entity as StockReservations;
+pub use super::stock_subscriptions::Entity as StockSubscriptions;
+pub use super::supplier_ledger_entries::Entity as SupplierLedgerEntries;
+pub use super::supplier_notifications::Entity as SupplierNotifications;
+pub use super::supplier_payouts::Entity as SupplierPayouts;
 pub use super::suppliers::Entity as Suppliers;
+pub use super::tax_rules::Entity as TaxRules;
 pub use super::users::Entity as Users;
+pub use super::wallet_transactions::Entity as WalletTransactions;
+pub use super::warehouse_stock::Entity as WarehouseStock;
+pub use super::warehouses::Entity as Warehouses;
@@ -1,4 +1,4 @@
-//! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.1
+//! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.2
 
 use sea_orm::entity::prelude::*;
 
@@ -18,8 +18,8 @@ pub struct Model {
 pub enum Relation {
     #[sea_orm(
         belongs_to = "super::products::Entity",
-        from = "(Column::ProductId, Column::ProductId, Column::ProductId)",
-        to = "(super::products::Column::ProductId, super::products::Column::ProductId, super::products::Column::ProductId)",
+        from = "Column::ProductId",
+        to = "super::products::Column::ProductId",
         on_update = "NoAction",
         on_delete = "Cascade"
     )]
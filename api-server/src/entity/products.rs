@@ -12,12 +12,21 @@ pub struct Model {
     pub description: Option<String>,
     #[sea_orm(column_type = "Decimal(Some((10, 2)))")]
     pub base_price: Decimal,
+    pub currency: String,
     pub category_id: Option<i32>,
     pub supplier_id: Option<i32>,
     pub stock_quantity: i32,
     pub base_product_id: Option<i32>,
     pub media_paths: Option<Vec<String>>,
     pub created_at: Option<DateTimeWithTimeZone>,
+    pub weight_grams: Option<i32>,
+    pub length_mm: Option<i32>,
+    pub width_mm: Option<i32>,
+    pub height_mm: Option<i32>,
+    pub is_gift_card: bool,
+    pub reorder_threshold: Option<i32>,
+    pub status: String,
+    pub sku: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -36,6 +45,8 @@ pub enum Relation {
     Discounts,
     #[sea_orm(has_many = "super::order_items::Entity")]
     OrderItems,
+    #[sea_orm(has_many = "super::product_variant_options::Entity")]
+    ProductVariantOptions,
     #[sea_orm(
         belongs_to = "Entity",
         from = "Column::BaseProductId",
@@ -46,6 +57,14 @@ pub enum Relation {
     SelfRef,
     #[sea_orm(has_many = "super::reviews::Entity")]
     Reviews,
+    #[sea_orm(has_many = "super::stock_movements::Entity")]
+    StockMovements,
+    #[sea_orm(has_many = "super::stock_reservations::Entity")]
+    StockReservations,
+    #[sea_orm(has_many = "super::stock_subscriptions::Entity")]
+    StockSubscriptions,
+    #[sea_orm(has_many = "super::supplier_notifications::Entity")]
+    SupplierNotifications,
     #[sea_orm(
         belongs_to = "super::suppliers::Entity",
         from = "Column::SupplierId",
@@ -54,6 +73,8 @@ pub enum Relation {
         on_delete = "SetNull"
     )]
     Suppliers,
+    #[sea_orm(has_many = "super::warehouse_stock::Entity")]
+    WarehouseStock,
 }
 
 impl Related<super::cart_items::Entity> for Entity {
@@ -80,16 +101,52 @@ impl Related<super::order_items::Entity> for Entity {
     }
 }
 
+impl Related<super::product_variant_options::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProductVariantOptions.def()
+    }
+}
+
 impl Related<super::reviews::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Reviews.def()
     }
 }
 
+impl Related<super::stock_movements::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::StockMovements.def()
+    }
+}
+
+impl Related<super::stock_reservations::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::StockReservations.def()
+    }
+}
+
+impl Related<super::stock_subscriptions::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::StockSubscriptions.def()
+    }
+}
+
+impl Related<super::supplier_notifications::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::SupplierNotifications.def()
+    }
+}
+
 impl Related<super::suppliers::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Suppliers.def()
     }
 }
 
+impl Related<super::warehouse_stock::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::WarehouseStock.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -11,16 +11,22 @@ use sea_orm::entity::prelude::*;
 pub enum PaymentMethodType {
     #[sea_orm(string_value = "card")]
     Card,
+    #[sea_orm(string_value = "cod")]
+    Cod,
     #[sea_orm(string_value = "iban")]
     Iban,
     #[sea_orm(string_value = "netbanking")]
     Netbanking,
     #[sea_orm(string_value = "upi")]
     Upi,
+    #[sea_orm(string_value = "wallet")]
+    Wallet,
 }
 #[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
 #[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "user_role")]
 pub enum UserRole {
+    #[sea_orm(string_value = "admin")]
+    Admin,
     #[sea_orm(string_value = "customer")]
     Customer,
     #[sea_orm(string_value = "supplier")]
@@ -12,12 +12,29 @@ pub struct Model {
     pub contact_phone: Option<String>,
     #[sea_orm(unique)]
     pub user_id: i32,
+    pub cod_enabled: bool,
+    #[sea_orm(column_type = "Decimal(Some((10, 2)))", nullable)]
+    pub cod_limit: Option<Decimal>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::order_shipping_charges::Entity")]
+    OrderShippingCharges,
     #[sea_orm(has_many = "super::products::Entity")]
     Products,
+    #[sea_orm(has_many = "super::return_requests::Entity")]
+    ReturnRequests,
+    #[sea_orm(has_many = "super::shipments::Entity")]
+    Shipments,
+    #[sea_orm(has_many = "super::shipping_profiles::Entity")]
+    ShippingProfiles,
+    #[sea_orm(has_many = "super::supplier_ledger_entries::Entity")]
+    SupplierLedgerEntries,
+    #[sea_orm(has_many = "super::supplier_notifications::Entity")]
+    SupplierNotifications,
+    #[sea_orm(has_many = "super::supplier_payouts::Entity")]
+    SupplierPayouts,
     #[sea_orm(
         belongs_to = "super::users::Entity",
         from = "Column::UserId",
@@ -26,6 +43,14 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     Users,
+    #[sea_orm(has_many = "super::warehouses::Entity")]
+    Warehouses,
+}
+
+impl Related<super::order_shipping_charges::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::OrderShippingCharges.def()
+    }
 }
 
 impl Related<super::products::Entity> for Entity {
@@ -34,10 +59,52 @@ impl Related<super::products::Entity> for Entity {
     }
 }
 
+impl Related<super::return_requests::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ReturnRequests.def()
+    }
+}
+
+impl Related<super::shipments::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Shipments.def()
+    }
+}
+
+impl Related<super::shipping_profiles::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ShippingProfiles.def()
+    }
+}
+
+impl Related<super::supplier_ledger_entries::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::SupplierLedgerEntries.def()
+    }
+}
+
+impl Related<super::supplier_notifications::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::SupplierNotifications.def()
+    }
+}
+
+impl Related<super::supplier_payouts::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::SupplierPayouts.def()
+    }
+}
+
 impl Related<super::users::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Users.def()
     }
 }
 
+impl Related<super::warehouses::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Warehouses.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
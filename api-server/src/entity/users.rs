@@ -20,6 +20,8 @@ pub struct Model {
 pub enum Relation {
     #[sea_orm(has_one = "super::customers::Entity")]
     Customers,
+    #[sea_orm(has_many = "super::stock_movements::Entity")]
+    StockMovements,
     #[sea_orm(has_one = "super::suppliers::Entity")]
     Suppliers,
 }
@@ -30,6 +32,12 @@ impl Related<super::customers::Entity> for Entity {
     }
 }
 
+impl Related<super::stock_movements::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::StockMovements.def()
+    }
+}
+
 impl Related<super::suppliers::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Suppliers.def()
@@ -2,7 +2,7 @@ use crate::{
     auth::{RoleGuard, ROLE_CUSTOMER},
     graphql::macros::role_guard,
     models::{
-        products::{check_product_exists, Products},
+        products::{check_product_exists, check_product_on_sale, Products},
         user::get_customer_supplier_id,
     },
 };
@@ -87,7 +87,7 @@ impl CartsMutation {
 
         let customer_id = get_customer_supplier_id(db, token, ROLE_CUSTOMER).await?;
 
-        check_product_exists(&txn, product_id).await?;
+        check_product_on_sale(&txn, product_id).await?;
 
         let cart = match ShoppingCartsEntity::find()
             .filter(shopping_carts::Column::CustomerId.eq(customer_id))
@@ -137,7 +137,7 @@ impl CartsMutation {
 
         let customer_id = get_customer_supplier_id(db, token, ROLE_CUSTOMER).await?;
 
-        check_product_exists(&txn, product_id).await?;
+        check_product_on_sale(&txn, product_id).await?;
 
         let cart = ShoppingCartsEntity::find_by_id(cart_id)
             .one(&txn)
@@ -0,0 +1,76 @@
+use crate::{
+    auth::{RoleGuard, ROLE_ADMIN},
+    graphql::macros::role_guard,
+    models::currency::{parse_exchange_rates, save_exchange_rate, ExchangeRates},
+};
+use async_graphql::{Context, Object, Upload};
+use sea_orm::{prelude::Decimal, DatabaseConnection, EntityTrait, QueryOrder, TransactionTrait};
+use std::io::Read;
+
+#[derive(Default)]
+pub struct CurrencyQuery;
+
+#[derive(Default)]
+pub struct CurrencyMutation;
+
+#[Object]
+impl CurrencyQuery {
+    async fn exchange_rates(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<Vec<ExchangeRates>, async_graphql::Error> {
+        use crate::entity::{exchange_rates, prelude::ExchangeRates as ExchangeRatesEntity};
+        let db = ctx.data::<DatabaseConnection>()?;
+
+        let exchange_rates = ExchangeRatesEntity::find()
+            .order_by_asc(exchange_rates::Column::Currency)
+            .all(db)
+            .await?;
+
+        Ok(exchange_rates
+            .into_iter()
+            .map(|exchange_rate| exchange_rate.into())
+            .collect())
+    }
+}
+
+#[Object]
+impl CurrencyMutation {
+    #[graphql(guard = "role_guard!(ROLE_ADMIN)")]
+    async fn set_exchange_rate(
+        &self,
+        ctx: &Context<'_>,
+        currency: String,
+        rate: Decimal,
+    ) -> Result<ExchangeRates, async_graphql::Error> {
+        let db = ctx.data::<DatabaseConnection>()?;
+
+        Ok(save_exchange_rate(db, &currency, rate).await?.into())
+    }
+
+    // the whole file is applied in one transaction, a bad line leaves the rates untouched
+    #[graphql(guard = "role_guard!(ROLE_ADMIN)")]
+    async fn import_exchange_rates(
+        &self,
+        ctx: &Context<'_>,
+        file: Upload,
+    ) -> Result<Vec<ExchangeRates>, async_graphql::Error> {
+        let db = ctx.data::<DatabaseConnection>()?;
+
+        let mut content = String::new();
+        file.value(ctx)?.into_read().read_to_string(&mut content)?;
+
+        let rates = parse_exchange_rates(&content)?;
+
+        let txn = db.begin().await?;
+
+        let mut exchange_rates = Vec::new();
+        for (currency, rate) in rates {
+            exchange_rates.push(save_exchange_rate(&txn, &currency, rate).await?.into());
+        }
+
+        txn.commit().await?;
+
+        Ok(exchange_rates)
+    }
+}
@@ -0,0 +1,151 @@
+use crate::{
+    auth::{RoleGuard, ROLE_ADMIN, ROLE_CUSTOMER},
+    graphql::macros::role_guard,
+    models::{
+        gift_cards::{issue_gift_card, normalize_gift_card_code, GiftCardTransactions, GiftCards},
+        user::get_customer_supplier_id,
+    },
+    money::Money,
+};
+use async_graphql::{Context, Object};
+use sea_orm::{
+    prelude::Decimal, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+    TransactionTrait,
+};
+
+#[derive(Default)]
+pub struct GiftCardsQuery;
+
+#[derive(Default)]
+pub struct GiftCardsMutation;
+
+#[Object]
+impl GiftCardsQuery {
+    // anyone holding the code can check what is left on it
+    #[graphql(guard = "role_guard!(ROLE_CUSTOMER)")]
+    async fn gift_card(
+        &self,
+        ctx: &Context<'_>,
+        code: String,
+    ) -> Result<GiftCards, async_graphql::Error> {
+        use crate::entity::{gift_cards, prelude::GiftCards as GiftCardsEntity};
+        let db = ctx.data::<DatabaseConnection>()?;
+
+        let gift_card = GiftCardsEntity::find()
+            .filter(gift_cards::Column::Code.eq(normalize_gift_card_code(&code)))
+            .one(db)
+            .await?
+            .ok_or("Gift card not found")?;
+
+        Ok(gift_card.into())
+    }
+
+    #[graphql(guard = "role_guard!(ROLE_CUSTOMER)")]
+    async fn gift_card_transactions(
+        &self,
+        ctx: &Context<'_>,
+        code: String,
+    ) -> Result<Vec<GiftCardTransactions>, async_graphql::Error> {
+        use crate::entity::{
+            gift_card_transactions, gift_cards,
+            prelude::{
+                GiftCardTransactions as GiftCardTransactionsEntity, GiftCards as GiftCardsEntity,
+            },
+        };
+        let db = ctx.data::<DatabaseConnection>()?;
+
+        let gift_card = GiftCardsEntity::find()
+            .filter(gift_cards::Column::Code.eq(normalize_gift_card_code(&code)))
+            .one(db)
+            .await?
+            .ok_or("Gift card not found")?;
+
+        let gift_card_transactions = GiftCardTransactionsEntity::find()
+            .filter(gift_card_transactions::Column::GiftCardId.eq(gift_card.gift_card_id))
+            .order_by_desc(gift_card_transactions::Column::CreatedAt)
+            .all(db)
+            .await?;
+
+        Ok(gift_card_transactions
+            .into_iter()
+            .map(|gift_card_transaction| {
+                (gift_card_transaction, gift_card.currency.as_str()).into()
+            })
+            .collect())
+    }
+
+    // cards bought by the customer or given to them as store credit
+    #[graphql(guard = "role_guard!(ROLE_CUSTOMER)")]
+    async fn my_gift_cards(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<Vec<GiftCards>, async_graphql::Error> {
+        use crate::entity::{gift_cards, prelude::GiftCards as GiftCardsEntity};
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+
+        let customer_id = get_customer_supplier_id(db, token, ROLE_CUSTOMER).await?;
+
+        let gift_cards = GiftCardsEntity::find()
+            .filter(gift_cards::Column::CustomerId.eq(customer_id))
+            .order_by_desc(gift_cards::Column::CreatedAt)
+            .all(db)
+            .await?;
+
+        Ok(gift_cards
+            .into_iter()
+            .map(|gift_card| gift_card.into())
+            .collect())
+    }
+
+    #[graphql(guard = "role_guard!(ROLE_ADMIN)")]
+    async fn gift_cards(&self, ctx: &Context<'_>) -> Result<Vec<GiftCards>, async_graphql::Error> {
+        use crate::entity::{gift_cards, prelude::GiftCards as GiftCardsEntity};
+        let db = ctx.data::<DatabaseConnection>()?;
+
+        let gift_cards = GiftCardsEntity::find()
+            .order_by_desc(gift_cards::Column::CreatedAt)
+            .all(db)
+            .await?;
+
+        Ok(gift_cards
+            .into_iter()
+            .map(|gift_card| gift_card.into())
+            .collect())
+    }
+}
+
+#[Object]
+impl GiftCardsMutation {
+    #[graphql(guard = "role_guard!(ROLE_ADMIN)")]
+    async fn issue_gift_card(
+        &self,
+        ctx: &Context<'_>,
+        amount: Money,
+        customer_id: Option<i32>,
+        note: Option<String>,
+    ) -> Result<GiftCards, async_graphql::Error> {
+        use crate::entity::prelude::Customers as CustomersEntity;
+        let db = ctx.data::<DatabaseConnection>()?;
+        let txn = db.begin().await?;
+
+        if amount.amount <= Decimal::ZERO {
+            return Err("Amount must be greater than zero".into());
+        }
+
+        if let Some(customer_id) = customer_id {
+            CustomersEntity::find_by_id(customer_id)
+                .one(&txn)
+                .await?
+                .ok_or("Customer not found")?;
+        }
+
+        let gift_card = issue_gift_card(&txn, &amount, customer_id, None, None, note).await?;
+
+        txn.commit().await?;
+
+        Ok(gift_card.into())
+    }
+}
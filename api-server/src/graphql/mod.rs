@@ -1,10 +1,23 @@
 mod addresses_objects;
 mod carts_objects;
+mod currency_objects;
+mod gift_cards_objects;
 mod orders_objects;
+mod payment_intents_objects;
 mod payments_objects;
 mod products_objects;
+mod returns_objects;
 pub mod schema;
+mod shipments_objects;
+mod shipping_objects;
+mod stock_alerts_objects;
+mod stock_movements_objects;
+mod supplier_analytics_objects;
+mod supplier_ledger_objects;
+mod taxes_objects;
 mod users_objects;
+mod wallet_objects;
+mod warehouses_objects;
 
 pub mod macros {
     macro_rules! role_guard {
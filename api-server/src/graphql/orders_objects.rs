@@ -1,17 +1,56 @@
 use crate::{
-    auth::{RoleGuard, ROLE_CUSTOMER, ROLE_SUPPLIER},
+    auth::{Auth, RoleGuard, ROLE_CUSTOMER, ROLE_SUPPLIER},
+    entity::{orders::Model as OrdersModel, payment_intents::Model as PaymentIntentsModel},
     graphql::macros::role_guard,
     models::{
-        bills::Bills,
-        orders::{Orders, RegisterOrder},
-        products::Products,
+        bills::{
+            BillAdjustments, Bills, BILL_ADJUSTMENT_PARTIAL_CANCELLATION, BILL_STATUS_PAID,
+            BILL_STATUS_PENDING,
+        },
+        currency::{convert, exchange_rate},
+        gift_cards::{
+            change_gift_card_balance, issue_purchased_gift_cards, normalize_gift_card_code,
+            return_to_gift_card, revoke_purchased_gift_cards,
+        },
+        orders::{
+            check_if_supplier_in_order, discount_applies, order_total, spread_discount,
+            CancelOrderItem, IdempotencyKey, Orders, RegisterOrder, RegisterOrderItem,
+            IDEMPOTENCY_WINDOW_HOURS, ORDER_STATUS_CANCELLED, ORDER_STATUS_PAID,
+            ORDER_STATUS_PENDING,
+        },
+        payment_intents::{
+            cancellation_refund, live_payment_intent, void_cancelled_payment,
+            PAYMENT_INTENT_STATUS_AUTHORIZED,
+        },
+        products::{check_product_on_sale, increment_stock, Products},
+        reservations::{
+            release_reservation, reserve_stock, take_stock, StockReservations,
+            RESERVATION_STATUS_ACTIVE,
+        },
+        shipping::{lines_by_supplier, supplier_delivery_options},
+        stock_movements::{
+            link_stock_movements, StockMovement, STOCK_MOVEMENT_CANCELLATION, STOCK_REFERENCE_ORDER,
+        },
+        supplier_ledger::{
+            record_order_sales, remove_order_item_discount, reverse_order_item_sale,
+            reverse_order_shipping,
+        },
+        taxes::{line_taxes, product_tax_class, retax_order_item, BillTaxes},
         user::get_customer_supplier_id,
+        wallet::{
+            change_wallet_balance, WALLET_TRANSACTION_ORDER_PAYMENT,
+            WALLET_TRANSACTION_ORDER_REFUND,
+        },
     },
+    money::{currency_code, prorate, Money, DEFAULT_CURRENCY},
+    payment_gateway::SharedPaymentProvider,
 };
 use async_graphql::{Context, Object};
+use chrono::{Duration, Utc};
 use sea_orm::{
     prelude::Decimal, ActiveModelTrait, ActiveValue::Set, ColumnTrait, DatabaseConnection,
-    EntityTrait, QueryFilter, TransactionTrait,
+    DatabaseTransaction, EntityTrait, ModelTrait, QueryFilter, QueryOrder, SqlErr,
+    TransactionTrait,
 };
 
 #[derive(Default)]
@@ -20,6 +59,87 @@ pub struct OrdersQuery;
 #[derive(Default)]
 pub struct OrdersMutation;
 
+// puts the stock back and gives back everything paid for an order that hasn't been shipped, an
+// authorized payment is handed back to be voided once the cancellation is committed
+async fn cancel_whole_order(
+    txn: &DatabaseTransaction,
+    order: OrdersModel,
+    user_id: i32,
+) -> Result<Option<PaymentIntentsModel>, async_graphql::Error> {
+    use crate::entity::{order_items, orders, prelude::Refunds as RefundsEntity};
+
+    // once shipped the goods are with the customer, they come back through a return instead
+    if order.status != ORDER_STATUS_PENDING && order.status != ORDER_STATUS_PAID {
+        return Err("Only orders that haven't been shipped can be cancelled".into());
+    }
+
+    let order_items_list = order_items::Entity::find()
+        .filter(order_items::Column::OrderId.eq(order.order_id))
+        .all(txn)
+        .await?;
+
+    for order_item in order_items_list {
+        revoke_purchased_gift_cards(txn, &order_item, order_item.quantity).await?;
+        increment_stock(
+            txn,
+            order_item.product_id,
+            order_item.quantity,
+            &StockMovement::new(STOCK_MOVEMENT_CANCELLATION)
+                .reference(STOCK_REFERENCE_ORDER, order.order_id)
+                .actor(Some(user_id))
+                .warehouse(order_item.warehouse_id),
+        )
+        .await?;
+        reverse_order_item_sale(txn, &order, &order_item, order_item.quantity).await?;
+    }
+    reverse_order_shipping(txn, &order).await?;
+
+    // an authorized payment is released once the cancellation is saved, a captured one is refunded
+    let mut authorized_payment = None;
+    if let Some(payment_intent) = live_payment_intent(txn, order.order_id).await? {
+        if payment_intent.status == PAYMENT_INTENT_STATUS_AUTHORIZED {
+            authorized_payment = Some(payment_intent);
+        } else {
+            let amount = payment_intent.captured_amount - payment_intent.refunded_amount;
+            if amount > Decimal::ZERO {
+                RefundsEntity::insert(cancellation_refund(
+                    &payment_intent,
+                    order.payment_method_id,
+                    amount,
+                ))
+                .exec(txn)
+                .await?;
+            }
+        }
+    }
+
+    if order.wallet_amount > Decimal::ZERO {
+        change_wallet_balance(
+            txn,
+            order.customer_id,
+            order.wallet_amount,
+            WALLET_TRANSACTION_ORDER_REFUND,
+            Some(order.order_id),
+            None,
+        )
+        .await?;
+    }
+
+    if order.gift_card_amount > Decimal::ZERO {
+        return_to_gift_card(txn, order.order_id, order.gift_card_amount).await?;
+    }
+
+    let mut order: orders::ActiveModel = order.into();
+
+    order.status = Set(ORDER_STATUS_CANCELLED.to_string());
+    order.wallet_amount = Set(Decimal::ZERO);
+    order.gift_card_amount = Set(Decimal::ZERO);
+
+    order.update(txn).await?;
+
+    Ok(authorized_payment)
+}
+
 #[Object]
 impl OrdersQuery {
     #[graphql(guard = "role_guard!(ROLE_CUSTOMER)")]
@@ -103,10 +223,184 @@ impl OrdersQuery {
 
         Ok(bills_list)
     }
+
+    #[graphql(guard = "role_guard!(ROLE_CUSTOMER)")]
+    async fn bill_adjustments(
+        &self,
+        ctx: &Context<'_>,
+        bill_id: i32,
+    ) -> Result<Vec<BillAdjustments>, async_graphql::Error> {
+        use crate::entity::{
+            bill_adjustments,
+            prelude::{
+                BillAdjustments as BillAdjustmentsEntity, Bills as BillsEntity,
+                Orders as OrdersEntity,
+            },
+        };
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+
+        let customer_id = get_customer_supplier_id(db, token, ROLE_CUSTOMER).await?;
+
+        let bill = BillsEntity::find_by_id(bill_id)
+            .one(db)
+            .await?
+            .ok_or("Bill not found")?;
+
+        let order = OrdersEntity::find_by_id(bill.order_id)
+            .one(db)
+            .await?
+            .ok_or("Order not found")?;
+
+        if order.customer_id != customer_id {
+            return Err("Unauthorized".into());
+        }
+
+        let adjustments = BillAdjustmentsEntity::find()
+            .filter(bill_adjustments::Column::BillId.eq(bill_id))
+            .all(db)
+            .await?;
+
+        Ok(adjustments
+            .into_iter()
+            .map(|adjustment| adjustment.into())
+            .collect())
+    }
+
+    // the bill's tax broken down by tax and rate, summed over the order lines
+    #[graphql(guard = "role_guard!(ROLE_CUSTOMER)")]
+    async fn bill_taxes(
+        &self,
+        ctx: &Context<'_>,
+        bill_id: i32,
+    ) -> Result<Vec<BillTaxes>, async_graphql::Error> {
+        use crate::entity::{
+            order_item_taxes, order_items,
+            prelude::{
+                Bills as BillsEntity, OrderItemTaxes as OrderItemTaxesEntity,
+                Orders as OrdersEntity,
+            },
+        };
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+
+        let customer_id = get_customer_supplier_id(db, token, ROLE_CUSTOMER).await?;
+
+        let bill = BillsEntity::find_by_id(bill_id)
+            .one(db)
+            .await?
+            .ok_or("Bill not found")?;
+
+        let order = OrdersEntity::find_by_id(bill.order_id)
+            .one(db)
+            .await?
+            .ok_or("Order not found")?;
+
+        if order.customer_id != customer_id {
+            return Err("Unauthorized".into());
+        }
+
+        let taxes = OrderItemTaxesEntity::find()
+            .inner_join(order_items::Entity)
+            .filter(order_items::Column::OrderId.eq(order.order_id))
+            .order_by_asc(order_item_taxes::Column::OrderItemTaxId)
+            .all(db)
+            .await?;
+
+        let mut breakdown: Vec<BillTaxes> = Vec::new();
+        for tax in taxes {
+            match breakdown
+                .iter_mut()
+                .find(|line| line.name == tax.name && line.rate == tax.rate)
+            {
+                Some(line) => {
+                    line.amount = Money::new(line.amount.amount + tax.amount, &bill.currency)
+                }
+                None => breakdown.push(BillTaxes {
+                    name: tax.name,
+                    rate: tax.rate,
+                    amount: Money::new(tax.amount, &bill.currency),
+                }),
+            }
+        }
+
+        Ok(breakdown)
+    }
 }
 
 #[Object]
 impl OrdersMutation {
+    // holds stock for the customer while they check out, unused holds expire on their own
+    #[graphql(guard = "role_guard!(ROLE_CUSTOMER)")]
+    async fn reserve_stock(
+        &self,
+        ctx: &Context<'_>,
+        items: Vec<RegisterOrderItem>,
+    ) -> Result<Vec<StockReservations>, async_graphql::Error> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+        let txn = db.begin().await?;
+
+        let customer_id = get_customer_supplier_id(db, token, ROLE_CUSTOMER).await?;
+        let user_id = Auth::verify_token(token)?.user_id.parse::<i32>()?;
+
+        let mut reservations = Vec::new();
+        for item in &items {
+            check_product_on_sale(&txn, item.product_id).await?;
+            let reservation = reserve_stock(
+                &txn,
+                customer_id,
+                item.product_id,
+                item.quantity,
+                Some(user_id),
+            )
+            .await?;
+            reservations.push(reservation.into());
+        }
+
+        txn.commit().await?;
+
+        Ok(reservations)
+    }
+
+    #[graphql(guard = "role_guard!(ROLE_CUSTOMER)")]
+    async fn release_stock_reservations(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<String, async_graphql::Error> {
+        use crate::entity::{
+            prelude::StockReservations as StockReservationsEntity, stock_reservations,
+        };
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+        let txn = db.begin().await?;
+
+        let customer_id = get_customer_supplier_id(db, token, ROLE_CUSTOMER).await?;
+        let user_id = Auth::verify_token(token)?.user_id.parse::<i32>()?;
+
+        let reservations = StockReservationsEntity::find()
+            .filter(stock_reservations::Column::CustomerId.eq(customer_id))
+            .filter(stock_reservations::Column::Status.eq(RESERVATION_STATUS_ACTIVE))
+            .all(&txn)
+            .await?;
+
+        for reservation in &reservations {
+            release_reservation(&txn, reservation, Some(user_id)).await?;
+        }
+
+        txn.commit().await?;
+
+        Ok("Stock reservations released".to_string())
+    }
+
     #[graphql(guard = "role_guard!(ROLE_CUSTOMER)")]
     async fn register_order(
         &self,
@@ -114,12 +408,17 @@ impl OrdersMutation {
         input: RegisterOrder,
     ) -> Result<Orders, async_graphql::Error> {
         use crate::entity::{
-            discounts, order_items, orders,
+            bills, discounts, gift_cards, order_item_taxes, order_items, order_shipping_charges,
+            orders,
             prelude::{
-                Discounts as DiscountsEntity, OrderItems as OrderItemsEntity,
-                Orders as OrdersEntity, Products as ProductsEntity,
+                Addresses as AddressesEntity, Bills as BillsEntity, Discounts as DiscountsEntity,
+                GiftCards as GiftCardsEntity, OrderItemTaxes as OrderItemTaxesEntity,
+                OrderItems as OrderItemsEntity, OrderShippingCharges as OrderShippingChargesEntity,
+                Orders as OrdersEntity, PaymentMethods as PaymentMethodsEntity,
+                StockReservations as StockReservationsEntity, Suppliers as SuppliersEntity,
             },
-            products,
+            sea_orm_active_enums::PaymentMethodType,
+            stock_reservations,
         };
         let db = ctx.data::<DatabaseConnection>()?;
         let token = ctx
@@ -128,89 +427,431 @@ impl OrdersMutation {
         let txn = db.begin().await?;
 
         let customer_id = get_customer_supplier_id(db, token, ROLE_CUSTOMER).await?;
+        let user_id = Auth::verify_token(token)?.user_id.parse::<i32>()?;
 
-        let discount_id = match &input.discount_code {
-            Some(discount_code) => ProductsEntity::find()
-                .filter(products::Column::Name.eq(discount_code))
-                .one(db)
-                .await
-                .map_err(|_| "Discount not found")?
-                .map(|product| product.product_id),
+        // the key can come either from the mutation input or from the Idempotency-Key header
+        let idempotency_key = input
+            .idempotency_key
+            .clone()
+            .or_else(|| ctx.data_opt::<IdempotencyKey>().map(|key| key.0.clone()));
+
+        if let Some(idempotency_key) = &idempotency_key {
+            if let Some(existing_order) = OrdersEntity::find()
+                .filter(orders::Column::CustomerId.eq(customer_id))
+                .filter(orders::Column::IdempotencyKey.eq(idempotency_key))
+                .one(&txn)
+                .await?
+            {
+                if existing_order.order_date.is_some_and(|order_date| {
+                    Utc::now().fixed_offset() - order_date
+                        < Duration::hours(IDEMPOTENCY_WINDOW_HOURS)
+                }) {
+                    return Ok(existing_order.into());
+                }
+
+                // the key has expired, release it so that it can be used for this order
+                let mut existing_order: orders::ActiveModel = existing_order.into();
+                existing_order.idempotency_key = Set(None);
+                existing_order.update(&txn).await?;
+            }
+        }
+
+        if input.order_items.is_empty() {
+            return Err("At least one item must be ordered".into());
+        }
+
+        let shipping_address = AddressesEntity::find_by_id(input.shipping_address_id)
+            .one(&txn)
+            .await?
+            .filter(|address| address.customer_id == customer_id)
+            .ok_or("Shipping address not found")?;
+
+        let payment_method = PaymentMethodsEntity::find_by_id(input.payment_method_id)
+            .one(&txn)
+            .await?
+            .filter(|payment_method| payment_method.customer_id == customer_id)
+            .ok_or("Payment method not found")?;
+
+        let currency = match &input.currency {
+            Some(currency) => currency_code(currency).ok_or("Invalid currency code")?,
+            None => DEFAULT_CURRENCY.to_string(),
+        };
+        // the rate is locked on the order, later changes to the exchange rates don't affect it
+        let exchange_rate = exchange_rate(&txn, &currency).await?;
+
+        let discount = match &input.discount_code {
+            Some(discount_code) => Some(
+                DiscountsEntity::find()
+                    .filter(discounts::Column::Code.eq(discount_code))
+                    .one(&txn)
+                    .await?
+                    .ok_or("Discount not found")?,
+            ),
             None => None,
         };
 
-        let mut total_amount: f64 = 0.0;
+        // stock is taken before the order is priced, and every decrement is conditional
+        // so two orders racing for the last units can't both get them
+        let mut order_lines = Vec::new();
+        let mut order_line_tax_classes = Vec::new();
+        let mut shipping_lines = Vec::new();
+        let mut consumed_reservations = Vec::new();
+        let mut sale_movements = Vec::new();
         for item in &input.order_items {
-            let product: products::Model = ProductsEntity::find_by_id(item.product_id)
-                .one(db)
-                .await
-                .map_err(|_| "Product not found")?
-                .unwrap();
-            total_amount += product.base_price.to_string().parse::<f64>()? * item.quantity as f64;
-        }
+            if item.quantity <= 0 {
+                return Err("Invalid quantity".into());
+            }
 
-        if let Some(discount_id) = discount_id {
-            let discount: discounts::Model = DiscountsEntity::find_by_id(discount_id)
-                .one(&txn)
-                .await?
-                .unwrap();
+            let product = check_product_on_sale(&txn, item.product_id).await?;
 
-            if discount.discount_type == "PERCENTAGE" {
-                total_amount -=
-                    total_amount * discount.discount_value.to_string().parse::<f64>()? / 100.0;
-            } else {
-                total_amount -= discount.discount_value.to_string().parse::<f64>()?;
+            let taken_stock = take_stock(
+                &txn,
+                customer_id,
+                item.product_id,
+                item.quantity,
+                Some(user_id),
+                &shipping_address,
+            )
+            .await?;
+            consumed_reservations.extend(taken_stock.reservation_id);
+            sale_movements.extend(taken_stock.stock_movement_ids);
+
+            let unit_price =
+                convert(&txn, product.base_price, &product.currency, &currency).await?;
+
+            order_lines.push(order_items::Model {
+                order_item_id: 0,
+                order_id: 0,
+                product_id: product.product_id,
+                quantity: item.quantity,
+                unit_price,
+                discount_amount: Decimal::ZERO,
+                tax_amount: Decimal::ZERO,
+                warehouse_id: taken_stock.warehouse_id,
+            });
+            order_line_tax_classes.push(product_tax_class(&txn, &product).await?);
+            shipping_lines.push((product, item.quantity));
+        }
+
+        if let Some(discount) = &discount {
+            if !discount_applies(discount, &order_lines) {
+                return Err("Discount does not apply to this order".into());
             }
 
             // increment discount usage
-            let mut discount: discounts::ActiveModel = discount.into();
-            discount.times_used = Set(Some(discount.times_used.unwrap().unwrap() + 1));
+            let mut update_discount: discounts::ActiveModel = discount.clone().into();
+            update_discount.times_used = Set(Some(discount.times_used.unwrap_or(0) + 1));
+            update_discount.update(&txn).await?;
+        }
+        spread_discount(&mut order_lines, discount.as_ref(), exchange_rate);
+
+        // tax is on what is paid for the line, after its share of the discount
+        let mut order_line_taxes = Vec::new();
+        for (line, tax_class) in order_lines.iter_mut().zip(&order_line_tax_classes) {
+            let taxes = line_taxes(
+                &txn,
+                &shipping_address,
+                tax_class,
+                line.unit_price * Decimal::from(line.quantity) - line.discount_amount,
+            )
+            .await?;
+            line.tax_amount = taxes.iter().map(|tax| tax.amount).sum();
+            order_line_taxes.push(taxes);
+        }
+
+        let suppliers = lines_by_supplier(shipping_lines);
+        let mut shipping_charges = Vec::new();
+        for (supplier_id, supplier_lines) in &suppliers {
+            let supplier_id = *supplier_id;
+            let options =
+                supplier_delivery_options(&txn, supplier_id, &shipping_address, supplier_lines)
+                    .await?;
+
+            // suppliers without shipping profiles ship for free
+            if options.is_empty() {
+                continue;
+            }
+
+            let (profile, cost) = options
+                .into_iter()
+                .find(|(profile, _)| {
+                    input
+                        .shipping_profile_ids
+                        .contains(&profile.shipping_profile_id)
+                })
+                .ok_or("Choose a delivery option for every supplier in the order")?;
+
+            shipping_charges.push(order_shipping_charges::Model {
+                order_shipping_charge_id: 0,
+                order_id: 0,
+                supplier_id: Some(supplier_id),
+                shipping_profile_id: Some(profile.shipping_profile_id),
+                amount: convert(&txn, cost, &profile.currency, &currency).await?,
+                name: profile.name,
+            });
+        }
+        let shipping_amount: Decimal = shipping_charges.iter().map(|charge| charge.amount).sum();
+
+        // cash is collected by every supplier for their own part, so each of them has to accept it
+        if payment_method.payment_type == PaymentMethodType::Cod {
+            for (supplier_id, supplier_lines) in &suppliers {
+                let supplier = SuppliersEntity::find_by_id(*supplier_id)
+                    .one(&txn)
+                    .await?
+                    .ok_or("Supplier not found")?;
+
+                if !supplier.cod_enabled {
+                    return Err(format!("{} doesn't accept cash on delivery", supplier.name).into());
+                }
+
+                let Some(cod_limit) = supplier.cod_limit else {
+                    continue;
+                };
+
+                let lines_amount: Decimal = order_lines
+                    .iter()
+                    .filter(|line| {
+                        supplier_lines
+                            .iter()
+                            .any(|(product, _)| product.product_id == line.product_id)
+                    })
+                    .map(|line| {
+                        line.unit_price * Decimal::from(line.quantity) - line.discount_amount
+                            + line.tax_amount
+                    })
+                    .sum();
+                let shipping: Decimal = shipping_charges
+                    .iter()
+                    .filter(|charge| charge.supplier_id == Some(*supplier_id))
+                    .map(|charge| charge.amount)
+                    .sum();
+
+                if convert(&txn, lines_amount + shipping, &currency, DEFAULT_CURRENCY).await?
+                    > cod_limit
+                {
+                    return Err(format!(
+                        "{} only accepts cash on delivery up to {} {}",
+                        supplier.name, cod_limit, DEFAULT_CURRENCY
+                    )
+                    .into());
+                }
+            }
+        }
+
+        let total_amount = order_total(&order_lines) + shipping_amount;
+
+        // a gift card is redeemed first, for the amount asked or as much as its balance covers
+        let gift_card = match &input.gift_card_code {
+            Some(code) => Some(
+                GiftCardsEntity::find()
+                    .filter(gift_cards::Column::Code.eq(normalize_gift_card_code(code)))
+                    .one(&txn)
+                    .await?
+                    .ok_or("Gift card not found")?,
+            ),
+            None => None,
+        };
+
+        let gift_card_amount = match &gift_card {
+            Some(gift_card) => {
+                if gift_card.currency != currency {
+                    return Err(format!(
+                        "This gift card can only pay for orders in {}",
+                        gift_card.currency
+                    )
+                    .into());
+                }
+
+                match &input.gift_card_amount {
+                    Some(gift_card_amount) => {
+                        if gift_card_amount.currency != currency {
+                            return Err("Gift card amount must be in the order currency".into());
+                        }
+                        if gift_card_amount.amount <= Decimal::ZERO
+                            || gift_card_amount.amount > gift_card.balance
+                            || gift_card_amount.amount > total_amount
+                        {
+                            return Err("Gift card amount must be more than zero and within both the card balance and the order total".into());
+                        }
+                        gift_card_amount.amount
+                    }
+                    None => gift_card.balance.min(total_amount),
+                }
+            }
+            None => Decimal::ZERO,
+        };
+
+        // the wallet pays for the rest of the order when it is the payment method, otherwise for as
+        // much as the customer chose and the payment method covers what is left
+        let wallet_amount = if payment_method.payment_type == PaymentMethodType::Wallet {
+            total_amount - gift_card_amount
+        } else {
+            input
+                .wallet_amount
+                .as_ref()
+                .map_or(Decimal::ZERO, |wallet_amount| wallet_amount.amount)
+        };
+
+        if wallet_amount > Decimal::ZERO
+            && (currency != DEFAULT_CURRENCY
+                || input
+                    .wallet_amount
+                    .as_ref()
+                    .is_some_and(|wallet_amount| wallet_amount.currency != DEFAULT_CURRENCY))
+        {
+            return Err(
+                format!("The wallet can only pay for orders in {}", DEFAULT_CURRENCY).into(),
+            );
         }
 
+        if wallet_amount < Decimal::ZERO || wallet_amount > total_amount - gift_card_amount {
+            return Err(
+                "Wallet amount must be between zero and what is left to pay on the order".into(),
+            );
+        }
+
+        let paid = wallet_amount + gift_card_amount == total_amount;
+
         let order = orders::ActiveModel {
             customer_id: Set(customer_id),
             shipping_address_id: Set(input.shipping_address_id),
             payment_method_id: Set(input.payment_method_id),
-            discount_id: Set(discount_id),
-            total_amount: Set(Decimal::from_str_exact(total_amount.to_string().as_str())?),
-            status: Set("PENDING".to_string()),
+            discount_id: Set(discount.as_ref().map(|discount| discount.discount_id)),
+            total_amount: Set(total_amount),
+            tax_amount: Set(order_lines.iter().map(|line| line.tax_amount).sum()),
+            shipping_amount: Set(shipping_amount),
+            wallet_amount: Set(wallet_amount),
+            gift_card_amount: Set(gift_card_amount),
+            currency: Set(currency.clone()),
+            exchange_rate: Set(exchange_rate),
+            status: Set(if paid {
+                ORDER_STATUS_PAID.to_string()
+            } else {
+                ORDER_STATUS_PENDING.to_string()
+            }),
+            idempotency_key: Set(idempotency_key.clone()),
             ..Default::default()
         };
 
-        let insert_order = OrdersEntity::insert(order)
-            .exec_with_returning(&txn)
+        let insert_order = match OrdersEntity::insert(order).exec_with_returning(&txn).await {
+            Ok(insert_order) => insert_order,
+            // a concurrent retry with the same key placed the order first
+            Err(e) if matches!(e.sql_err(), Some(SqlErr::UniqueConstraintViolation(_))) => {
+                txn.rollback().await?;
+                return Ok(OrdersEntity::find()
+                    .filter(orders::Column::CustomerId.eq(customer_id))
+                    .filter(orders::Column::IdempotencyKey.eq(idempotency_key))
+                    .one(db)
+                    .await?
+                    .ok_or("Order not found")?
+                    .into());
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        if wallet_amount > Decimal::ZERO {
+            change_wallet_balance(
+                &txn,
+                customer_id,
+                -wallet_amount,
+                WALLET_TRANSACTION_ORDER_PAYMENT,
+                Some(insert_order.order_id),
+                None,
+            )
             .await?;
+        }
 
-        for item in &input.order_items {
-            let product: products::Model = ProductsEntity::find_by_id(item.product_id)
-                .one(&txn)
-                .await?
-                .unwrap();
+        if let Some(gift_card) = gift_card.filter(|_| gift_card_amount > Decimal::ZERO) {
+            change_gift_card_balance(
+                &txn,
+                gift_card.gift_card_id,
+                -gift_card_amount,
+                Some(insert_order.order_id),
+            )
+            .await?;
+        }
 
-            if product.stock_quantity < item.quantity {
-                return Err("Insufficient stock".into());
-            }
+        for (line, taxes) in order_lines.into_iter().zip(order_line_taxes) {
+            let order_item = order_items::ActiveModel {
+                order_id: Set(insert_order.order_id),
+                product_id: Set(line.product_id),
+                quantity: Set(line.quantity),
+                unit_price: Set(line.unit_price),
+                discount_amount: Set(line.discount_amount),
+                tax_amount: Set(line.tax_amount),
+                warehouse_id: Set(line.warehouse_id),
+                ..Default::default()
+            };
+            let insert_order_item = OrderItemsEntity::insert(order_item)
+                .exec_with_returning(&txn)
+                .await?;
 
-            let product_base_price = product.base_price;
+            for tax in taxes {
+                let order_item_tax = order_item_taxes::ActiveModel {
+                    order_item_id: Set(insert_order_item.order_item_id),
+                    name: Set(tax.name),
+                    rate: Set(tax.rate),
+                    amount: Set(tax.amount),
+                    ..Default::default()
+                };
+                OrderItemTaxesEntity::insert(order_item_tax)
+                    .exec(&txn)
+                    .await?;
+            }
+        }
 
-            let product: products::ActiveModel = products::ActiveModel {
-                stock_quantity: Set(product.stock_quantity - item.quantity),
-                ..product.into()
+        for charge in shipping_charges {
+            let order_shipping_charge = order_shipping_charges::ActiveModel {
+                order_id: Set(insert_order.order_id),
+                supplier_id: Set(charge.supplier_id),
+                shipping_profile_id: Set(charge.shipping_profile_id),
+                name: Set(charge.name),
+                amount: Set(charge.amount),
+                ..Default::default()
             };
+            OrderShippingChargesEntity::insert(order_shipping_charge)
+                .exec(&txn)
+                .await?;
+        }
+
+        link_stock_movements(
+            &txn,
+            sale_movements,
+            STOCK_REFERENCE_ORDER,
+            insert_order.order_id,
+        )
+        .await?;
 
-            ProductsEntity::update(product)
-                .filter(products::Column::ProductId.eq(item.product_id))
+        if !consumed_reservations.is_empty() {
+            StockReservationsEntity::update_many()
+                .col_expr(
+                    stock_reservations::Column::OrderId,
+                    insert_order.order_id.into(),
+                )
+                .filter(stock_reservations::Column::ReservationId.is_in(consumed_reservations))
                 .exec(&txn)
                 .await?;
+        }
 
-            let order_item = order_items::ActiveModel {
-                order_id: Set(insert_order.order_id),
-                product_id: Set(item.product_id),
-                quantity: Set(item.quantity),
-                unit_price: Set(product_base_price),
-                ..Default::default()
-            };
-            OrderItemsEntity::insert(order_item).exec(&txn).await?;
+        // customers pay at the time of order, so the bill is generated along with it
+        let bill = bills::ActiveModel {
+            order_id: Set(insert_order.order_id),
+            total_amount: Set(insert_order.total_amount),
+            tax_amount: Set(insert_order.tax_amount),
+            shipping_amount: Set(insert_order.shipping_amount),
+            currency: Set(insert_order.currency.clone()),
+            payment_status: Set(if paid {
+                BILL_STATUS_PAID.to_string()
+            } else {
+                BILL_STATUS_PENDING.to_string()
+            }),
+            ..Default::default()
+        };
+        BillsEntity::insert(bill).exec(&txn).await?;
+
+        if paid {
+            issue_purchased_gift_cards(&txn, insert_order.order_id).await?;
+            record_order_sales(&txn, insert_order.order_id).await?;
         }
 
         txn.commit().await?;
@@ -225,23 +866,60 @@ impl OrdersMutation {
         order_id: i32,
         status: String,
     ) -> Result<String, async_graphql::Error> {
-        use crate::entity::{orders, prelude::Orders as OrdersEntity};
+        use crate::entity::{
+            order_items,
+            prelude::{
+                OrderItems as OrderItemsEntity, Orders as OrdersEntity, Products as ProductsEntity,
+            },
+            products,
+        };
         let db = ctx.data::<DatabaseConnection>()?;
+        let payment_provider = ctx.data::<SharedPaymentProvider>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
         let txn = db.begin().await?;
 
-        let order: orders::Model = OrdersEntity::find_by_id(order_id)
+        let supplier_id = get_customer_supplier_id(db, token, ROLE_SUPPLIER).await?;
+        let user_id = Auth::verify_token(token)?.user_id.parse::<i32>()?;
+
+        let order = OrdersEntity::find_by_id(order_id)
             .one(&txn)
-            .await
-            .map_err(|_| "Order not found")?
-            .unwrap();
+            .await?
+            .ok_or("Order not found")?;
+
+        check_if_supplier_in_order(&txn, supplier_id, order_id).await?;
 
-        let mut update_order: orders::ActiveModel = order.into();
-        update_order.status = Set(status);
+        // paying, shipping and delivering move the order on their own, all that is left to a
+        // supplier is cancelling an order they can't fulfil
+        if status != ORDER_STATUS_CANCELLED {
+            return Err("Suppliers can only cancel orders".into());
+        }
+
+        let other_suppliers_items = OrderItemsEntity::find()
+            .inner_join(ProductsEntity)
+            .filter(order_items::Column::OrderId.eq(order_id))
+            .filter(
+                products::Column::SupplierId
+                    .ne(supplier_id)
+                    .or(products::Column::SupplierId.is_null()),
+            )
+            .one(&txn)
+            .await?;
+        if other_suppliers_items.is_some() {
+            return Err(
+                "Orders with other suppliers' items can't be cancelled by a supplier".into(),
+            );
+        }
 
-        update_order.update(&txn).await?;
+        let authorized_payment = cancel_whole_order(&txn, order, user_id).await?;
 
         txn.commit().await?;
 
+        if let Some(payment_intent) = authorized_payment {
+            void_cancelled_payment(db, payment_provider, payment_intent).await?;
+        }
+
         Ok("Order status updated".to_string())
     }
 
@@ -251,18 +929,16 @@ impl OrdersMutation {
         ctx: &Context<'_>,
         order_id: i32,
     ) -> Result<String, async_graphql::Error> {
-        use crate::entity::{
-            order_items, orders,
-            prelude::{Orders as OrdersEntity, Products as ProductsEntity},
-            products,
-        };
+        use crate::entity::{orders, prelude::Orders as OrdersEntity};
         let db = ctx.data::<DatabaseConnection>()?;
+        let payment_provider = ctx.data::<SharedPaymentProvider>()?;
         let token = ctx
             .data_opt::<String>()
             .ok_or("No authorization token found")?;
         let txn = db.begin().await?;
 
         let customer_id = get_customer_supplier_id(db, token, ROLE_CUSTOMER).await?;
+        let user_id = Auth::verify_token(token)?.user_id.parse::<i32>()?;
 
         let order: orders::Model = OrdersEntity::find_by_id(order_id)
             .one(&txn)
@@ -274,40 +950,414 @@ impl OrdersMutation {
             return Err("Unauthorized".into());
         }
 
-        if order.status == "CANCELLED" {
-            return Err("Order already cancelled".into());
+        let authorized_payment = cancel_whole_order(&txn, order, user_id).await?;
+
+        txn.commit().await?;
+
+        // a failed void leaves the intent authorized on the cancelled order, the authorization lapses
+        // with the provider if it isn't released later
+        if let Some(payment_intent) = authorized_payment {
+            void_cancelled_payment(db, payment_provider, payment_intent).await?;
         }
 
-        let order_items_list = order_items::Entity::find()
-            .filter(order_items::Column::OrderId.eq(order_id))
-            .all(&txn)
-            .await?;
+        Ok("Order cancelled".to_string())
+    }
+
+    #[graphql(guard = "role_guard!(ROLE_CUSTOMER)")]
+    async fn cancel_order_items(
+        &self,
+        ctx: &Context<'_>,
+        order_id: i32,
+        items: Vec<CancelOrderItem>,
+    ) -> Result<Orders, async_graphql::Error> {
+        use crate::entity::{
+            bill_adjustments, bills, order_items, orders,
+            prelude::{
+                BillAdjustments as BillAdjustmentsEntity, Bills as BillsEntity,
+                Discounts as DiscountsEntity, OrderItems as OrderItemsEntity,
+                Orders as OrdersEntity, Refunds as RefundsEntity,
+            },
+        };
+        let db = ctx.data::<DatabaseConnection>()?;
+        let payment_provider = ctx.data::<SharedPaymentProvider>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+        let txn = db.begin().await?;
+
+        let customer_id = get_customer_supplier_id(db, token, ROLE_CUSTOMER).await?;
+        let user_id = Auth::verify_token(token)?.user_id.parse::<i32>()?;
+
+        let order = OrdersEntity::find_by_id(order_id)
+            .one(&txn)
+            .await?
+            .ok_or("Order not found")?;
+
+        if order.customer_id != customer_id {
+            return Err("Unauthorized".into());
+        }
+
+        if order.status != ORDER_STATUS_PENDING && order.status != ORDER_STATUS_PAID {
+            return Err("Items can only be cancelled before the order is shipped".into());
+        }
 
-        for order_item in order_items_list {
-            let product: products::Model = ProductsEntity::find_by_id(order_item.product_id)
+        if items.is_empty() {
+            return Err("At least one item must be cancelled".into());
+        }
+
+        for item in &items {
+            let order_item = OrderItemsEntity::find_by_id(item.order_item_id)
                 .one(&txn)
                 .await?
-                .unwrap();
+                .filter(|order_item| order_item.order_id == order_id)
+                .ok_or("Order item not found in this order")?;
 
-            let product: products::ActiveModel = products::ActiveModel {
-                stock_quantity: Set(product.stock_quantity + order_item.quantity),
-                ..product.into()
-            };
+            if item.quantity <= 0 || item.quantity > order_item.quantity {
+                return Err("Invalid quantity to cancel".into());
+            }
 
-            ProductsEntity::update(product)
-                .filter(products::Column::ProductId.eq(order_item.product_id))
-                .exec(&txn)
+            revoke_purchased_gift_cards(&txn, &order_item, item.quantity).await?;
+
+            increment_stock(
+                &txn,
+                order_item.product_id,
+                item.quantity,
+                &StockMovement::new(STOCK_MOVEMENT_CANCELLATION)
+                    .reference(STOCK_REFERENCE_ORDER, order_id)
+                    .actor(Some(user_id))
+                    .warehouse(order_item.warehouse_id),
+            )
+            .await?;
+            reverse_order_item_sale(&txn, &order, &order_item, item.quantity).await?;
+
+            if item.quantity == order_item.quantity {
+                order_item.delete(&txn).await?;
+            } else {
+                let remaining = order_item.quantity - item.quantity;
+
+                let discount_amount =
+                    prorate(order_item.discount_amount, remaining, order_item.quantity);
+                let tax_amount = retax_order_item(
+                    &txn,
+                    order_item.order_item_id,
+                    order_item.unit_price * Decimal::from(remaining) - discount_amount,
+                )
                 .await?;
+
+                let mut order_item: order_items::ActiveModel = order_item.into();
+                order_item.quantity = Set(remaining);
+                order_item.discount_amount = Set(discount_amount);
+                order_item.tax_amount = Set(tax_amount);
+                order_item.update(&txn).await?;
+            }
         }
 
+        let mut remaining_items = OrderItemsEntity::find()
+            .filter(order_items::Column::OrderId.eq(order_id))
+            .all(&txn)
+            .await?;
+
+        let discount = match order.discount_id {
+            Some(discount_id) => DiscountsEntity::find_by_id(discount_id).one(&txn).await?,
+            None => None,
+        }
+        .filter(|discount| discount_applies(discount, &remaining_items));
+
+        // what is left of the order pays full price once it no longer qualifies for the discount
+        if discount.is_none() {
+            for remaining_item in remaining_items
+                .iter_mut()
+                .filter(|remaining_item| !remaining_item.discount_amount.is_zero())
+            {
+                remove_order_item_discount(&txn, &order, remaining_item).await?;
+                remaining_item.discount_amount = Decimal::ZERO;
+                remaining_item.tax_amount = retax_order_item(
+                    &txn,
+                    remaining_item.order_item_id,
+                    remaining_item.unit_price * Decimal::from(remaining_item.quantity),
+                )
+                .await?;
+
+                let mut order_item: order_items::ActiveModel = remaining_item.clone().into();
+                order_item.discount_amount = Set(Decimal::ZERO);
+                order_item.tax_amount = Set(remaining_item.tax_amount);
+                order_item.update(&txn).await?;
+            }
+        }
+
+        // shipping is only given back once there is nothing left to ship
+        let shipping_amount = if remaining_items.is_empty() {
+            Decimal::ZERO
+        } else {
+            order.shipping_amount
+        };
+
+        let total_amount = order_total(&remaining_items) + shipping_amount;
+        let adjustment = total_amount - order.total_amount;
+
+        let tax_amount: Decimal = remaining_items.iter().map(|item| item.tax_amount).sum();
+
+        // the payment method is paid back first, then the gift card and the wallet last, each only
+        // once the total drops below what was paid ahead of it
+        let wallet_amount = order.wallet_amount.min(total_amount);
+        let gift_card_amount = order.gift_card_amount.min(total_amount - wallet_amount);
+        if gift_card_amount < order.gift_card_amount {
+            return_to_gift_card(&txn, order_id, order.gift_card_amount - gift_card_amount).await?;
+        }
+        if wallet_amount < order.wallet_amount {
+            change_wallet_balance(
+                &txn,
+                customer_id,
+                order.wallet_amount - wallet_amount,
+                WALLET_TRANSACTION_ORDER_REFUND,
+                Some(order_id),
+                None,
+            )
+            .await?;
+        }
+
+        if remaining_items.is_empty() {
+            reverse_order_shipping(&txn, &order).await?;
+        }
+
+        let payment_method_id = order.payment_method_id;
+
         let mut order: orders::ActiveModel = order.into();
+        order.total_amount = Set(total_amount);
+        order.wallet_amount = Set(wallet_amount);
+        order.gift_card_amount = Set(gift_card_amount);
+        order.tax_amount = Set(tax_amount);
+        order.shipping_amount = Set(shipping_amount);
+        order.discount_id = Set(discount.map(|discount| discount.discount_id));
+        if remaining_items.is_empty() {
+            order.status = Set(ORDER_STATUS_CANCELLED.to_string());
+        }
+        let order = order.update(&txn).await?;
 
-        order.status = Set("CANCELLED".to_string());
+        if let Some(bill) = BillsEntity::find()
+            .filter(bills::Column::OrderId.eq(order_id))
+            .one(&txn)
+            .await?
+        {
+            let bill_adjustment = bill_adjustments::ActiveModel {
+                bill_id: Set(bill.bill_id),
+                amount: Set(adjustment),
+                currency: Set(order.currency.clone()),
+                reason: Set(BILL_ADJUSTMENT_PARTIAL_CANCELLATION.to_string()),
+                ..Default::default()
+            };
+            BillAdjustmentsEntity::insert(bill_adjustment)
+                .exec(&txn)
+                .await?;
 
-        order.update(&txn).await?;
+            let mut bill: bills::ActiveModel = bill.into();
+            bill.total_amount = Set(total_amount);
+            bill.tax_amount = Set(tax_amount);
+            bill.shipping_amount = Set(shipping_amount);
+            bill.update(&txn).await?;
+        }
+
+        // an authorized payment only captures what is still owed, or is released once nothing is
+        // left of the order, a captured one pays back the difference
+        let mut authorized_payment = None;
+        if let Some(payment_intent) = live_payment_intent(&txn, order_id).await? {
+            if payment_intent.status == PAYMENT_INTENT_STATUS_AUTHORIZED {
+                authorized_payment = Some(payment_intent).filter(|_| remaining_items.is_empty());
+            } else {
+                let amount = payment_intent.captured_amount
+                    - payment_intent.refunded_amount
+                    - (total_amount - wallet_amount - gift_card_amount);
+                if amount > Decimal::ZERO {
+                    RefundsEntity::insert(cancellation_refund(
+                        &payment_intent,
+                        payment_method_id,
+                        amount,
+                    ))
+                    .exec(&txn)
+                    .await?;
+                }
+            }
+        }
 
         txn.commit().await?;
 
-        Ok("Order cancelled".to_string())
+        if let Some(payment_intent) = authorized_payment {
+            void_cancelled_payment(db, payment_provider, payment_intent).await?;
+        }
+
+        Ok(order.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        auth::{Auth, ROLE_CUSTOMER},
+        entity::{
+            addresses, customers, payment_methods, products,
+            sea_orm_active_enums::{PaymentMethodType, UserRole},
+            stock_movements, users,
+        },
+        graphql::schema::create_schema,
+        models::{
+            reservations::reserve_stock, stock_movements::STOCK_MOVEMENT_RESERVATION_RELEASE,
+        },
+        payment_gateway::MockPaymentProvider,
+    };
+    use async_graphql::Request;
+    use chrono::Duration;
+    use sea_orm::{
+        prelude::Decimal, ActiveValue::Set, ColumnTrait, ConnectOptions, Database,
+        DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter, TransactionTrait,
+    };
+    use std::{env, sync::Arc};
+
+    struct TestCustomer {
+        customer_id: i32,
+        address_id: i32,
+        payment_method_id: i32,
+        token: String,
+    }
+
+    // a signed in customer with a shipping address and a UPI payment method
+    async fn insert_customer(db: &DatabaseConnection, email: String) -> TestCustomer {
+        let user = users::Entity::insert(users::ActiveModel {
+            email: Set(email),
+            password: Set(String::new()),
+            role: Set(UserRole::Customer),
+            ..Default::default()
+        })
+        .exec_with_returning(db)
+        .await
+        .unwrap();
+        let customer = customers::Entity::insert(customers::ActiveModel {
+            first_name: Set("Test".to_string()),
+            last_name: Set("Customer".to_string()),
+            user_id: Set(user.user_id),
+            ..Default::default()
+        })
+        .exec_with_returning(db)
+        .await
+        .unwrap();
+        let address = addresses::Entity::insert(addresses::ActiveModel {
+            customer_id: Set(customer.customer_id),
+            street_address: Set("1 Test Street".to_string()),
+            city: Set("Bengaluru".to_string()),
+            postal_code: Set("560001".to_string()),
+            country: Set("IND".to_string()),
+            ..Default::default()
+        })
+        .exec_with_returning(db)
+        .await
+        .unwrap();
+        let payment_method = payment_methods::Entity::insert(payment_methods::ActiveModel {
+            customer_id: Set(customer.customer_id),
+            payment_type: Set(PaymentMethodType::Upi),
+            upi_id: Set(Some("test@upi".to_string())),
+            ..Default::default()
+        })
+        .exec_with_returning(db)
+        .await
+        .unwrap();
+
+        TestCustomer {
+            customer_id: customer.customer_id,
+            address_id: address.address_id,
+            payment_method_id: payment_method.payment_method_id,
+            token: Auth::create_token(user.user_id, ROLE_CUSTOMER.to_string(), Duration::hours(1))
+                .unwrap(),
+        }
+    }
+
+    // needs a database with schema.sql applied and the secret tokens are signed with:
+    // TEST_DATABASE_URL=postgresql://... TOKEN_SECRET=... cargo test -- --ignored
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn concurrent_orders_do_not_oversell() {
+        let database_url = env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set");
+        env::var("TOKEN_SECRET").expect("TOKEN_SECRET must be set");
+        // every order holds a transaction and looks the customer up outside of it
+        let db = Database::connect(
+            ConnectOptions::new(database_url)
+                .max_connections(100)
+                .to_owned(),
+        )
+        .await
+        .unwrap();
+        let schema = create_schema(db.clone(), Arc::new(MockPaymentProvider));
+
+        let product = products::Entity::insert(products::ActiveModel {
+            name: Set("Concurrency test product".to_string()),
+            base_price: Set(Decimal::ONE),
+            stock_quantity: Set(20),
+            ..Default::default()
+        })
+        .exec_with_returning(&db)
+        .await
+        .unwrap();
+
+        // the first customers hold 2 units each but only order 1, the rest race for what is left
+        let mut orders = Vec::new();
+        for i in 0..50 {
+            let customer = insert_customer(
+                &db,
+                format!("oversell_{}_{}@example.com", product.product_id, i),
+            )
+            .await;
+
+            let reserved = i < 5;
+            if reserved {
+                let txn = db.begin().await.unwrap();
+                reserve_stock(&txn, customer.customer_id, product.product_id, 2, None)
+                    .await
+                    .unwrap();
+                txn.commit().await.unwrap();
+            }
+
+            let request = Request::new(format!(
+                "mutation {{ registerOrder(input: {{ shippingAddressId: {}, paymentMethodId: {}, \
+                 orderItems: [{{ productId: {}, quantity: 1 }}] }}) {{ orderId }} }}",
+                customer.address_id, customer.payment_method_id, product.product_id
+            ))
+            .data(customer.token);
+            orders.push((reserved, request));
+        }
+
+        let orders: Vec<_> = orders
+            .into_iter()
+            .map(|(reserved, request)| {
+                let schema = schema.clone();
+                tokio::spawn(async move { (reserved, schema.execute(request).await.is_ok()) })
+            })
+            .collect();
+
+        let mut sold = 0;
+        for order in orders {
+            let (reserved, placed) = order.await.unwrap();
+            assert!(
+                placed || !reserved,
+                "an order with a reservation must go through"
+            );
+            if placed {
+                sold += 1;
+            }
+        }
+
+        let stock_quantity = products::Entity::find_by_id(product.product_id)
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap()
+            .stock_quantity;
+        let released = stock_movements::Entity::find()
+            .filter(stock_movements::Column::ProductId.eq(product.product_id))
+            .filter(stock_movements::Column::MovementType.eq(STOCK_MOVEMENT_RESERVATION_RELEASE))
+            .count(&db)
+            .await
+            .unwrap();
+
+        assert!(stock_quantity >= 0);
+        assert_eq!(sold + stock_quantity, 20);
+        assert_eq!(released, 5);
     }
 }
@@ -0,0 +1,579 @@
+use crate::{
+    auth::{RoleGuard, ROLE_ADMIN, ROLE_CUSTOMER},
+    entity::sea_orm_active_enums::PaymentMethodType,
+    graphql::macros::role_guard,
+    models::{
+        bills::BILL_STATUS_PAID,
+        gift_cards::{issue_gift_card, return_to_gift_card},
+        orders::ORDER_STATUS_PENDING,
+        payment_intents::{
+            capture_payment_intent, live_payment_intent, PaymentEvents, PaymentIntents,
+            PAYMENT_INTENT_STATUS_AUTHORIZED, PAYMENT_INTENT_STATUS_CAPTURED,
+            PAYMENT_INTENT_STATUS_FAILED, PAYMENT_INTENT_STATUS_VOIDED,
+        },
+        returns::{Refunds, REFUND_STATUS_COMPLETED, REFUND_STATUS_PENDING},
+        user::get_customer_supplier_id,
+        wallet::{change_wallet_balance, WALLET_TRANSACTION_ORDER_REFUND},
+    },
+    money::Money,
+    payment_gateway::{AuthorizeRequest, PaymentError, SharedPaymentProvider},
+    payment_webhook::process_payment_event,
+};
+use async_graphql::{Context, Object};
+use chrono::Utc;
+use sea_orm::{
+    prelude::Decimal, ActiveModelTrait, ActiveValue::Set, ColumnTrait, DatabaseConnection,
+    EntityTrait, PaginatorTrait, QueryFilter, QueryOrder, TransactionTrait,
+};
+
+#[derive(Default)]
+pub struct PaymentIntentsQuery;
+
+#[derive(Default)]
+pub struct PaymentIntentsMutation;
+
+#[Object]
+impl PaymentIntentsQuery {
+    #[graphql(guard = "role_guard!(ROLE_CUSTOMER)")]
+    async fn payment_intents(
+        &self,
+        ctx: &Context<'_>,
+        order_id: i32,
+    ) -> Result<Vec<PaymentIntents>, async_graphql::Error> {
+        use crate::entity::{
+            payment_intents,
+            prelude::{Orders as OrdersEntity, PaymentIntents as PaymentIntentsEntity},
+        };
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+
+        let customer_id = get_customer_supplier_id(db, token, ROLE_CUSTOMER).await?;
+
+        OrdersEntity::find_by_id(order_id)
+            .one(db)
+            .await?
+            .filter(|order| order.customer_id == customer_id)
+            .ok_or("Order not found")?;
+
+        let payment_intents = PaymentIntentsEntity::find()
+            .filter(payment_intents::Column::OrderId.eq(order_id))
+            .order_by_asc(payment_intents::Column::PaymentIntentId)
+            .all(db)
+            .await?;
+
+        Ok(payment_intents
+            .into_iter()
+            .map(|payment_intent| payment_intent.into())
+            .collect())
+    }
+
+    #[graphql(guard = "role_guard!(ROLE_ADMIN)")]
+    async fn payment_events(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] unprocessed_only: bool,
+    ) -> Result<Vec<PaymentEvents>, async_graphql::Error> {
+        use crate::entity::{payment_events, prelude::PaymentEvents as PaymentEventsEntity};
+        let db = ctx.data::<DatabaseConnection>()?;
+
+        let mut query = PaymentEventsEntity::find();
+        if unprocessed_only {
+            query = query.filter(payment_events::Column::ProcessedAt.is_null());
+        }
+
+        let payment_events = query
+            .order_by_asc(payment_events::Column::PaymentEventId)
+            .all(db)
+            .await?;
+
+        Ok(payment_events
+            .into_iter()
+            .map(|payment_event| payment_event.into())
+            .collect())
+    }
+}
+
+#[Object]
+impl PaymentIntentsMutation {
+    // a declined payment is kept as a failed intent with the reason, the customer can then
+    // switch the payment method and try again
+    #[graphql(guard = "role_guard!(ROLE_CUSTOMER)")]
+    async fn authorize_payment(
+        &self,
+        ctx: &Context<'_>,
+        order_id: i32,
+    ) -> Result<PaymentIntents, async_graphql::Error> {
+        use crate::entity::{
+            bills, payment_intents,
+            prelude::{
+                Bills as BillsEntity, Orders as OrdersEntity,
+                PaymentIntents as PaymentIntentsEntity, PaymentMethods as PaymentMethodsEntity,
+            },
+        };
+        let db = ctx.data::<DatabaseConnection>()?;
+        let payment_provider = ctx.data::<SharedPaymentProvider>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+        let txn = db.begin().await?;
+
+        let customer_id = get_customer_supplier_id(db, token, ROLE_CUSTOMER).await?;
+
+        let order = OrdersEntity::find_by_id(order_id)
+            .one(&txn)
+            .await?
+            .filter(|order| order.customer_id == customer_id)
+            .ok_or("Order not found")?;
+
+        if order.status != ORDER_STATUS_PENDING {
+            return Err("Only pending orders can be paid for".into());
+        }
+
+        if live_payment_intent(&txn, order_id).await?.is_some() {
+            return Err("Order already has a payment".into());
+        }
+
+        let bill = BillsEntity::find()
+            .filter(bills::Column::OrderId.eq(order_id))
+            .one(&txn)
+            .await?
+            .ok_or("No bill found for this order")?;
+
+        let payment_method = PaymentMethodsEntity::find_by_id(order.payment_method_id)
+            .one(&txn)
+            .await?
+            .ok_or("Payment method not found")?;
+
+        match payment_method.payment_type {
+            PaymentMethodType::Cod => {
+                return Err("Cash on delivery orders are paid when they are delivered".into())
+            }
+            PaymentMethodType::Wallet => {
+                return Err("Wallet payments are taken when the order is placed".into())
+            }
+            _ => {}
+        }
+
+        // the parts paid from the wallet and a gift card were already taken when the order was placed
+        let amount = Money::new(
+            bill.total_amount - order.wallet_amount - order.gift_card_amount,
+            &bill.currency,
+        );
+        if amount.amount <= Decimal::ZERO {
+            return Err("Nothing is left to pay on this order".into());
+        }
+
+        let attempt = PaymentIntentsEntity::find()
+            .filter(payment_intents::Column::OrderId.eq(order_id))
+            .count(&txn)
+            .await?
+            + 1;
+
+        let (status, provider_reference, failure_reason) = match payment_provider
+            .authorize(AuthorizeRequest {
+                order_id,
+                attempt,
+                amount: &amount,
+                payment_method: &payment_method,
+            })
+            .await
+        {
+            Ok(reference) => (PAYMENT_INTENT_STATUS_AUTHORIZED, Some(reference), None),
+            Err(PaymentError::Declined(reason)) => {
+                (PAYMENT_INTENT_STATUS_FAILED, None, Some(reason))
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let payment_intent = payment_intents::ActiveModel {
+            order_id: Set(order_id),
+            bill_id: Set(bill.bill_id),
+            provider: Set(payment_provider.name().to_string()),
+            provider_reference: Set(provider_reference),
+            amount: Set(amount.amount),
+            currency: Set(amount.currency),
+            status: Set(status.to_string()),
+            failure_reason: Set(failure_reason),
+            ..Default::default()
+        };
+
+        let insert_payment_intent = PaymentIntentsEntity::insert(payment_intent)
+            .exec_with_returning(&txn)
+            .await?;
+
+        txn.commit().await?;
+
+        Ok(insert_payment_intent.into())
+    }
+
+    // the order only counts as paid once the money has actually been captured
+    #[graphql(guard = "role_guard!(ROLE_CUSTOMER)")]
+    async fn capture_payment(
+        &self,
+        ctx: &Context<'_>,
+        payment_intent_id: i32,
+    ) -> Result<PaymentIntents, async_graphql::Error> {
+        use crate::entity::{
+            payment_intents,
+            prelude::{
+                Bills as BillsEntity, Orders as OrdersEntity,
+                PaymentIntents as PaymentIntentsEntity,
+            },
+        };
+        let db = ctx.data::<DatabaseConnection>()?;
+        let payment_provider = ctx.data::<SharedPaymentProvider>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+        let txn = db.begin().await?;
+
+        let customer_id = get_customer_supplier_id(db, token, ROLE_CUSTOMER).await?;
+
+        let (payment_intent, order) = PaymentIntentsEntity::find_by_id(payment_intent_id)
+            .find_also_related(OrdersEntity)
+            .one(&txn)
+            .await?
+            .filter(|(_, order)| {
+                order
+                    .as_ref()
+                    .is_some_and(|order| order.customer_id == customer_id)
+            })
+            .ok_or("Payment not found")?;
+
+        let order = order.ok_or("Order not found")?;
+
+        if payment_intent.status != PAYMENT_INTENT_STATUS_AUTHORIZED {
+            return Err("Only authorized payments can be captured".into());
+        }
+
+        if order.status != ORDER_STATUS_PENDING {
+            return Err("Only pending orders can be paid for".into());
+        }
+
+        let bill = BillsEntity::find_by_id(payment_intent.bill_id)
+            .one(&txn)
+            .await?
+            .ok_or("No bill found for this order")?;
+
+        // items cancelled after authorizing lower the bill, only what is still owed is taken
+        let amount = Money::new(
+            (bill.total_amount - order.wallet_amount - order.gift_card_amount)
+                .min(payment_intent.amount),
+            &payment_intent.currency,
+        );
+        if amount.amount <= Decimal::ZERO {
+            return Err("Nothing is left to pay on this order".into());
+        }
+
+        let reference = payment_intent
+            .provider_reference
+            .clone()
+            .ok_or("Payment has no provider reference")?;
+
+        // the key is per payment, a capture retried after the save failed isn't taken twice
+        let idempotency_key = format!("capture_{}", payment_intent.payment_intent_id);
+        if let Err(e) = payment_provider
+            .capture(&reference, &amount, &idempotency_key)
+            .await
+        {
+            let mut payment_intent: payment_intents::ActiveModel = payment_intent.into();
+            payment_intent.failure_reason = Set(Some(e.to_string()));
+            payment_intent.updated_at = Set(Some(Utc::now().fixed_offset()));
+            payment_intent.update(&txn).await?;
+            txn.commit().await?;
+            return Err(e.into());
+        }
+
+        let payment_intent = capture_payment_intent(&txn, payment_intent, amount.amount).await?;
+
+        txn.commit().await?;
+
+        Ok(payment_intent.into())
+    }
+
+    #[graphql(guard = "role_guard!(ROLE_CUSTOMER)")]
+    async fn void_payment(
+        &self,
+        ctx: &Context<'_>,
+        payment_intent_id: i32,
+    ) -> Result<PaymentIntents, async_graphql::Error> {
+        use crate::entity::{
+            payment_intents,
+            prelude::{Orders as OrdersEntity, PaymentIntents as PaymentIntentsEntity},
+        };
+        let db = ctx.data::<DatabaseConnection>()?;
+        let payment_provider = ctx.data::<SharedPaymentProvider>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+        let txn = db.begin().await?;
+
+        let customer_id = get_customer_supplier_id(db, token, ROLE_CUSTOMER).await?;
+
+        let (payment_intent, _) = PaymentIntentsEntity::find_by_id(payment_intent_id)
+            .find_also_related(OrdersEntity)
+            .one(&txn)
+            .await?
+            .filter(|(_, order)| {
+                order
+                    .as_ref()
+                    .is_some_and(|order| order.customer_id == customer_id)
+            })
+            .ok_or("Payment not found")?;
+
+        if payment_intent.status != PAYMENT_INTENT_STATUS_AUTHORIZED {
+            return Err("Only authorized payments can be voided".into());
+        }
+
+        let reference = payment_intent
+            .provider_reference
+            .clone()
+            .ok_or("Payment has no provider reference")?;
+
+        payment_provider
+            .void(
+                &reference,
+                &format!("void_{}", payment_intent.payment_intent_id),
+            )
+            .await?;
+
+        let mut payment_intent: payment_intents::ActiveModel = payment_intent.into();
+        payment_intent.status = Set(PAYMENT_INTENT_STATUS_VOIDED.to_string());
+        payment_intent.updated_at = Set(Some(Utc::now().fixed_offset()));
+        let payment_intent = payment_intent.update(&txn).await?;
+
+        txn.commit().await?;
+
+        Ok(payment_intent.into())
+    }
+
+    // re-processes stored webhook events, every event that hasn't gone through yet when no ids
+    // are given
+    #[graphql(guard = "role_guard!(ROLE_ADMIN)")]
+    async fn replay_payment_events(
+        &self,
+        ctx: &Context<'_>,
+        payment_event_ids: Option<Vec<i32>>,
+    ) -> Result<Vec<PaymentEvents>, async_graphql::Error> {
+        use crate::entity::{payment_events, prelude::PaymentEvents as PaymentEventsEntity};
+        let db = ctx.data::<DatabaseConnection>()?;
+
+        let query = match payment_event_ids {
+            Some(payment_event_ids) => PaymentEventsEntity::find()
+                .filter(payment_events::Column::PaymentEventId.is_in(payment_event_ids)),
+            None => {
+                PaymentEventsEntity::find().filter(payment_events::Column::ProcessedAt.is_null())
+            }
+        };
+
+        let mut replayed = Vec::new();
+        for payment_event in query
+            .order_by_asc(payment_events::Column::PaymentEventId)
+            .all(db)
+            .await?
+        {
+            replayed.push(process_payment_event(db, payment_event).await?.into());
+        }
+
+        Ok(replayed)
+    }
+
+    // refunds are paid back through the provider against the payment that was captured for the
+    // bill, and to the gift card and wallet for what they paid. A failed attempt leaves the
+    // refund pending so it can be retried
+    #[graphql(guard = "role_guard!(ROLE_ADMIN)")]
+    async fn process_refund(
+        &self,
+        ctx: &Context<'_>,
+        refund_id: i32,
+        store_credit: Option<bool>,
+    ) -> Result<Refunds, async_graphql::Error> {
+        use crate::entity::{
+            orders, payment_intents,
+            prelude::{
+                Bills as BillsEntity, Orders as OrdersEntity,
+                PaymentIntents as PaymentIntentsEntity, Refunds as RefundsEntity,
+            },
+            refunds,
+        };
+        let db = ctx.data::<DatabaseConnection>()?;
+        let payment_provider = ctx.data::<SharedPaymentProvider>()?;
+        let txn = db.begin().await?;
+
+        let refund = RefundsEntity::find_by_id(refund_id)
+            .one(&txn)
+            .await?
+            .ok_or("Refund not found")?;
+
+        if refund.status != REFUND_STATUS_PENDING {
+            return Err("Refund already processed".into());
+        }
+
+        let bill = BillsEntity::find_by_id(refund.bill_id)
+            .one(&txn)
+            .await?
+            .ok_or("No bill found for this refund")?;
+
+        // store credit is a gift card for the customer instead of money back, it also works for
+        // orders paid without a payment provider
+        if store_credit.unwrap_or(false) {
+            let order = OrdersEntity::find_by_id(bill.order_id)
+                .one(&txn)
+                .await?
+                .ok_or("Order not found")?;
+
+            let payment_intent = PaymentIntentsEntity::find()
+                .filter(payment_intents::Column::BillId.eq(bill.bill_id))
+                .filter(payment_intents::Column::Status.eq(PAYMENT_INTENT_STATUS_CAPTURED))
+                .one(&txn)
+                .await?;
+
+            // a cancellation refund gives back part of the captured payment, a return refund part
+            // of what the bill was paid less the returns already refunded
+            let refundable = if refund.return_id.is_none() {
+                payment_intent
+                    .as_ref()
+                    .map_or(Decimal::ZERO, |payment_intent| {
+                        payment_intent.captured_amount - payment_intent.refunded_amount
+                    })
+            } else {
+                let paid = if bill.payment_status == BILL_STATUS_PAID {
+                    bill.total_amount
+                } else {
+                    order.wallet_amount + order.gift_card_amount
+                };
+                let refunded: Decimal = RefundsEntity::find()
+                    .filter(refunds::Column::BillId.eq(bill.bill_id))
+                    .filter(refunds::Column::ReturnId.is_not_null())
+                    .filter(refunds::Column::Status.eq(REFUND_STATUS_COMPLETED))
+                    .all(&txn)
+                    .await?
+                    .iter()
+                    .map(|refund| refund.amount)
+                    .sum();
+                paid - refunded
+            };
+            if refund.currency != bill.currency || refund.amount > refundable {
+                return Err("Refund exceeds the amount left to refund on the order".into());
+            }
+
+            if let Some(payment_intent) = payment_intent {
+                if refund.currency == payment_intent.currency
+                    && refund.amount
+                        <= payment_intent.captured_amount - payment_intent.refunded_amount
+                {
+                    let refunded_amount = payment_intent.refunded_amount + refund.amount;
+                    let mut payment_intent: payment_intents::ActiveModel = payment_intent.into();
+                    payment_intent.refunded_amount = Set(refunded_amount);
+                    payment_intent.updated_at = Set(Some(Utc::now().fixed_offset()));
+                    payment_intent.update(&txn).await?;
+                }
+            }
+
+            issue_gift_card(
+                &txn,
+                &Money::new(refund.amount, &refund.currency),
+                Some(order.customer_id),
+                None,
+                Some(refund.refund_id),
+                Some(format!("Store credit for order {}", order.order_id)),
+            )
+            .await?;
+
+            let mut refund: refunds::ActiveModel = refund.into();
+            refund.status = Set(REFUND_STATUS_COMPLETED.to_string());
+            let refund = refund.update(&txn).await?;
+
+            txn.commit().await?;
+
+            return Ok(refund.into());
+        }
+
+        let order = OrdersEntity::find_by_id(bill.order_id)
+            .one(&txn)
+            .await?
+            .ok_or("Order not found")?;
+
+        if refund.currency != bill.currency {
+            return Err("Refund currency doesn't match the bill".into());
+        }
+
+        let payment_intent = PaymentIntentsEntity::find()
+            .filter(payment_intents::Column::BillId.eq(bill.bill_id))
+            .filter(payment_intents::Column::Status.eq(PAYMENT_INTENT_STATUS_CAPTURED))
+            .one(&txn)
+            .await?;
+
+        // the payment method is paid back first, then the gift card and the wallet last, the same
+        // way a partial cancellation gives money back
+        let provider_amount = payment_intent
+            .as_ref()
+            .map_or(Decimal::ZERO, |payment_intent| {
+                refund
+                    .amount
+                    .min(payment_intent.captured_amount - payment_intent.refunded_amount)
+            });
+        let gift_card_amount = order.gift_card_amount.min(refund.amount - provider_amount);
+        let wallet_amount = order
+            .wallet_amount
+            .min(refund.amount - provider_amount - gift_card_amount);
+        if provider_amount + gift_card_amount + wallet_amount < refund.amount {
+            return Err("Refund exceeds the amount left on the payment".into());
+        }
+
+        if gift_card_amount > Decimal::ZERO {
+            return_to_gift_card(&txn, order.order_id, gift_card_amount).await?;
+        }
+        if wallet_amount > Decimal::ZERO {
+            change_wallet_balance(
+                &txn,
+                order.customer_id,
+                wallet_amount,
+                WALLET_TRANSACTION_ORDER_REFUND,
+                Some(order.order_id),
+                None,
+            )
+            .await?;
+        }
+        if gift_card_amount > Decimal::ZERO || wallet_amount > Decimal::ZERO {
+            let order_gift_card_amount = order.gift_card_amount - gift_card_amount;
+            let order_wallet_amount = order.wallet_amount - wallet_amount;
+            let mut order: orders::ActiveModel = order.into();
+            order.gift_card_amount = Set(order_gift_card_amount);
+            order.wallet_amount = Set(order_wallet_amount);
+            order.update(&txn).await?;
+        }
+
+        if let Some(payment_intent) = payment_intent.filter(|_| provider_amount > Decimal::ZERO) {
+            let reference = payment_intent
+                .provider_reference
+                .clone()
+                .ok_or("Payment has no provider reference")?;
+
+            // the key is the refund's, a retry after the save failed doesn't pay it out again
+            payment_provider
+                .refund(
+                    &reference,
+                    &Money::new(provider_amount, &refund.currency),
+                    &format!("refund_{}", refund.refund_id),
+                )
+                .await?;
+
+            let refunded_amount = payment_intent.refunded_amount + provider_amount;
+            let mut payment_intent: payment_intents::ActiveModel = payment_intent.into();
+            payment_intent.refunded_amount = Set(refunded_amount);
+            payment_intent.updated_at = Set(Some(Utc::now().fixed_offset()));
+            payment_intent.update(&txn).await?;
+        }
+
+        let mut refund: refunds::ActiveModel = refund.into();
+        refund.status = Set(REFUND_STATUS_COMPLETED.to_string());
+        let refund = refund.update(&txn).await?;
+
+        txn.commit().await?;
+
+        Ok(refund.into())
+    }
+}
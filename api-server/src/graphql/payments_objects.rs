@@ -2,13 +2,19 @@ use crate::{
     auth::{Auth, RoleGuard, ROLE_CUSTOMER},
     graphql::macros::role_guard,
     models::{
-        payments::{create_payment_method, CardTypes, PaymentMethods, RegisterPaymentMethod},
+        payments::{
+            create_payment_method, detect_card_brand, CardTypes, PaymentMethods,
+            RegisterPaymentMethod,
+        },
         user::get_customer_supplier_id,
     },
+    payment_gateway::SharedPaymentProvider,
 };
 use async_graphql::{Context, Object};
 use sea_orm::ActiveValue::Set;
-use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, TransactionTrait};
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, TransactionTrait,
+};
 
 #[derive(Default)]
 pub struct PaymentsQuery;
@@ -47,17 +53,39 @@ impl PaymentsQuery {
         Ok(payment_methods)
     }
 
+    async fn card_types(&self, ctx: &Context<'_>) -> Result<Vec<CardTypes>, async_graphql::Error> {
+        use crate::entity::{card_types, prelude::CardTypes as CardTypesEntity};
+        let db = ctx.data::<DatabaseConnection>()?;
+
+        let card_types = CardTypesEntity::find()
+            .order_by_asc(card_types::Column::CardTypeId)
+            .all(db)
+            .await?;
+
+        Ok(card_types
+            .into_iter()
+            .map(|card_type| card_type.into())
+            .collect())
+    }
+
+    // the first six to eight digits are enough, so checkout forms can show the brand while typing
     async fn card_type(
         &self,
         ctx: &Context<'_>,
-        card_type_id: i32,
+        card_number: String,
     ) -> Result<CardTypes, async_graphql::Error> {
-        use crate::entity::prelude::CardTypes as CardTypesEntity;
+        use crate::entity::{card_types, prelude::CardTypes as CardTypesEntity};
         let db = ctx.data::<DatabaseConnection>()?;
 
-        let card_type = CardTypesEntity::find_by_id(card_type_id).one(db).await?;
+        let card_number: String = card_number.chars().filter(|c| c.is_ascii_digit()).collect();
+
+        let card_type = CardTypesEntity::find()
+            .filter(card_types::Column::Name.eq(detect_card_brand(&card_number)))
+            .one(db)
+            .await?
+            .ok_or("Card type not found")?;
 
-        Ok(card_type.unwrap().into())
+        Ok(card_type.into())
     }
 }
 
@@ -71,6 +99,7 @@ impl PaymentsMutation {
     ) -> Result<PaymentMethods, async_graphql::Error> {
         use crate::entity::prelude::PaymentMethods as PaymentMethodsEntity;
         let db = ctx.data::<DatabaseConnection>()?;
+        let payment_provider = ctx.data::<SharedPaymentProvider>()?;
         let token = ctx
             .data_opt::<String>()
             .ok_or("No authorization token found")?;
@@ -79,7 +108,8 @@ impl PaymentsMutation {
         let customer_id = get_customer_supplier_id(db, token, ROLE_CUSTOMER).await?;
         let is_default: Option<bool> = Some(input.is_default.unwrap_or(false));
 
-        let payment_method = create_payment_method(customer_id, is_default, input, &txn).await?;
+        let payment_method =
+            create_payment_method(customer_id, is_default, input, payment_provider, &txn).await?;
 
         let insert_payment_method = PaymentMethodsEntity::insert(payment_method)
             .exec_with_returning(&txn)
@@ -99,6 +129,7 @@ impl PaymentsMutation {
     ) -> Result<PaymentMethods, async_graphql::Error> {
         use crate::entity::{payment_methods, prelude::PaymentMethods as PaymentMethodsEntity};
         let db = ctx.data::<DatabaseConnection>()?;
+        let payment_provider = ctx.data::<SharedPaymentProvider>()?;
         let token = ctx
             .data_opt::<String>()
             .ok_or("No authorization token found")?;
@@ -108,7 +139,7 @@ impl PaymentsMutation {
         let is_default: Option<bool> = Some(input.is_default.unwrap_or(false));
 
         let mut payment_method =
-            create_payment_method(customer_id, is_default, input, &txn).await?;
+            create_payment_method(customer_id, is_default, input, payment_provider, &txn).await?;
         payment_method.payment_method_id = Set(payment_method_id);
 
         let update_payment_method = PaymentMethodsEntity::update(payment_method)
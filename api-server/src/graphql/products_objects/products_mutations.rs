@@ -1,12 +1,18 @@
 use crate::{
-    auth::{RoleGuard, ROLE_CUSTOMER, ROLE_SUPPLIER},
+    auth::{Auth, RoleGuard, ROLE_CUSTOMER, ROLE_SUPPLIER},
     graphql::macros::role_guard,
     models::{
+        currency::exchange_rate,
+        product_import::{import_products, CatalogueFormat, ProductImportReport},
         products::{
             check_if_supplier_owns_product, create_discount_model, create_product_model,
-            create_review_model, Discounts, Products, RegisterDiscount, RegisterProduct,
-            RegisterReview, Reviews,
+            create_review_model, set_product_status, update_discount_model, update_product_model,
+            update_review_model, Discounts, Products, RegisterDiscount, RegisterProduct,
+            RegisterReview, Reviews, UpdateDiscount, UpdateProduct, UpdateReview,
+            PRODUCT_STATUS_ACTIVE, PRODUCT_STATUS_ARCHIVED,
         },
+        stock_alerts::notify_back_in_stock,
+        stock_movements::{adjust_stock_to, STOCK_MOVEMENT_ADJUSTMENT},
         user::get_customer_supplier_id,
     },
 };
@@ -34,19 +40,64 @@ impl ProductsMutation {
             .data_opt::<String>()
             .ok_or("No authorization token found")?;
         let supplier_id = get_customer_supplier_id(db, token, ROLE_SUPPLIER).await?;
+        let user_id = Auth::verify_token(token)?.user_id.parse::<i32>()?;
+        exchange_rate(db, &input.base_price.currency).await?;
+        let stock_quantity = input.stock_quantity;
         let product = create_product_model(input, supplier_id)?;
+        let txn = db.begin().await?;
         let insert_product = ProductsEntity::insert(product)
-            .exec_with_returning(db)
+            .exec_with_returning(&txn)
             .await?;
+        adjust_stock_to(
+            &txn,
+            &insert_product,
+            stock_quantity,
+            STOCK_MOVEMENT_ADJUSTMENT,
+            user_id,
+        )
+        .await?;
+        let insert_product = ProductsEntity::find_by_id(insert_product.product_id)
+            .one(&txn)
+            .await?
+            .ok_or("Product not found")?;
+        txn.commit().await?;
         Ok(insert_product.into())
     }
 
+    // creates or updates the supplier's products by SKU, with dry_run nothing is saved and the
+    // report shows what the import would do
+    #[graphql(guard = "role_guard!(ROLE_SUPPLIER)")]
+    async fn import_products(
+        &self,
+        ctx: &Context<'_>,
+        format: CatalogueFormat,
+        data: String,
+        dry_run: Option<bool>,
+    ) -> Result<ProductImportReport, async_graphql::Error> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+        let supplier_id = get_customer_supplier_id(db, token, ROLE_SUPPLIER).await?;
+        let user_id = Auth::verify_token(token)?.user_id.parse::<i32>()?;
+
+        import_products(
+            db,
+            supplier_id,
+            user_id,
+            format,
+            &data,
+            dry_run.unwrap_or(false),
+        )
+        .await
+    }
+
     #[graphql(guard = "role_guard!(ROLE_SUPPLIER)")]
     async fn update_product(
         &self,
         ctx: &Context<'_>,
         product_id: i32,
-        input: RegisterProduct,
+        input: UpdateProduct,
     ) -> Result<Products, async_graphql::Error> {
         use crate::entity::{prelude::Products as ProductsEntity, products};
         let db = ctx.data::<DatabaseConnection>()?;
@@ -54,24 +105,47 @@ impl ProductsMutation {
             .data_opt::<String>()
             .ok_or("No authorization token found")?;
         let supplier_id = get_customer_supplier_id(db, token, ROLE_SUPPLIER).await?;
+        let user_id = Auth::verify_token(token)?.user_id.parse::<i32>()?;
         check_if_supplier_owns_product(db, supplier_id, product_id).await?;
-        let mut product = create_product_model(input, supplier_id)?;
+        if let Some(base_price) = &input.base_price {
+            exchange_rate(db, &base_price.currency).await?;
+        }
+        let stock_quantity = input.stock_quantity;
+        let mut product = update_product_model(input)?;
 
         product.product_id = Set(product_id);
+        let txn = db.begin().await?;
         let update_product = ProductsEntity::update(product)
             .filter(products::Column::ProductId.eq(product_id))
-            .exec(db)
+            .exec(&txn)
             .await?;
+        if let Some(stock_quantity) = stock_quantity {
+            adjust_stock_to(
+                &txn,
+                &update_product,
+                stock_quantity,
+                STOCK_MOVEMENT_ADJUSTMENT,
+                user_id,
+            )
+            .await?;
+        }
+        let update_product = ProductsEntity::find_by_id(product_id)
+            .one(&txn)
+            .await?
+            .ok_or("Product not found")?;
+        txn.commit().await?;
+
+        notify_back_in_stock(db, product_id).await?;
         Ok(update_product.into())
     }
 
+    // products are archived rather than deleted, orders, carts and reviews keep pointing at them
     #[graphql(guard = "role_guard!(ROLE_SUPPLIER)")]
     async fn delete_product(
         &self,
         ctx: &Context<'_>,
         product_id: i32,
     ) -> Result<String, async_graphql::Error> {
-        use crate::entity::prelude::Products as ProductsEntity;
         let db = ctx.data::<DatabaseConnection>()?;
         let token = ctx
             .data_opt::<String>()
@@ -80,8 +154,26 @@ impl ProductsMutation {
         let supplier_id = get_customer_supplier_id(db, token, ROLE_SUPPLIER).await?;
 
         check_if_supplier_owns_product(db, supplier_id, product_id).await?;
-        ProductsEntity::delete_by_id(product_id).exec(db).await?;
-        Ok("Product deleted".to_string())
+        set_product_status(db, product_id, PRODUCT_STATUS_ARCHIVED).await?;
+        Ok("Product archived".to_string())
+    }
+
+    #[graphql(guard = "role_guard!(ROLE_SUPPLIER)")]
+    async fn restore_product(
+        &self,
+        ctx: &Context<'_>,
+        product_id: i32,
+    ) -> Result<Products, async_graphql::Error> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+
+        let supplier_id = get_customer_supplier_id(db, token, ROLE_SUPPLIER).await?;
+
+        check_if_supplier_owns_product(db, supplier_id, product_id).await?;
+        let product = set_product_status(db, product_id, PRODUCT_STATUS_ACTIVE).await?;
+        Ok(product.into())
     }
 
     #[graphql(guard = "role_guard!(ROLE_CUSTOMER)")]
@@ -131,7 +223,7 @@ impl ProductsMutation {
         &self,
         ctx: &Context<'_>,
         review_id: i32,
-        input: RegisterReview,
+        input: UpdateReview,
     ) -> Result<Reviews, async_graphql::Error> {
         use crate::entity::{prelude::Reviews as ReviewsEntity, reviews};
         let db = ctx.data::<DatabaseConnection>()?;
@@ -142,7 +234,14 @@ impl ProductsMutation {
 
         let customer_id = get_customer_supplier_id(db, token, ROLE_CUSTOMER).await?;
 
-        let review = create_review_model(input, customer_id)?;
+        ReviewsEntity::find_by_id(review_id)
+            .one(&txn)
+            .await?
+            .filter(|review| review.customer_id == customer_id)
+            .ok_or("Review not found")?;
+
+        let mut review = update_review_model(input)?;
+        review.review_id = Set(review_id);
 
         let update_review = ReviewsEntity::update(review)
             .filter(reviews::Column::ReviewId.eq(review_id))
@@ -213,7 +312,7 @@ impl ProductsMutation {
         &self,
         ctx: &Context<'_>,
         discount_id: i32,
-        input: RegisterDiscount,
+        input: UpdateDiscount,
     ) -> Result<Discounts, async_graphql::Error> {
         use crate::entity::{discounts, prelude::Discounts as DiscountsEntity};
         let db = ctx.data::<DatabaseConnection>()?;
@@ -222,9 +321,18 @@ impl ProductsMutation {
             .ok_or("No authorization token found")?;
         let supplier_id = get_customer_supplier_id(db, token, ROLE_SUPPLIER).await?;
 
-        check_if_supplier_owns_product(db, supplier_id, input.product_id).await?;
+        // the discount has to be on one of the supplier's products before and after the update
+        let discount_product_id = DiscountsEntity::find_by_id(discount_id)
+            .one(db)
+            .await?
+            .and_then(|discount| discount.product_id)
+            .ok_or("Discount not found")?;
+        check_if_supplier_owns_product(db, supplier_id, discount_product_id).await?;
+        if let Some(product_id) = input.product_id {
+            check_if_supplier_owns_product(db, supplier_id, product_id).await?;
+        }
 
-        let mut discount = create_discount_model(input)?;
+        let mut discount = update_discount_model(input)?;
         discount.discount_id = Set(discount_id);
 
         let update_discount = DiscountsEntity::update(discount)
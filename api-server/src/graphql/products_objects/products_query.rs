@@ -1,8 +1,14 @@
-use crate::models::{
-    order_und_pagination::{OrderAndPagination, PageInfo},
-    products::{
-        paginate_products, Categories, Discounts, Products, ProductsPaginate, Reviews,
-        ReviewsPaginate,
+use crate::{
+    auth::{RoleGuard, ROLE_SUPPLIER},
+    graphql::macros::role_guard,
+    models::{
+        order_und_pagination::{OrderAndPagination, PageInfo},
+        product_import::{export_products, CatalogueFormat},
+        products::{
+            paginate_products, Categories, Discounts, Products, ProductsPaginate, Reviews,
+            ReviewsPaginate, PRODUCT_STATUS_ACTIVE,
+        },
+        user::get_customer_supplier_id,
     },
 };
 use async_graphql::{Context, Object};
@@ -30,7 +36,9 @@ impl ProductsQuery {
         let page = paginator.pagination.page - 1;
         let page_size = paginator.pagination.page_size;
 
-        let products = ProductsEntity::find().filter(
+        let products = ProductsEntity::find()
+            .filter(products::Column::Status.eq(PRODUCT_STATUS_ACTIVE))
+            .filter(
             match (category_id, supplier_id, base_product_id, product_id) {
                 (Some(category_id), None, None, None) => {
                     products::Column::CategoryId.eq(category_id)
@@ -75,7 +83,9 @@ impl ProductsQuery {
         let page = paginator.pagination.page - 1;
         let page_size = paginator.pagination.page_size;
 
-        let products = ProductsEntity::find().filter(products::Column::Name.contains(name));
+        let products = ProductsEntity::find()
+            .filter(products::Column::Status.eq(PRODUCT_STATUS_ACTIVE))
+            .filter(products::Column::Name.contains(name));
 
         let products = paginate_products(paginator, products).await?;
 
@@ -95,6 +105,66 @@ impl ProductsQuery {
         })
     }
 
+    // customers only see active products, suppliers list all of theirs here
+    #[graphql(guard = "role_guard!(ROLE_SUPPLIER)")]
+    async fn my_products(
+        &self,
+        ctx: &Context<'_>,
+        status: Option<String>,
+        paginator: OrderAndPagination,
+    ) -> Result<ProductsPaginate, async_graphql::Error> {
+        use crate::entity::{prelude::Products as ProductsEntity, products};
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+
+        let supplier_id = get_customer_supplier_id(db, token, ROLE_SUPPLIER).await?;
+
+        let page = paginator.pagination.page - 1;
+        let page_size = paginator.pagination.page_size;
+
+        let mut products =
+            ProductsEntity::find().filter(products::Column::SupplierId.eq(supplier_id));
+        if let Some(status) = status {
+            products = products.filter(products::Column::Status.eq(status));
+        }
+
+        let products = paginate_products(paginator, products).await?;
+
+        let products = products.paginate(db, page_size);
+        let items = PageInfo {
+            total_pages: products.num_pages().await?,
+            total_items: products.num_items().await?,
+        };
+
+        let products = products.fetch_page(page).await?;
+
+        let products: Vec<Products> = products.into_iter().map(|product| product.into()).collect();
+
+        Ok(ProductsPaginate {
+            products,
+            page_info: items,
+        })
+    }
+
+    // the same columns importProducts takes, so a file can be exported, edited and imported again
+    #[graphql(guard = "role_guard!(ROLE_SUPPLIER)")]
+    async fn export_products(
+        &self,
+        ctx: &Context<'_>,
+        format: CatalogueFormat,
+    ) -> Result<String, async_graphql::Error> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+
+        let supplier_id = get_customer_supplier_id(db, token, ROLE_SUPPLIER).await?;
+
+        export_products(db, supplier_id, format).await
+    }
+
     async fn categories(&self, ctx: &Context<'_>) -> Result<Vec<Categories>, async_graphql::Error> {
         use crate::entity::prelude::Categories as CategoriesEntity;
         let db = ctx.data::<DatabaseConnection>()?;
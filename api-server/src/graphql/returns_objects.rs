@@ -0,0 +1,414 @@
+use crate::{
+    auth::{Auth, RoleGuard, ROLE_CUSTOMER, ROLE_SUPPLIER},
+    graphql::macros::role_guard,
+    models::{
+        orders::ORDER_STATUS_DELIVERED,
+        products::increment_stock,
+        returns::{
+            find_supplier_return, refund_amount, Refunds, RegisterReturn, ReturnItems,
+            ReturnRequests, REFUND_STATUS_PENDING, RETURN_STATUS_APPROVED, RETURN_STATUS_RECEIVED,
+            RETURN_STATUS_REJECTED, RETURN_STATUS_REQUESTED,
+        },
+        stock_movements::{StockMovement, STOCK_MOVEMENT_RETURN, STOCK_REFERENCE_RETURN},
+        supplier_ledger::reverse_order_item_sale,
+        user::get_customer_supplier_id,
+    },
+};
+use async_graphql::{Context, Object};
+use chrono::Utc;
+use sea_orm::{
+    prelude::Decimal, ActiveModelTrait, ActiveValue::Set, ColumnTrait, DatabaseConnection,
+    EntityTrait, JoinType, QueryFilter, QuerySelect, RelationTrait, TransactionTrait,
+};
+
+#[derive(Default)]
+pub struct ReturnsQuery;
+
+#[derive(Default)]
+pub struct ReturnsMutation;
+
+#[Object]
+impl ReturnsQuery {
+    #[graphql(guard = "role_guard!(ROLE_CUSTOMER)")]
+    async fn returns(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<Vec<ReturnRequests>, async_graphql::Error> {
+        use crate::entity::{prelude::ReturnRequests as ReturnRequestsEntity, return_requests};
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+
+        let customer_id = get_customer_supplier_id(db, token, ROLE_CUSTOMER).await?;
+
+        let return_requests = ReturnRequestsEntity::find()
+            .filter(return_requests::Column::CustomerId.eq(customer_id))
+            .all(db)
+            .await?;
+
+        Ok(return_requests
+            .into_iter()
+            .map(|return_request| return_request.into())
+            .collect())
+    }
+
+    #[graphql(guard = "role_guard!(ROLE_SUPPLIER)")]
+    async fn supplier_returns(
+        &self,
+        ctx: &Context<'_>,
+        status: Option<String>,
+    ) -> Result<Vec<ReturnRequests>, async_graphql::Error> {
+        use crate::entity::{prelude::ReturnRequests as ReturnRequestsEntity, return_requests};
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+
+        let supplier_id = get_customer_supplier_id(db, token, ROLE_SUPPLIER).await?;
+
+        let mut return_requests = ReturnRequestsEntity::find()
+            .filter(return_requests::Column::SupplierId.eq(supplier_id));
+
+        if let Some(status) = status {
+            return_requests = return_requests.filter(return_requests::Column::Status.eq(status));
+        }
+
+        Ok(return_requests
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|return_request| return_request.into())
+            .collect())
+    }
+
+    #[graphql(guard = "role_guard!(ROLE_CUSTOMER, ROLE_SUPPLIER)")]
+    async fn return_items(
+        &self,
+        ctx: &Context<'_>,
+        return_id: i32,
+    ) -> Result<Vec<ReturnItems>, async_graphql::Error> {
+        use crate::entity::{
+            prelude::{ReturnItems as ReturnItemsEntity, ReturnRequests as ReturnRequestsEntity},
+            return_items,
+        };
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+
+        let role = Auth::verify_token(token)?.role;
+        let id = get_customer_supplier_id(db, token, &role).await?;
+
+        let return_request = ReturnRequestsEntity::find_by_id(return_id)
+            .one(db)
+            .await?
+            .ok_or("Return request not found")?;
+
+        let owner_id = match role.as_str() {
+            ROLE_CUSTOMER => Some(return_request.customer_id),
+            _ => return_request.supplier_id,
+        };
+
+        if owner_id != Some(id) {
+            return Err("Unauthorized".into());
+        }
+
+        let return_items = ReturnItemsEntity::find()
+            .filter(return_items::Column::ReturnId.eq(return_id))
+            .all(db)
+            .await?;
+
+        Ok(return_items
+            .into_iter()
+            .map(|return_item| return_item.into())
+            .collect())
+    }
+
+    #[graphql(guard = "role_guard!(ROLE_CUSTOMER)")]
+    async fn refunds(&self, ctx: &Context<'_>) -> Result<Vec<Refunds>, async_graphql::Error> {
+        use crate::entity::{bills, orders, prelude::Refunds as RefundsEntity, refunds};
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+
+        let customer_id = get_customer_supplier_id(db, token, ROLE_CUSTOMER).await?;
+
+        let refunds = RefundsEntity::find()
+            .join(JoinType::InnerJoin, refunds::Relation::Bills.def())
+            .join(JoinType::InnerJoin, bills::Relation::Orders.def())
+            .filter(orders::Column::CustomerId.eq(customer_id))
+            .all(db)
+            .await?;
+
+        Ok(refunds.into_iter().map(|refund| refund.into()).collect())
+    }
+}
+
+#[Object]
+impl ReturnsMutation {
+    #[graphql(guard = "role_guard!(ROLE_CUSTOMER)")]
+    async fn request_return(
+        &self,
+        ctx: &Context<'_>,
+        input: RegisterReturn,
+    ) -> Result<ReturnRequests, async_graphql::Error> {
+        use crate::entity::{
+            prelude::{
+                OrderItems as OrderItemsEntity, Orders as OrdersEntity, Products as ProductsEntity,
+                ReturnItems as ReturnItemsEntity, ReturnRequests as ReturnRequestsEntity,
+            },
+            return_items, return_requests,
+        };
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+        let txn = db.begin().await?;
+
+        let customer_id = get_customer_supplier_id(db, token, ROLE_CUSTOMER).await?;
+
+        let order = OrdersEntity::find_by_id(input.order_id)
+            .one(&txn)
+            .await?
+            .ok_or("Order not found")?;
+
+        if order.customer_id != customer_id {
+            return Err("Unauthorized".into());
+        }
+
+        if order.status != ORDER_STATUS_DELIVERED {
+            return Err("Only delivered orders can be returned".into());
+        }
+
+        if input.items.is_empty() {
+            return Err("At least one item must be returned".into());
+        }
+
+        let mut supplier_id = None;
+
+        for item in &input.items {
+            let order_item = OrderItemsEntity::find_by_id(item.order_item_id)
+                .one(&txn)
+                .await?
+                .filter(|order_item| order_item.order_id == order.order_id)
+                .ok_or("Order item not found in this order")?;
+
+            if item.quantity <= 0 {
+                return Err("Return quantity must be positive".into());
+            }
+
+            // quantities already under an open or completed return can't be returned twice
+            let already_returned: i32 = ReturnItemsEntity::find()
+                .inner_join(ReturnRequestsEntity)
+                .filter(return_items::Column::OrderItemId.eq(order_item.order_item_id))
+                .filter(return_requests::Column::Status.ne(RETURN_STATUS_REJECTED))
+                .all(&txn)
+                .await?
+                .iter()
+                .map(|return_item| return_item.quantity)
+                .sum();
+
+            if already_returned + item.quantity > order_item.quantity {
+                return Err("Return quantity exceeds the quantity ordered".into());
+            }
+
+            let product = ProductsEntity::find_by_id(order_item.product_id)
+                .one(&txn)
+                .await?
+                .ok_or("Product not found")?;
+
+            match supplier_id {
+                None => supplier_id = Some(product.supplier_id),
+                Some(supplier_id) if supplier_id != product.supplier_id => {
+                    return Err("Items from different suppliers must be returned separately".into())
+                }
+                Some(_) => {}
+            }
+        }
+
+        let return_request = return_requests::ActiveModel {
+            order_id: Set(order.order_id),
+            customer_id: Set(customer_id),
+            supplier_id: Set(supplier_id.flatten()),
+            status: Set(RETURN_STATUS_REQUESTED.to_string()),
+            ..Default::default()
+        };
+
+        let insert_return = ReturnRequestsEntity::insert(return_request)
+            .exec_with_returning(&txn)
+            .await?;
+
+        for item in input.items {
+            let return_item = return_items::ActiveModel {
+                return_id: Set(insert_return.return_id),
+                order_item_id: Set(item.order_item_id),
+                quantity: Set(item.quantity),
+                reason: Set(item.reason),
+                ..Default::default()
+            };
+            ReturnItemsEntity::insert(return_item).exec(&txn).await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(insert_return.into())
+    }
+
+    #[graphql(guard = "role_guard!(ROLE_SUPPLIER)")]
+    async fn approve_return(
+        &self,
+        ctx: &Context<'_>,
+        return_id: i32,
+    ) -> Result<ReturnRequests, async_graphql::Error> {
+        use crate::entity::return_requests;
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+        let txn = db.begin().await?;
+
+        let supplier_id = get_customer_supplier_id(db, token, ROLE_SUPPLIER).await?;
+
+        let return_request = find_supplier_return(&txn, supplier_id, return_id).await?;
+
+        if return_request.status != RETURN_STATUS_REQUESTED {
+            return Err("Return request has already been decided".into());
+        }
+
+        let mut return_request: return_requests::ActiveModel = return_request.into();
+        return_request.status = Set(RETURN_STATUS_APPROVED.to_string());
+        return_request.decided_at = Set(Some(Utc::now().fixed_offset()));
+        let return_request = return_request.update(&txn).await?;
+
+        txn.commit().await?;
+
+        Ok(return_request.into())
+    }
+
+    #[graphql(guard = "role_guard!(ROLE_SUPPLIER)")]
+    async fn reject_return(
+        &self,
+        ctx: &Context<'_>,
+        return_id: i32,
+        reason: Option<String>,
+    ) -> Result<ReturnRequests, async_graphql::Error> {
+        use crate::entity::return_requests;
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+        let txn = db.begin().await?;
+
+        let supplier_id = get_customer_supplier_id(db, token, ROLE_SUPPLIER).await?;
+
+        let return_request = find_supplier_return(&txn, supplier_id, return_id).await?;
+
+        if return_request.status != RETURN_STATUS_REQUESTED {
+            return Err("Return request has already been decided".into());
+        }
+
+        let mut return_request: return_requests::ActiveModel = return_request.into();
+        return_request.status = Set(RETURN_STATUS_REJECTED.to_string());
+        return_request.rejection_reason = Set(reason);
+        return_request.decided_at = Set(Some(Utc::now().fixed_offset()));
+        let return_request = return_request.update(&txn).await?;
+
+        txn.commit().await?;
+
+        Ok(return_request.into())
+    }
+
+    #[graphql(guard = "role_guard!(ROLE_SUPPLIER)")]
+    async fn receive_return(
+        &self,
+        ctx: &Context<'_>,
+        return_id: i32,
+    ) -> Result<Refunds, async_graphql::Error> {
+        use crate::entity::{
+            bills,
+            prelude::{
+                Bills as BillsEntity, OrderItems as OrderItemsEntity, Orders as OrdersEntity,
+                Refunds as RefundsEntity, ReturnItems as ReturnItemsEntity,
+            },
+            refunds, return_items, return_requests,
+        };
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+        let txn = db.begin().await?;
+
+        let supplier_id = get_customer_supplier_id(db, token, ROLE_SUPPLIER).await?;
+        let user_id = Auth::verify_token(token)?.user_id.parse::<i32>()?;
+
+        let return_request = find_supplier_return(&txn, supplier_id, return_id).await?;
+
+        if return_request.status != RETURN_STATUS_APPROVED {
+            return Err("Only approved returns can be received".into());
+        }
+
+        let return_items_list = ReturnItemsEntity::find()
+            .filter(return_items::Column::ReturnId.eq(return_id))
+            .all(&txn)
+            .await?;
+
+        let order = OrdersEntity::find_by_id(return_request.order_id)
+            .one(&txn)
+            .await?
+            .ok_or("Order not found")?;
+
+        let mut amount = Decimal::ZERO;
+
+        for return_item in return_items_list {
+            let order_item = OrderItemsEntity::find_by_id(return_item.order_item_id)
+                .one(&txn)
+                .await?
+                .ok_or("Order item not found")?;
+
+            amount += refund_amount(&order_item, return_item.quantity);
+
+            increment_stock(
+                &txn,
+                order_item.product_id,
+                return_item.quantity,
+                &StockMovement::new(STOCK_MOVEMENT_RETURN)
+                    .reference(STOCK_REFERENCE_RETURN, return_id)
+                    .actor(Some(user_id))
+                    .warehouse(order_item.warehouse_id),
+            )
+            .await?;
+            reverse_order_item_sale(&txn, &order, &order_item, return_item.quantity).await?;
+        }
+
+        let bill = BillsEntity::find()
+            .filter(bills::Column::OrderId.eq(order.order_id))
+            .one(&txn)
+            .await?
+            .ok_or("No bill found for this order")?;
+
+        let refund = refunds::ActiveModel {
+            return_id: Set(Some(return_id)),
+            bill_id: Set(bill.bill_id),
+            payment_method_id: Set(order.payment_method_id),
+            amount: Set(amount),
+            currency: Set(bill.currency.clone()),
+            status: Set(REFUND_STATUS_PENDING.to_string()),
+            ..Default::default()
+        };
+
+        let insert_refund = RefundsEntity::insert(refund)
+            .exec_with_returning(&txn)
+            .await?;
+
+        let mut return_request: return_requests::ActiveModel = return_request.into();
+        return_request.status = Set(RETURN_STATUS_RECEIVED.to_string());
+        return_request.received_at = Set(Some(Utc::now().fixed_offset()));
+        return_request.update(&txn).await?;
+
+        txn.commit().await?;
+
+        Ok(insert_refund.into())
+    }
+}
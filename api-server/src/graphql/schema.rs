@@ -1,11 +1,26 @@
 use crate::graphql::{
     addresses_objects::{AddressesMutation, AddressesQuery},
     carts_objects::{CartsMutation, CartsQuery},
+    currency_objects::{CurrencyMutation, CurrencyQuery},
+    gift_cards_objects::{GiftCardsMutation, GiftCardsQuery},
     orders_objects::{OrdersMutation, OrdersQuery},
+    payment_intents_objects::{PaymentIntentsMutation, PaymentIntentsQuery},
     payments_objects::{PaymentsMutation, PaymentsQuery},
     products_objects::{products_mutations::ProductsMutation, products_query::ProductsQuery},
+    returns_objects::{ReturnsMutation, ReturnsQuery},
+    shipments_objects::{ShipmentsMutation, ShipmentsQuery},
+    shipping_objects::{ShippingMutation, ShippingQuery},
+    stock_alerts_objects::{StockAlertsMutation, StockAlertsQuery},
+    stock_movements_objects::{StockMovementsMutation, StockMovementsQuery},
+    supplier_analytics_objects::SupplierAnalyticsQuery,
+    supplier_ledger_objects::{SupplierLedgerMutation, SupplierLedgerQuery},
+    taxes_objects::{TaxesMutation, TaxesQuery},
     users_objects::{UsersMutation, UsersQuery},
+    wallet_objects::{WalletMutation, WalletQuery},
+    warehouses_objects::{WarehousesMutation, WarehousesQuery},
 };
+use crate::models::orders::IdempotencyKey;
+use crate::payment_gateway::SharedPaymentProvider;
 use async_graphql::{http::GraphiQLSource, EmptySubscription, MergedObject, Schema};
 use async_graphql_axum::GraphQLRequest;
 use axum::{
@@ -21,29 +36,55 @@ pub type AppSchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
 pub struct QueryRoot(
     AddressesQuery,
     CartsQuery,
+    CurrencyQuery,
+    GiftCardsQuery,
     OrdersQuery,
+    PaymentIntentsQuery,
     PaymentsQuery,
     ProductsQuery,
+    ReturnsQuery,
+    ShipmentsQuery,
+    ShippingQuery,
+    StockAlertsQuery,
+    StockMovementsQuery,
+    SupplierAnalyticsQuery,
+    SupplierLedgerQuery,
+    TaxesQuery,
     UsersQuery,
+    WalletQuery,
+    WarehousesQuery,
 );
 
 #[derive(MergedObject, Default)]
 pub struct MutationRoot(
     AddressesMutation,
     CartsMutation,
+    CurrencyMutation,
+    GiftCardsMutation,
     OrdersMutation,
+    PaymentIntentsMutation,
     PaymentsMutation,
     ProductsMutation,
+    ReturnsMutation,
+    ShipmentsMutation,
+    ShippingMutation,
+    StockAlertsMutation,
+    StockMovementsMutation,
+    SupplierLedgerMutation,
+    TaxesMutation,
     UsersMutation,
+    WalletMutation,
+    WarehousesMutation,
 );
 
-pub fn create_schema(db: DatabaseConnection) -> AppSchema {
+pub fn create_schema(db: DatabaseConnection, payment_provider: SharedPaymentProvider) -> AppSchema {
     Schema::build(
         QueryRoot::default(),
         MutationRoot::default(),
         EmptySubscription,
     )
     .data(db)
+    .data(payment_provider)
     .finish()
 }
 
@@ -69,6 +110,13 @@ pub async fn graphql_handler(
         request = request.data(token);
     }
 
+    if let Some(idempotency_key) = headers
+        .get("idempotency-key")
+        .and_then(|value| value.to_str().ok())
+    {
+        request = request.data(IdempotencyKey(idempotency_key.to_string()));
+    }
+
     let response = schema.execute(request).await;
     Json(response)
 }
@@ -0,0 +1,157 @@
+use crate::{
+    auth::{RoleGuard, ROLE_CUSTOMER, ROLE_SUPPLIER},
+    graphql::macros::role_guard,
+    models::{
+        orders::{
+            check_if_supplier_in_order, ORDER_STATUS_PAID, ORDER_STATUS_PENDING,
+            ORDER_STATUS_SHIPPED,
+        },
+        shipments::{RegisterShipment, ShipmentTracking, Shipments, SHIPMENT_STATUS_LABEL_CREATED},
+        user::get_customer_supplier_id,
+    },
+};
+use async_graphql::{Context, Object};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    QueryOrder, TransactionTrait,
+};
+
+#[derive(Default)]
+pub struct ShipmentsQuery;
+
+#[derive(Default)]
+pub struct ShipmentsMutation;
+
+#[Object]
+impl ShipmentsQuery {
+    #[graphql(guard = "role_guard!(ROLE_CUSTOMER)")]
+    async fn track_shipment(
+        &self,
+        ctx: &Context<'_>,
+        order_id: i32,
+    ) -> Result<Vec<ShipmentTracking>, async_graphql::Error> {
+        use crate::entity::{
+            prelude::{
+                Orders as OrdersEntity, ShipmentEvents as ShipmentEventsEntity,
+                Shipments as ShipmentsEntity,
+            },
+            shipment_events, shipments,
+        };
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+
+        let customer_id = get_customer_supplier_id(db, token, ROLE_CUSTOMER).await?;
+
+        let order = OrdersEntity::find_by_id(order_id)
+            .one(db)
+            .await?
+            .ok_or("Order not found")?;
+
+        if order.customer_id != customer_id {
+            return Err("Unauthorized".into());
+        }
+
+        let shipments = ShipmentsEntity::find()
+            .filter(shipments::Column::OrderId.eq(order_id))
+            .order_by_asc(shipments::Column::ShippedAt)
+            .all(db)
+            .await?;
+
+        let mut tracking = Vec::new();
+
+        for shipment in shipments {
+            let events = ShipmentEventsEntity::find()
+                .filter(shipment_events::Column::ShipmentId.eq(shipment.shipment_id))
+                .order_by_asc(shipment_events::Column::OccurredAt)
+                .all(db)
+                .await?;
+
+            tracking.push(ShipmentTracking {
+                shipment: shipment.into(),
+                events: events.into_iter().map(|event| event.into()).collect(),
+            });
+        }
+
+        Ok(tracking)
+    }
+}
+
+#[Object]
+impl ShipmentsMutation {
+    #[graphql(guard = "role_guard!(ROLE_SUPPLIER)")]
+    async fn register_shipment(
+        &self,
+        ctx: &Context<'_>,
+        input: RegisterShipment,
+    ) -> Result<Shipments, async_graphql::Error> {
+        use crate::entity::{
+            orders,
+            prelude::{
+                Orders as OrdersEntity, PaymentMethods as PaymentMethodsEntity,
+                ShipmentEvents as ShipmentEventsEntity, Shipments as ShipmentsEntity,
+            },
+            sea_orm_active_enums::PaymentMethodType,
+            shipment_events, shipments,
+        };
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+        let txn = db.begin().await?;
+
+        let supplier_id = get_customer_supplier_id(db, token, ROLE_SUPPLIER).await?;
+
+        check_if_supplier_in_order(&txn, supplier_id, input.order_id).await?;
+
+        let order = OrdersEntity::find_by_id(input.order_id)
+            .one(&txn)
+            .await?
+            .ok_or("Order not found")?;
+
+        // only paid orders are sent, except cash on delivery which is paid when it arrives. An order
+        // split between suppliers is already shipped when the later ones send their part.
+        let cash_on_delivery = PaymentMethodsEntity::find_by_id(order.payment_method_id)
+            .one(&txn)
+            .await?
+            .is_some_and(|payment_method| payment_method.payment_type == PaymentMethodType::Cod);
+        let can_ship = order.status == ORDER_STATUS_PAID
+            || order.status == ORDER_STATUS_SHIPPED
+            || (order.status == ORDER_STATUS_PENDING && cash_on_delivery);
+        if !can_ship {
+            return Err("Only paid or cash on delivery orders can be shipped".into());
+        }
+
+        let shipment = shipments::ActiveModel {
+            order_id: Set(input.order_id),
+            supplier_id: Set(Some(supplier_id)),
+            carrier: Set(input.carrier),
+            tracking_number: Set(input.tracking_number),
+            status: Set(SHIPMENT_STATUS_LABEL_CREATED.to_string()),
+            ..Default::default()
+        };
+
+        let insert_shipment = ShipmentsEntity::insert(shipment)
+            .exec_with_returning(&txn)
+            .await?;
+
+        let event = shipment_events::ActiveModel {
+            shipment_id: Set(insert_shipment.shipment_id),
+            status: Set(SHIPMENT_STATUS_LABEL_CREATED.to_string()),
+            occurred_at: Set(chrono::Utc::now().fixed_offset()),
+            ..Default::default()
+        };
+        ShipmentEventsEntity::insert(event).exec(&txn).await?;
+
+        if order.status != ORDER_STATUS_SHIPPED {
+            let mut order: orders::ActiveModel = order.into();
+            order.status = Set(ORDER_STATUS_SHIPPED.to_string());
+            order.update(&txn).await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(insert_shipment.into())
+    }
+}
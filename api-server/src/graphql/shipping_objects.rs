@@ -0,0 +1,210 @@
+use crate::{
+    auth::{RoleGuard, ROLE_CUSTOMER, ROLE_SUPPLIER},
+    graphql::macros::role_guard,
+    models::{
+        currency::convert,
+        shipping::{
+            create_shipping_profile_model, lines_by_supplier, supplier_delivery_options,
+            DeliveryOptions, RegisterShippingProfile, ShippingProfiles,
+        },
+        user::get_customer_supplier_id,
+    },
+    money::{currency_code, Money},
+};
+use async_graphql::{Context, Object};
+use sea_orm::{
+    ActiveValue::Set, ColumnTrait, DatabaseConnection, EntityTrait, ModelTrait, QueryFilter,
+    QueryOrder,
+};
+
+#[derive(Default)]
+pub struct ShippingQuery;
+
+#[derive(Default)]
+pub struct ShippingMutation;
+
+#[Object]
+impl ShippingQuery {
+    async fn shipping_profiles(
+        &self,
+        ctx: &Context<'_>,
+        supplier_id: i32,
+    ) -> Result<Vec<ShippingProfiles>, async_graphql::Error> {
+        use crate::entity::{
+            prelude::ShippingProfiles as ShippingProfilesEntity, shipping_profiles,
+        };
+        let db = ctx.data::<DatabaseConnection>()?;
+
+        let shipping_profiles = ShippingProfilesEntity::find()
+            .filter(shipping_profiles::Column::SupplierId.eq(supplier_id))
+            .order_by_asc(shipping_profiles::Column::ShippingProfileId)
+            .all(db)
+            .await?;
+
+        Ok(shipping_profiles
+            .into_iter()
+            .map(|shipping_profile| shipping_profile.into())
+            .collect())
+    }
+
+    // every supplier in the cart ships their own items, so the options are listed per supplier
+    #[graphql(guard = "role_guard!(ROLE_CUSTOMER)")]
+    async fn delivery_options(
+        &self,
+        ctx: &Context<'_>,
+        shipping_address_id: i32,
+        currency: Option<String>,
+    ) -> Result<Vec<DeliveryOptions>, async_graphql::Error> {
+        use crate::entity::{
+            cart_items,
+            prelude::{
+                Addresses as AddressesEntity, CartItems as CartItemsEntity,
+                Products as ProductsEntity, ShoppingCarts as ShoppingCartsEntity,
+            },
+            shopping_carts,
+        };
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+
+        let customer_id = get_customer_supplier_id(db, token, ROLE_CUSTOMER).await?;
+
+        let currency = match currency {
+            Some(currency) => Some(currency_code(&currency).ok_or("Invalid currency code")?),
+            None => None,
+        };
+
+        let address = AddressesEntity::find_by_id(shipping_address_id)
+            .one(db)
+            .await?
+            .filter(|address| address.customer_id == customer_id)
+            .ok_or("Address not found")?;
+
+        let cart = ShoppingCartsEntity::find()
+            .filter(shopping_carts::Column::CustomerId.eq(customer_id))
+            .one(db)
+            .await?
+            .ok_or("Cart not found")?;
+
+        let cart_items = CartItemsEntity::find()
+            .filter(cart_items::Column::CartId.eq(cart.cart_id))
+            .find_also_related(ProductsEntity)
+            .all(db)
+            .await?;
+
+        let lines = cart_items
+            .into_iter()
+            .filter_map(|(cart_item, product)| product.map(|product| (product, cart_item.quantity)))
+            .collect();
+
+        let mut delivery_options = Vec::new();
+        for (supplier_id, supplier_lines) in lines_by_supplier(lines) {
+            for (profile, cost) in
+                supplier_delivery_options(db, supplier_id, &address, &supplier_lines).await?
+            {
+                let cost = match &currency {
+                    Some(currency) => Money::new(
+                        convert(db, cost, &profile.currency, currency).await?,
+                        currency,
+                    ),
+                    None => Money::new(cost, &profile.currency),
+                };
+
+                delivery_options.push(DeliveryOptions {
+                    shipping_profile_id: profile.shipping_profile_id,
+                    supplier_id,
+                    name: profile.name,
+                    cost,
+                    estimated_days: profile.estimated_days,
+                });
+            }
+        }
+
+        Ok(delivery_options)
+    }
+}
+
+#[Object]
+impl ShippingMutation {
+    #[graphql(guard = "role_guard!(ROLE_SUPPLIER)")]
+    async fn register_shipping_profile(
+        &self,
+        ctx: &Context<'_>,
+        input: RegisterShippingProfile,
+    ) -> Result<ShippingProfiles, async_graphql::Error> {
+        use crate::entity::prelude::ShippingProfiles as ShippingProfilesEntity;
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+
+        let supplier_id = get_customer_supplier_id(db, token, ROLE_SUPPLIER).await?;
+
+        let shipping_profile = create_shipping_profile_model(input, supplier_id)?;
+        let insert_shipping_profile = ShippingProfilesEntity::insert(shipping_profile)
+            .exec_with_returning(db)
+            .await?;
+
+        Ok(insert_shipping_profile.into())
+    }
+
+    #[graphql(guard = "role_guard!(ROLE_SUPPLIER)")]
+    async fn update_shipping_profile(
+        &self,
+        ctx: &Context<'_>,
+        shipping_profile_id: i32,
+        input: RegisterShippingProfile,
+    ) -> Result<ShippingProfiles, async_graphql::Error> {
+        use crate::entity::{
+            prelude::ShippingProfiles as ShippingProfilesEntity, shipping_profiles,
+        };
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+
+        let supplier_id = get_customer_supplier_id(db, token, ROLE_SUPPLIER).await?;
+
+        ShippingProfilesEntity::find_by_id(shipping_profile_id)
+            .one(db)
+            .await?
+            .filter(|shipping_profile| shipping_profile.supplier_id == supplier_id)
+            .ok_or("Shipping profile not found")?;
+
+        let mut shipping_profile = create_shipping_profile_model(input, supplier_id)?;
+        shipping_profile.shipping_profile_id = Set(shipping_profile_id);
+
+        let update_shipping_profile = ShippingProfilesEntity::update(shipping_profile)
+            .filter(shipping_profiles::Column::ShippingProfileId.eq(shipping_profile_id))
+            .exec(db)
+            .await?;
+
+        Ok(update_shipping_profile.into())
+    }
+
+    #[graphql(guard = "role_guard!(ROLE_SUPPLIER)")]
+    async fn delete_shipping_profile(
+        &self,
+        ctx: &Context<'_>,
+        shipping_profile_id: i32,
+    ) -> Result<String, async_graphql::Error> {
+        use crate::entity::prelude::ShippingProfiles as ShippingProfilesEntity;
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+
+        let supplier_id = get_customer_supplier_id(db, token, ROLE_SUPPLIER).await?;
+
+        ShippingProfilesEntity::find_by_id(shipping_profile_id)
+            .one(db)
+            .await?
+            .filter(|shipping_profile| shipping_profile.supplier_id == supplier_id)
+            .ok_or("Shipping profile not found")?
+            .delete(db)
+            .await?;
+
+        Ok("Shipping profile deleted".to_string())
+    }
+}
@@ -0,0 +1,179 @@
+use crate::{
+    auth::{RoleGuard, ROLE_CUSTOMER, ROLE_SUPPLIER},
+    graphql::macros::role_guard,
+    models::{
+        products::check_product_on_sale,
+        stock_alerts::{StockSubscriptions, SupplierNotifications},
+        user::get_customer_supplier_id,
+    },
+};
+use async_graphql::{Context, Object};
+use sea_orm::{
+    ActiveValue::Set, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+};
+
+#[derive(Default)]
+pub struct StockAlertsQuery;
+
+#[derive(Default)]
+pub struct StockAlertsMutation;
+
+#[Object]
+impl StockAlertsQuery {
+    #[graphql(guard = "role_guard!(ROLE_SUPPLIER)")]
+    async fn supplier_notifications(
+        &self,
+        ctx: &Context<'_>,
+        unread_only: Option<bool>,
+    ) -> Result<Vec<SupplierNotifications>, async_graphql::Error> {
+        use crate::entity::{
+            prelude::SupplierNotifications as SupplierNotificationsEntity, supplier_notifications,
+        };
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+
+        let supplier_id = get_customer_supplier_id(db, token, ROLE_SUPPLIER).await?;
+
+        let mut notifications = SupplierNotificationsEntity::find()
+            .filter(supplier_notifications::Column::SupplierId.eq(supplier_id));
+        if unread_only.unwrap_or(false) {
+            notifications = notifications.filter(supplier_notifications::Column::IsRead.eq(false));
+        }
+
+        let notifications = notifications
+            .order_by_desc(supplier_notifications::Column::NotificationId)
+            .all(db)
+            .await?;
+
+        Ok(notifications
+            .into_iter()
+            .map(|notification| notification.into())
+            .collect())
+    }
+
+    #[graphql(guard = "role_guard!(ROLE_CUSTOMER)")]
+    async fn my_stock_subscriptions(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<Vec<StockSubscriptions>, async_graphql::Error> {
+        use crate::entity::{
+            prelude::StockSubscriptions as StockSubscriptionsEntity, stock_subscriptions,
+        };
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+
+        let customer_id = get_customer_supplier_id(db, token, ROLE_CUSTOMER).await?;
+
+        let subscriptions = StockSubscriptionsEntity::find()
+            .filter(stock_subscriptions::Column::CustomerId.eq(customer_id))
+            .order_by_desc(stock_subscriptions::Column::CreatedAt)
+            .all(db)
+            .await?;
+
+        Ok(subscriptions
+            .into_iter()
+            .map(|subscription| subscription.into())
+            .collect())
+    }
+}
+
+#[Object]
+impl StockAlertsMutation {
+    #[graphql(guard = "role_guard!(ROLE_SUPPLIER)")]
+    async fn mark_supplier_notifications_read(
+        &self,
+        ctx: &Context<'_>,
+        notification_ids: Vec<i32>,
+    ) -> Result<String, async_graphql::Error> {
+        use crate::entity::{
+            prelude::SupplierNotifications as SupplierNotificationsEntity, supplier_notifications,
+        };
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+
+        let supplier_id = get_customer_supplier_id(db, token, ROLE_SUPPLIER).await?;
+
+        SupplierNotificationsEntity::update_many()
+            .col_expr(supplier_notifications::Column::IsRead, true.into())
+            .filter(supplier_notifications::Column::SupplierId.eq(supplier_id))
+            .filter(supplier_notifications::Column::NotificationId.is_in(notification_ids))
+            .exec(db)
+            .await?;
+
+        Ok("Notifications marked as read".to_string())
+    }
+
+    // the customer is mailed once when the product is restocked
+    #[graphql(guard = "role_guard!(ROLE_CUSTOMER)")]
+    async fn subscribe_back_in_stock(
+        &self,
+        ctx: &Context<'_>,
+        product_id: i32,
+    ) -> Result<StockSubscriptions, async_graphql::Error> {
+        use crate::entity::{
+            prelude::StockSubscriptions as StockSubscriptionsEntity, stock_subscriptions,
+        };
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+
+        let customer_id = get_customer_supplier_id(db, token, ROLE_CUSTOMER).await?;
+
+        let product = check_product_on_sale(db, product_id).await?;
+        if product.stock_quantity > 0 {
+            return Err("Product is in stock".into());
+        }
+
+        if let Some(subscription) = StockSubscriptionsEntity::find()
+            .filter(stock_subscriptions::Column::CustomerId.eq(customer_id))
+            .filter(stock_subscriptions::Column::ProductId.eq(product_id))
+            .one(db)
+            .await?
+        {
+            return Ok(subscription.into());
+        }
+
+        let subscription = stock_subscriptions::ActiveModel {
+            customer_id: Set(customer_id),
+            product_id: Set(product_id),
+            ..Default::default()
+        };
+
+        Ok(StockSubscriptionsEntity::insert(subscription)
+            .exec_with_returning(db)
+            .await?
+            .into())
+    }
+
+    #[graphql(guard = "role_guard!(ROLE_CUSTOMER)")]
+    async fn unsubscribe_back_in_stock(
+        &self,
+        ctx: &Context<'_>,
+        product_id: i32,
+    ) -> Result<String, async_graphql::Error> {
+        use crate::entity::{
+            prelude::StockSubscriptions as StockSubscriptionsEntity, stock_subscriptions,
+        };
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+
+        let customer_id = get_customer_supplier_id(db, token, ROLE_CUSTOMER).await?;
+
+        StockSubscriptionsEntity::delete_many()
+            .filter(stock_subscriptions::Column::CustomerId.eq(customer_id))
+            .filter(stock_subscriptions::Column::ProductId.eq(product_id))
+            .exec(db)
+            .await?;
+
+        Ok("Unsubscribed".to_string())
+    }
+}
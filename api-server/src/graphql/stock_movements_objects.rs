@@ -0,0 +1,109 @@
+use crate::{
+    auth::{Auth, RoleGuard, ROLE_ADMIN, ROLE_SUPPLIER},
+    graphql::macros::role_guard,
+    models::{
+        products::{check_if_supplier_owns_product, Products},
+        stock_alerts::notify_back_in_stock,
+        stock_movements::{change_stock, StockMovement, StockMovements, STOCK_MOVEMENT_ADJUSTMENT},
+        user::get_customer_supplier_id,
+        warehouses::find_supplier_warehouse,
+    },
+};
+use async_graphql::{Context, Object};
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, TransactionTrait,
+};
+
+#[derive(Default)]
+pub struct StockMovementsQuery;
+
+#[derive(Default)]
+pub struct StockMovementsMutation;
+
+#[Object]
+impl StockMovementsQuery {
+    // newest first, suppliers only see the history of their own products
+    #[graphql(guard = "role_guard!(ROLE_SUPPLIER, ROLE_ADMIN)")]
+    async fn stock_movements(
+        &self,
+        ctx: &Context<'_>,
+        product_id: i32,
+    ) -> Result<Vec<StockMovements>, async_graphql::Error> {
+        use crate::entity::{prelude::StockMovements as StockMovementsEntity, stock_movements};
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+
+        if Auth::verify_token(token)?.role != ROLE_ADMIN {
+            let supplier_id = get_customer_supplier_id(db, token, ROLE_SUPPLIER).await?;
+            check_if_supplier_owns_product(db, supplier_id, product_id).await?;
+        }
+
+        let stock_movements = StockMovementsEntity::find()
+            .filter(stock_movements::Column::ProductId.eq(product_id))
+            .order_by_desc(stock_movements::Column::StockMovementId)
+            .all(db)
+            .await?;
+
+        Ok(stock_movements
+            .into_iter()
+            .map(|stock_movement| stock_movement.into())
+            .collect())
+    }
+}
+
+#[Object]
+impl StockMovementsMutation {
+    // a count correction, damaged goods or a delivery, suppliers with warehouses can name the one
+    // it happened in
+    #[graphql(guard = "role_guard!(ROLE_SUPPLIER)")]
+    async fn adjust_stock(
+        &self,
+        ctx: &Context<'_>,
+        product_id: i32,
+        quantity: i32,
+        note: Option<String>,
+        warehouse_id: Option<i32>,
+    ) -> Result<Products, async_graphql::Error> {
+        use crate::entity::prelude::Products as ProductsEntity;
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+
+        let supplier_id = get_customer_supplier_id(db, token, ROLE_SUPPLIER).await?;
+        let user_id = Auth::verify_token(token)?.user_id.parse::<i32>()?;
+        check_if_supplier_owns_product(db, supplier_id, product_id).await?;
+
+        if quantity == 0 {
+            return Err("Quantity cannot be zero".into());
+        }
+        if let Some(warehouse_id) = warehouse_id {
+            find_supplier_warehouse(db, supplier_id, warehouse_id).await?;
+        }
+
+        let txn = db.begin().await?;
+        change_stock(
+            &txn,
+            product_id,
+            quantity,
+            &StockMovement::new(STOCK_MOVEMENT_ADJUSTMENT)
+                .actor(Some(user_id))
+                .note(note)
+                .warehouse(warehouse_id),
+        )
+        .await?
+        .ok_or("Insufficient stock")?;
+
+        let product = ProductsEntity::find_by_id(product_id)
+            .one(&txn)
+            .await?
+            .ok_or("Product not found")?;
+        txn.commit().await?;
+
+        notify_back_in_stock(db, product_id).await?;
+
+        Ok(product.into())
+    }
+}
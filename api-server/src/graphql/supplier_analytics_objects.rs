@@ -0,0 +1,91 @@
+use crate::{
+    auth::{RoleGuard, ROLE_SUPPLIER},
+    graphql::macros::role_guard,
+    models::{
+        supplier_analytics::{
+            rating_trend, sales_by_period, sales_summary, top_products, RatingPeriod,
+            SalesInterval, SalesPeriod, SalesSummary, TopProduct,
+        },
+        user::get_customer_supplier_id,
+    },
+};
+use async_graphql::{Context, Object};
+use sea_orm::{prelude::DateTimeWithTimeZone, DatabaseConnection};
+
+#[derive(Default)]
+pub struct SupplierAnalyticsQuery;
+
+// every range is `from` inclusive and `to` exclusive, either end can be left open
+#[Object]
+impl SupplierAnalyticsQuery {
+    #[graphql(guard = "role_guard!(ROLE_SUPPLIER)")]
+    async fn supplier_sales(
+        &self,
+        ctx: &Context<'_>,
+        interval: SalesInterval,
+        from: Option<DateTimeWithTimeZone>,
+        to: Option<DateTimeWithTimeZone>,
+    ) -> Result<Vec<SalesPeriod>, async_graphql::Error> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+
+        let supplier_id = get_customer_supplier_id(db, token, ROLE_SUPPLIER).await?;
+
+        Ok(sales_by_period(db, supplier_id, interval, from, to).await?)
+    }
+
+    #[graphql(guard = "role_guard!(ROLE_SUPPLIER)")]
+    async fn supplier_sales_summary(
+        &self,
+        ctx: &Context<'_>,
+        from: Option<DateTimeWithTimeZone>,
+        to: Option<DateTimeWithTimeZone>,
+    ) -> Result<SalesSummary, async_graphql::Error> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+
+        let supplier_id = get_customer_supplier_id(db, token, ROLE_SUPPLIER).await?;
+
+        Ok(sales_summary(db, supplier_id, from, to).await?)
+    }
+
+    #[graphql(guard = "role_guard!(ROLE_SUPPLIER)")]
+    async fn supplier_top_products(
+        &self,
+        ctx: &Context<'_>,
+        from: Option<DateTimeWithTimeZone>,
+        to: Option<DateTimeWithTimeZone>,
+        #[graphql(default = 10)] limit: u64,
+    ) -> Result<Vec<TopProduct>, async_graphql::Error> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+
+        let supplier_id = get_customer_supplier_id(db, token, ROLE_SUPPLIER).await?;
+
+        Ok(top_products(db, supplier_id, from, to, limit).await?)
+    }
+
+    #[graphql(guard = "role_guard!(ROLE_SUPPLIER)")]
+    async fn supplier_rating_trend(
+        &self,
+        ctx: &Context<'_>,
+        interval: SalesInterval,
+        from: Option<DateTimeWithTimeZone>,
+        to: Option<DateTimeWithTimeZone>,
+    ) -> Result<Vec<RatingPeriod>, async_graphql::Error> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+
+        let supplier_id = get_customer_supplier_id(db, token, ROLE_SUPPLIER).await?;
+
+        Ok(rating_trend(db, supplier_id, interval, from, to).await?)
+    }
+}
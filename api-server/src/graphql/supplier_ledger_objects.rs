@@ -0,0 +1,233 @@
+use crate::{
+    auth::{Auth, RoleGuard, ROLE_ADMIN, ROLE_SUPPLIER},
+    graphql::macros::role_guard,
+    models::{
+        products::Categories,
+        supplier_ledger::{
+            create_payout_statement, record_supplier_adjustment, supplier_balance, SupplierBalance,
+            SupplierLedgerEntries, SupplierPayouts, PAYOUT_STATUS_PAID, PAYOUT_STATUS_PENDING,
+        },
+        user::get_customer_supplier_id,
+    },
+    money::{Money, DEFAULT_CURRENCY},
+};
+use async_graphql::{Context, Object};
+use chrono::Utc;
+use sea_orm::{
+    prelude::{DateTimeWithTimeZone, Decimal},
+    ActiveModelTrait,
+    ActiveValue::Set,
+    ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, TransactionTrait,
+};
+
+#[derive(Default)]
+pub struct SupplierLedgerQuery;
+
+#[derive(Default)]
+pub struct SupplierLedgerMutation;
+
+// suppliers only see their own books, admins pick the supplier
+async fn ledger_supplier_id(
+    ctx: &Context<'_>,
+    supplier_id: Option<i32>,
+) -> Result<i32, async_graphql::Error> {
+    let db = ctx.data::<DatabaseConnection>()?;
+    let token = ctx
+        .data_opt::<String>()
+        .ok_or("No authorization token found")?;
+
+    if Auth::verify_token(token)?.role == ROLE_ADMIN {
+        supplier_id.ok_or_else(|| "Supplier id is required".into())
+    } else {
+        get_customer_supplier_id(db, token, ROLE_SUPPLIER).await
+    }
+}
+
+#[Object]
+impl SupplierLedgerQuery {
+    #[graphql(guard = "role_guard!(ROLE_SUPPLIER, ROLE_ADMIN)")]
+    async fn supplier_balance(
+        &self,
+        ctx: &Context<'_>,
+        supplier_id: Option<i32>,
+    ) -> Result<SupplierBalance, async_graphql::Error> {
+        let db = ctx.data::<DatabaseConnection>()?;
+        let supplier_id = ledger_supplier_id(ctx, supplier_id).await?;
+
+        Ok(supplier_balance(db, supplier_id).await?)
+    }
+
+    #[graphql(guard = "role_guard!(ROLE_SUPPLIER, ROLE_ADMIN)")]
+    async fn supplier_ledger(
+        &self,
+        ctx: &Context<'_>,
+        supplier_id: Option<i32>,
+        from: Option<DateTimeWithTimeZone>,
+        to: Option<DateTimeWithTimeZone>,
+    ) -> Result<Vec<SupplierLedgerEntries>, async_graphql::Error> {
+        use crate::entity::{
+            prelude::SupplierLedgerEntries as SupplierLedgerEntriesEntity, supplier_ledger_entries,
+        };
+        let db = ctx.data::<DatabaseConnection>()?;
+        let supplier_id = ledger_supplier_id(ctx, supplier_id).await?;
+
+        let mut ledger_entries = SupplierLedgerEntriesEntity::find()
+            .filter(supplier_ledger_entries::Column::SupplierId.eq(supplier_id));
+        if let Some(from) = from {
+            ledger_entries =
+                ledger_entries.filter(supplier_ledger_entries::Column::CreatedAt.gte(from));
+        }
+        if let Some(to) = to {
+            ledger_entries =
+                ledger_entries.filter(supplier_ledger_entries::Column::CreatedAt.lte(to));
+        }
+
+        let ledger_entries = ledger_entries
+            .order_by_desc(supplier_ledger_entries::Column::CreatedAt)
+            .all(db)
+            .await?;
+
+        Ok(ledger_entries
+            .into_iter()
+            .map(|ledger_entry| ledger_entry.into())
+            .collect())
+    }
+
+    #[graphql(guard = "role_guard!(ROLE_SUPPLIER, ROLE_ADMIN)")]
+    async fn supplier_payouts(
+        &self,
+        ctx: &Context<'_>,
+        supplier_id: Option<i32>,
+    ) -> Result<Vec<SupplierPayouts>, async_graphql::Error> {
+        use crate::entity::{prelude::SupplierPayouts as SupplierPayoutsEntity, supplier_payouts};
+        let db = ctx.data::<DatabaseConnection>()?;
+        let supplier_id = ledger_supplier_id(ctx, supplier_id).await?;
+
+        let supplier_payouts = SupplierPayoutsEntity::find()
+            .filter(supplier_payouts::Column::SupplierId.eq(supplier_id))
+            .order_by_desc(supplier_payouts::Column::CreatedAt)
+            .all(db)
+            .await?;
+
+        Ok(supplier_payouts
+            .into_iter()
+            .map(|supplier_payout| supplier_payout.into())
+            .collect())
+    }
+}
+
+#[Object]
+impl SupplierLedgerMutation {
+    // percent of the item amount the platform keeps, without one the parent category's rate applies
+    #[graphql(guard = "role_guard!(ROLE_ADMIN)")]
+    async fn set_category_commission(
+        &self,
+        ctx: &Context<'_>,
+        category_id: i32,
+        commission_rate: Option<Decimal>,
+    ) -> Result<Categories, async_graphql::Error> {
+        use crate::entity::{categories, prelude::Categories as CategoriesEntity};
+        let db = ctx.data::<DatabaseConnection>()?;
+
+        if commission_rate.is_some_and(|rate| rate < Decimal::ZERO || rate > Decimal::ONE_HUNDRED) {
+            return Err("Commission rate must be between 0 and 100".into());
+        }
+
+        let category = CategoriesEntity::find_by_id(category_id)
+            .one(db)
+            .await?
+            .ok_or("Category not found")?;
+
+        let mut category: categories::ActiveModel = category.into();
+        category.commission_rate = Set(commission_rate);
+
+        Ok(category.update(db).await?.into())
+    }
+
+    #[graphql(guard = "role_guard!(ROLE_ADMIN)")]
+    async fn record_supplier_adjustment(
+        &self,
+        ctx: &Context<'_>,
+        supplier_id: i32,
+        amount: Money,
+        note: String,
+    ) -> Result<SupplierBalance, async_graphql::Error> {
+        use crate::entity::prelude::Suppliers as SuppliersEntity;
+        let db = ctx.data::<DatabaseConnection>()?;
+        let txn = db.begin().await?;
+
+        if amount.currency != DEFAULT_CURRENCY {
+            return Err(format!("Supplier ledgers are kept in {}", DEFAULT_CURRENCY).into());
+        }
+
+        if amount.amount.is_zero() {
+            return Err("Amount cannot be zero".into());
+        }
+
+        SuppliersEntity::find_by_id(supplier_id)
+            .one(&txn)
+            .await?
+            .ok_or("Supplier not found")?;
+
+        record_supplier_adjustment(&txn, supplier_id, amount.amount, note).await?;
+
+        let balance = supplier_balance(&txn, supplier_id).await?;
+
+        txn.commit().await?;
+
+        Ok(balance)
+    }
+
+    #[graphql(guard = "role_guard!(ROLE_ADMIN)")]
+    async fn create_supplier_payout(
+        &self,
+        ctx: &Context<'_>,
+        supplier_id: i32,
+        period_end: Option<DateTimeWithTimeZone>,
+    ) -> Result<SupplierPayouts, async_graphql::Error> {
+        use crate::entity::prelude::Suppliers as SuppliersEntity;
+        let db = ctx.data::<DatabaseConnection>()?;
+        let txn = db.begin().await?;
+
+        SuppliersEntity::find_by_id(supplier_id)
+            .one(&txn)
+            .await?
+            .ok_or("Supplier not found")?;
+
+        let supplier_payout = create_payout_statement(
+            &txn,
+            supplier_id,
+            period_end.unwrap_or_else(|| Utc::now().fixed_offset()),
+        )
+        .await?;
+
+        txn.commit().await?;
+
+        Ok(supplier_payout.into())
+    }
+
+    #[graphql(guard = "role_guard!(ROLE_ADMIN)")]
+    async fn mark_supplier_payout_paid(
+        &self,
+        ctx: &Context<'_>,
+        supplier_payout_id: i32,
+    ) -> Result<SupplierPayouts, async_graphql::Error> {
+        use crate::entity::{prelude::SupplierPayouts as SupplierPayoutsEntity, supplier_payouts};
+        let db = ctx.data::<DatabaseConnection>()?;
+
+        let supplier_payout = SupplierPayoutsEntity::find_by_id(supplier_payout_id)
+            .one(db)
+            .await?
+            .ok_or("Payout not found")?;
+
+        if supplier_payout.status != PAYOUT_STATUS_PENDING {
+            return Err("Payout already paid".into());
+        }
+
+        let mut supplier_payout: supplier_payouts::ActiveModel = supplier_payout.into();
+        supplier_payout.status = Set(PAYOUT_STATUS_PAID.to_string());
+        supplier_payout.paid_at = Set(Some(Utc::now().fixed_offset()));
+
+        Ok(supplier_payout.update(db).await?.into())
+    }
+}
@@ -0,0 +1,133 @@
+use crate::{
+    auth::{RoleGuard, ROLE_ADMIN},
+    graphql::macros::role_guard,
+    models::{
+        products::Categories,
+        taxes::{create_tax_rule_model, RegisterTaxRule, TaxRules},
+    },
+};
+use async_graphql::{Context, Object};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, DatabaseConnection, EntityTrait, ModelTrait,
+    QueryFilter, QueryOrder,
+};
+
+#[derive(Default)]
+pub struct TaxesQuery;
+
+#[derive(Default)]
+pub struct TaxesMutation;
+
+#[Object]
+impl TaxesQuery {
+    async fn tax_rules(
+        &self,
+        ctx: &Context<'_>,
+        country: Option<String>,
+    ) -> Result<Vec<TaxRules>, async_graphql::Error> {
+        use crate::entity::{prelude::TaxRules as TaxRulesEntity, tax_rules};
+        let db = ctx.data::<DatabaseConnection>()?;
+
+        let mut query = TaxRulesEntity::find();
+        if let Some(country) = country {
+            query = query.filter(tax_rules::Column::Country.eq(country.to_uppercase()));
+        }
+
+        let tax_rules = query
+            .order_by_asc(tax_rules::Column::Country)
+            .order_by_asc(tax_rules::Column::TaxClass)
+            .all(db)
+            .await?;
+
+        Ok(tax_rules
+            .into_iter()
+            .map(|tax_rule| tax_rule.into())
+            .collect())
+    }
+}
+
+#[Object]
+impl TaxesMutation {
+    #[graphql(guard = "role_guard!(ROLE_ADMIN)")]
+    async fn register_tax_rule(
+        &self,
+        ctx: &Context<'_>,
+        input: RegisterTaxRule,
+    ) -> Result<TaxRules, async_graphql::Error> {
+        use crate::entity::prelude::TaxRules as TaxRulesEntity;
+        let db = ctx.data::<DatabaseConnection>()?;
+
+        let tax_rule = create_tax_rule_model(input)?;
+        let insert_tax_rule = TaxRulesEntity::insert(tax_rule)
+            .exec_with_returning(db)
+            .await?;
+
+        Ok(insert_tax_rule.into())
+    }
+
+    #[graphql(guard = "role_guard!(ROLE_ADMIN)")]
+    async fn update_tax_rule(
+        &self,
+        ctx: &Context<'_>,
+        tax_rule_id: i32,
+        input: RegisterTaxRule,
+    ) -> Result<TaxRules, async_graphql::Error> {
+        use crate::entity::{prelude::TaxRules as TaxRulesEntity, tax_rules};
+        let db = ctx.data::<DatabaseConnection>()?;
+
+        TaxRulesEntity::find_by_id(tax_rule_id)
+            .one(db)
+            .await?
+            .ok_or("Tax rule not found")?;
+
+        let mut tax_rule = create_tax_rule_model(input)?;
+        tax_rule.tax_rule_id = Set(tax_rule_id);
+
+        let update_tax_rule = TaxRulesEntity::update(tax_rule)
+            .filter(tax_rules::Column::TaxRuleId.eq(tax_rule_id))
+            .exec(db)
+            .await?;
+
+        Ok(update_tax_rule.into())
+    }
+
+    #[graphql(guard = "role_guard!(ROLE_ADMIN)")]
+    async fn delete_tax_rule(
+        &self,
+        ctx: &Context<'_>,
+        tax_rule_id: i32,
+    ) -> Result<String, async_graphql::Error> {
+        use crate::entity::prelude::TaxRules as TaxRulesEntity;
+        let db = ctx.data::<DatabaseConnection>()?;
+
+        TaxRulesEntity::find_by_id(tax_rule_id)
+            .one(db)
+            .await?
+            .ok_or("Tax rule not found")?
+            .delete(db)
+            .await?;
+
+        Ok("Tax rule deleted".to_string())
+    }
+
+    #[graphql(guard = "role_guard!(ROLE_ADMIN)")]
+    async fn set_category_tax_class(
+        &self,
+        ctx: &Context<'_>,
+        category_id: i32,
+        tax_class: String,
+    ) -> Result<Categories, async_graphql::Error> {
+        use crate::entity::{categories, prelude::Categories as CategoriesEntity};
+        let db = ctx.data::<DatabaseConnection>()?;
+
+        let category = CategoriesEntity::find_by_id(category_id)
+            .one(db)
+            .await?
+            .ok_or("Category not found")?;
+
+        let mut category: categories::ActiveModel = category.into();
+        category.tax_class = Set(tax_class);
+
+        Ok(category.update(db).await?.into())
+    }
+}
@@ -3,14 +3,16 @@ use crate::{
     auth::{Auth, RoleGuard, ROLE_CUSTOMER, ROLE_SUPPLIER},
     graphql::macros::role_guard,
     models::user::{
-        Customers, LoginUser, RegisterCustomer, RegisterSupplier, RegisterUser, Suppliers, Users,
+        get_customer_supplier_id, Customers, LoginUser, RegisterCustomer, RegisterSupplier,
+        RegisterUser, Suppliers, Users,
     },
+    money::{Money, DEFAULT_CURRENCY},
 };
 use async_graphql::{Context, Object};
 use chrono::Duration;
 use sea_orm::{
-    ActiveEnum, ActiveModelTrait, ActiveValue::Set, ColumnTrait, DatabaseConnection, EntityTrait,
-    QueryFilter,
+    prelude::Decimal, ActiveEnum, ActiveModelTrait, ActiveValue::Set, ColumnTrait,
+    DatabaseConnection, EntityTrait, QueryFilter,
 };
 
 #[derive(Default)]
@@ -187,6 +189,44 @@ impl UsersMutation {
         Ok(insert_supplier.into())
     }
 
+    // the limit is the most a single order may have to collect in cash for this supplier,
+    // leaving it out means no limit
+    #[graphql(guard = "role_guard!(ROLE_SUPPLIER)")]
+    async fn update_cod_settings(
+        &self,
+        ctx: &Context<'_>,
+        cod_enabled: bool,
+        cod_limit: Option<Money>,
+    ) -> Result<Suppliers, async_graphql::Error> {
+        use crate::entity::{prelude::Suppliers as SuppliersEntity, suppliers};
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+
+        let supplier_id = get_customer_supplier_id(db, token, ROLE_SUPPLIER).await?;
+
+        if let Some(cod_limit) = &cod_limit {
+            if cod_limit.currency != DEFAULT_CURRENCY {
+                return Err(format!("COD limits are set in {}", DEFAULT_CURRENCY).into());
+            }
+            if cod_limit.amount <= Decimal::ZERO {
+                return Err("COD limit must be greater than zero".into());
+            }
+        }
+
+        let supplier = SuppliersEntity::find_by_id(supplier_id)
+            .one(db)
+            .await?
+            .ok_or("Supplier not found")?;
+
+        let mut supplier: suppliers::ActiveModel = supplier.into();
+        supplier.cod_enabled = Set(cod_enabled);
+        supplier.cod_limit = Set(cod_limit.map(|cod_limit| cod_limit.amount));
+
+        Ok(supplier.update(db).await?.into())
+    }
+
     async fn login(
         &self,
         ctx: &Context<'_>,
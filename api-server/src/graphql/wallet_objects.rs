@@ -0,0 +1,93 @@
+use crate::{
+    auth::{RoleGuard, ROLE_ADMIN, ROLE_CUSTOMER},
+    graphql::macros::role_guard,
+    models::{
+        user::get_customer_supplier_id,
+        wallet::{change_wallet_balance, WalletTransactions, WALLET_TRANSACTION_CREDIT},
+    },
+    money::{Money, DEFAULT_CURRENCY},
+};
+use async_graphql::{Context, Object};
+use sea_orm::{
+    prelude::Decimal, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+    TransactionTrait,
+};
+
+#[derive(Default)]
+pub struct WalletQuery;
+
+#[derive(Default)]
+pub struct WalletMutation;
+
+#[Object]
+impl WalletQuery {
+    #[graphql(guard = "role_guard!(ROLE_CUSTOMER)")]
+    async fn wallet_transactions(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<Vec<WalletTransactions>, async_graphql::Error> {
+        use crate::entity::{
+            prelude::WalletTransactions as WalletTransactionsEntity, wallet_transactions,
+        };
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+
+        let customer_id = get_customer_supplier_id(db, token, ROLE_CUSTOMER).await?;
+
+        let wallet_transactions = WalletTransactionsEntity::find()
+            .filter(wallet_transactions::Column::CustomerId.eq(customer_id))
+            .order_by_desc(wallet_transactions::Column::CreatedAt)
+            .all(db)
+            .await?;
+
+        Ok(wallet_transactions
+            .into_iter()
+            .map(|wallet_transaction| wallet_transaction.into())
+            .collect())
+    }
+}
+
+#[Object]
+impl WalletMutation {
+    #[graphql(guard = "role_guard!(ROLE_ADMIN)")]
+    async fn credit_wallet(
+        &self,
+        ctx: &Context<'_>,
+        customer_id: i32,
+        amount: Money,
+        note: Option<String>,
+    ) -> Result<WalletTransactions, async_graphql::Error> {
+        use crate::entity::prelude::Customers as CustomersEntity;
+        let db = ctx.data::<DatabaseConnection>()?;
+        let txn = db.begin().await?;
+
+        if amount.currency != DEFAULT_CURRENCY {
+            return Err(format!("Wallets are kept in {}", DEFAULT_CURRENCY).into());
+        }
+
+        if amount.amount <= Decimal::ZERO {
+            return Err("Amount must be greater than zero".into());
+        }
+
+        CustomersEntity::find_by_id(customer_id)
+            .one(&txn)
+            .await?
+            .ok_or("Customer not found")?;
+
+        let wallet_transaction = change_wallet_balance(
+            &txn,
+            customer_id,
+            amount.amount,
+            WALLET_TRANSACTION_CREDIT,
+            None,
+            note,
+        )
+        .await?;
+
+        txn.commit().await?;
+
+        Ok(wallet_transaction.into())
+    }
+}
@@ -0,0 +1,195 @@
+use crate::{
+    auth::{Auth, RoleGuard, ROLE_SUPPLIER},
+    graphql::macros::role_guard,
+    models::{
+        products::check_if_supplier_owns_product,
+        stock_movements::transfer_stock,
+        user::get_customer_supplier_id,
+        warehouses::{
+            create_warehouse_model, find_supplier_warehouse, move_stock_into_first_warehouse,
+            RegisterWarehouse, WarehouseStock, Warehouses,
+        },
+    },
+};
+use async_graphql::{Context, Object};
+use sea_orm::{
+    ActiveValue::Set, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+    TransactionTrait,
+};
+
+#[derive(Default)]
+pub struct WarehousesQuery;
+
+#[derive(Default)]
+pub struct WarehousesMutation;
+
+#[Object]
+impl WarehousesQuery {
+    #[graphql(guard = "role_guard!(ROLE_SUPPLIER)")]
+    async fn warehouses(&self, ctx: &Context<'_>) -> Result<Vec<Warehouses>, async_graphql::Error> {
+        use crate::entity::{prelude::Warehouses as WarehousesEntity, warehouses};
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+
+        let supplier_id = get_customer_supplier_id(db, token, ROLE_SUPPLIER).await?;
+
+        let warehouses = WarehousesEntity::find()
+            .filter(warehouses::Column::SupplierId.eq(supplier_id))
+            .order_by_asc(warehouses::Column::WarehouseId)
+            .all(db)
+            .await?;
+
+        Ok(warehouses
+            .into_iter()
+            .map(|warehouse| warehouse.into())
+            .collect())
+    }
+
+    // the product's `stockQuantity` is the sum of these
+    #[graphql(guard = "role_guard!(ROLE_SUPPLIER)")]
+    async fn warehouse_stock(
+        &self,
+        ctx: &Context<'_>,
+        product_id: i32,
+    ) -> Result<Vec<WarehouseStock>, async_graphql::Error> {
+        use crate::entity::{prelude::WarehouseStock as WarehouseStockEntity, warehouse_stock};
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+
+        let supplier_id = get_customer_supplier_id(db, token, ROLE_SUPPLIER).await?;
+        check_if_supplier_owns_product(db, supplier_id, product_id).await?;
+
+        let warehouse_stock = WarehouseStockEntity::find()
+            .filter(warehouse_stock::Column::ProductId.eq(product_id))
+            .order_by_asc(warehouse_stock::Column::WarehouseId)
+            .all(db)
+            .await?;
+
+        Ok(warehouse_stock
+            .into_iter()
+            .map(|warehouse_stock| warehouse_stock.into())
+            .collect())
+    }
+}
+
+#[Object]
+impl WarehousesMutation {
+    #[graphql(guard = "role_guard!(ROLE_SUPPLIER)")]
+    async fn register_warehouse(
+        &self,
+        ctx: &Context<'_>,
+        input: RegisterWarehouse,
+    ) -> Result<Warehouses, async_graphql::Error> {
+        use crate::entity::{prelude::Warehouses as WarehousesEntity, warehouses};
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+
+        let supplier_id = get_customer_supplier_id(db, token, ROLE_SUPPLIER).await?;
+        let warehouse = create_warehouse_model(input, supplier_id)?;
+
+        let txn = db.begin().await?;
+        let is_first = WarehousesEntity::find()
+            .filter(warehouses::Column::SupplierId.eq(supplier_id))
+            .one(&txn)
+            .await?
+            .is_none();
+
+        let insert_warehouse = WarehousesEntity::insert(warehouse)
+            .exec_with_returning(&txn)
+            .await?;
+        if is_first {
+            move_stock_into_first_warehouse(&txn, &insert_warehouse).await?;
+        }
+        txn.commit().await?;
+
+        Ok(insert_warehouse.into())
+    }
+
+    #[graphql(guard = "role_guard!(ROLE_SUPPLIER)")]
+    async fn update_warehouse(
+        &self,
+        ctx: &Context<'_>,
+        warehouse_id: i32,
+        input: RegisterWarehouse,
+    ) -> Result<Warehouses, async_graphql::Error> {
+        use crate::entity::{prelude::Warehouses as WarehousesEntity, warehouses};
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+
+        let supplier_id = get_customer_supplier_id(db, token, ROLE_SUPPLIER).await?;
+        find_supplier_warehouse(db, supplier_id, warehouse_id).await?;
+
+        let mut warehouse = create_warehouse_model(input, supplier_id)?;
+        warehouse.warehouse_id = Set(warehouse_id);
+
+        let update_warehouse = WarehousesEntity::update(warehouse)
+            .filter(warehouses::Column::WarehouseId.eq(warehouse_id))
+            .exec(db)
+            .await?;
+
+        Ok(update_warehouse.into())
+    }
+
+    #[graphql(guard = "role_guard!(ROLE_SUPPLIER)")]
+    async fn transfer_stock(
+        &self,
+        ctx: &Context<'_>,
+        product_id: i32,
+        from_warehouse_id: i32,
+        to_warehouse_id: i32,
+        quantity: i32,
+        note: Option<String>,
+    ) -> Result<Vec<WarehouseStock>, async_graphql::Error> {
+        use crate::entity::{
+            prelude::{Products as ProductsEntity, WarehouseStock as WarehouseStockEntity},
+            warehouse_stock,
+        };
+        let db = ctx.data::<DatabaseConnection>()?;
+        let token = ctx
+            .data_opt::<String>()
+            .ok_or("No authorization token found")?;
+
+        let supplier_id = get_customer_supplier_id(db, token, ROLE_SUPPLIER).await?;
+        let user_id = Auth::verify_token(token)?.user_id.parse::<i32>()?;
+        check_if_supplier_owns_product(db, supplier_id, product_id).await?;
+        find_supplier_warehouse(db, supplier_id, from_warehouse_id).await?;
+        find_supplier_warehouse(db, supplier_id, to_warehouse_id).await?;
+
+        let txn = db.begin().await?;
+        let product = ProductsEntity::find_by_id(product_id)
+            .one(&txn)
+            .await?
+            .ok_or("Product not found")?;
+
+        transfer_stock(
+            &txn,
+            &product,
+            from_warehouse_id,
+            to_warehouse_id,
+            quantity,
+            user_id,
+            note,
+        )
+        .await?;
+
+        let warehouse_stock = WarehouseStockEntity::find()
+            .filter(warehouse_stock::Column::ProductId.eq(product_id))
+            .order_by_asc(warehouse_stock::Column::WarehouseId)
+            .all(&txn)
+            .await?;
+        txn.commit().await?;
+
+        Ok(warehouse_stock
+            .into_iter()
+            .map(|warehouse_stock| warehouse_stock.into())
+            .collect())
+    }
+}
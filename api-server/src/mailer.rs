@@ -0,0 +1,35 @@
+use mail_send::mail_builder::MessageBuilder;
+use mail_send::SmtpClientBuilder;
+use std::env;
+
+const MAIL_FROM_NAME: &str = "Nine11";
+const MAIL_FROM_ADDRESS: &str = "postmaster@testing.giripriyadarshan.com";
+
+pub async fn send_mail(to: String, subject: String, html_body: String) -> Result<(), String> {
+    let message = MessageBuilder::new()
+        .from((MAIL_FROM_NAME, MAIL_FROM_ADDRESS))
+        .to(to)
+        .subject(subject)
+        .html_body(html_body);
+
+    let smtp_username = env::var("SMTP_USERNAME").map_err(|_| "SMTP_USERNAME must be set")?;
+    let smtp_password = env::var("SMTP_PASSWORD").map_err(|_| "SMTP_PASSWORD must be set")?;
+    SmtpClientBuilder::new("smtp.mailgun.org", 587)
+        .implicit_tls(false)
+        .credentials((smtp_username.as_str(), smtp_password.as_str()))
+        .connect()
+        .await
+        .map_err(|e| e.to_string())?
+        .send(message)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// mails are sent after the request is answered, a failing mail server doesn't fail the request
+pub fn send_mail_in_background(to: String, subject: String, html_body: String) {
+    tokio::spawn(async move {
+        if let Err(e) = send_mail(to.clone(), subject, html_body).await {
+            eprintln!("Failed to send mail to {}: {}", to, e);
+        }
+    });
+}
@@ -1,11 +1,26 @@
 mod auth;
+mod carrier_webhook;
 mod entity;
 mod error;
 mod graphql;
+mod mailer;
+mod media_storage;
+mod media_upload;
 mod models;
+mod money;
+mod payment_gateway;
+mod payment_webhook;
+mod tokenize_cards;
 mod verify_mail;
 
+use crate::carrier_webhook::carrier_webhook;
 use crate::error::handle_error;
+use crate::media_storage::media_storage_from_env;
+use crate::media_upload::{media_max_bytes, serve_media, upload_media};
+use crate::models::reservations::release_expired_reservations;
+use crate::payment_gateway::payment_provider_from_env;
+use crate::payment_webhook::payment_webhook;
+use crate::tokenize_cards::tokenize_stored_cards;
 use crate::verify_mail::verify_mail;
 use crate::{
     error::AppError,
@@ -13,19 +28,20 @@ use crate::{
 };
 use axum::{
     error_handling::HandleErrorLayer,
+    extract::DefaultBodyLimit,
     http::{
         header::{
             ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS,
             ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN, AUTHORIZATION, CONTENT_TYPE,
         },
-        Method,
+        HeaderName, Method,
     },
-    routing::get,
+    routing::{get, post},
     BoxError, Extension, Router,
 };
 use dotenv::dotenv;
 use sea_orm::Database;
-use std::env;
+use std::{env, time::Duration};
 use tokio::net::TcpListener;
 use tower::{layer::util::Identity, ServiceBuilder};
 use tower_http::cors::{Any, CorsLayer};
@@ -45,7 +61,29 @@ async fn main() -> Result<(), AppError> {
             context: None,
         })?;
 
-    let schema = graphql::schema::create_schema(db.clone());
+    let payment_provider = payment_provider_from_env()?;
+    let media_storage = media_storage_from_env()?;
+
+    // `cargo run -- --tokenize-cards` migrates card numbers stored by older versions and exits
+    if env::args().any(|arg| arg == "--tokenize-cards") {
+        let tokenized = tokenize_stored_cards(&db, &payment_provider).await?;
+        println!("Tokenized {} stored cards", tokenized);
+        return Ok(());
+    }
+
+    // stock held by abandoned checkouts goes back on sale once the reservation expires
+    let reservations_db = db.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            if let Err(e) = release_expired_reservations(&reservations_db).await {
+                eprintln!("Failed to release expired stock reservations: {}", e);
+            }
+        }
+    });
+
+    let schema = graphql::schema::create_schema(db.clone(), payment_provider);
     let cors = CorsLayer::new()
         .allow_origin(Any)
         .allow_methods([Method::GET, Method::POST])
@@ -56,6 +94,7 @@ async fn main() -> Result<(), AppError> {
             ACCESS_CONTROL_ALLOW_CREDENTIALS,
             ACCESS_CONTROL_ALLOW_ORIGIN,
             ACCESS_CONTROL_ALLOW_METHODS,
+            HeaderName::from_static("idempotency-key"),
         ]);
 
     let middleware_stack = ServiceBuilder::new()
@@ -74,6 +113,36 @@ async fn main() -> Result<(), AppError> {
         .route(
             "/verify/:token",
             get(verify_mail)
+                .layer::<_, BoxError>(Extension(db.clone()))
+                .layer(Identity::new())
+                .layer(middleware_stack.clone()),
+        )
+        .route(
+            "/webhooks/carrier",
+            post(carrier_webhook)
+                .layer::<_, BoxError>(Extension(db.clone()))
+                .layer(Identity::new())
+                .layer(middleware_stack.clone()),
+        )
+        // the limit leaves room for the multipart framing around the file itself
+        .route(
+            "/media",
+            post(upload_media)
+                .layer::<_, BoxError>(DefaultBodyLimit::max(media_max_bytes() + 64 * 1024))
+                .layer::<_, BoxError>(Extension(media_storage.clone()))
+                .layer(Identity::new())
+                .layer(middleware_stack.clone()),
+        )
+        .route(
+            "/media/:file_name",
+            get(serve_media)
+                .layer::<_, BoxError>(Extension(media_storage))
+                .layer(Identity::new())
+                .layer(middleware_stack.clone()),
+        )
+        .route(
+            "/webhooks/payment",
+            post(payment_webhook)
                 .layer::<_, BoxError>(Extension(db))
                 .layer(Identity::new())
                 .layer(middleware_stack),
@@ -0,0 +1,55 @@
+use crate::error::AppError;
+use async_trait::async_trait;
+use std::{env, io, path::PathBuf, sync::Arc};
+
+// where uploaded product and review media is kept, files are addressed by name only
+#[async_trait]
+pub trait MediaStorage: Send + Sync {
+    async fn put(&self, file_name: &str, bytes: &[u8]) -> io::Result<()>;
+
+    // None when there is no file with that name
+    async fn get(&self, file_name: &str) -> io::Result<Option<Vec<u8>>>;
+}
+
+pub type SharedMediaStorage = Arc<dyn MediaStorage>;
+
+// picks the storage named by MEDIA_STORAGE, the local directory one when it isn't set
+pub fn media_storage_from_env() -> Result<SharedMediaStorage, AppError> {
+    match env::var("MEDIA_STORAGE").as_deref() {
+        Ok("local") | Err(_) => {
+            let dir = env::var("MEDIA_DIR").unwrap_or_else(|_| "media".to_string());
+            Ok(Arc::new(LocalMediaStorage::new(dir)))
+        }
+        Ok(storage) => Err(AppError::Internal(format!(
+            "Unknown media storage: {}",
+            storage
+        ))),
+    }
+}
+
+// keeps the files in MEDIA_DIR on the server's disk, the directory is created on the first upload
+pub struct LocalMediaStorage {
+    dir: PathBuf,
+}
+
+impl LocalMediaStorage {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+#[async_trait]
+impl MediaStorage for LocalMediaStorage {
+    async fn put(&self, file_name: &str, bytes: &[u8]) -> io::Result<()> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        tokio::fs::write(self.dir.join(file_name), bytes).await
+    }
+
+    async fn get(&self, file_name: &str) -> io::Result<Option<Vec<u8>>> {
+        match tokio::fs::read(self.dir.join(file_name)).await {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use crate::auth::Auth;
+use crate::media_storage::SharedMediaStorage;
+use axum::extract::{Multipart, Path};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::{Extension, Json};
+use image::{ImageError, ImageFormat, ImageReader, Limits};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::{env, io::Cursor};
+
+// uploads above this many bytes are refused, MEDIA_MAX_BYTES changes it
+pub const DEFAULT_MEDIA_MAX_BYTES: usize = 5 * 1024 * 1024;
+
+// thumbnails fit in a square of this many pixels and keep the image's proportions
+const THUMBNAIL_SIZE: u32 = 320;
+
+// a small file can still decode to a huge image, so larger images are refused before they are decoded
+const MAX_IMAGE_DIMENSION: u32 = 8192;
+const MAX_IMAGE_ALLOC: u64 = 256 * 1024 * 1024;
+
+// file names come from the content, so a name always serves the same bytes and can be cached for good
+const MEDIA_CACHE_CONTROL: &str = "public, max-age=31536000, immutable";
+
+pub fn media_max_bytes() -> usize {
+    env::var("MEDIA_MAX_BYTES")
+        .ok()
+        .and_then(|max_bytes| max_bytes.parse().ok())
+        .unwrap_or(DEFAULT_MEDIA_MAX_BYTES)
+}
+
+// the paths go into `mediaPaths` of a product or a review
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadedMedia {
+    pub path: String,
+    pub thumbnail_path: String,
+}
+
+fn media_extension(format: ImageFormat) -> Option<&'static str> {
+    match format {
+        ImageFormat::Png => Some("png"),
+        ImageFormat::Jpeg => Some("jpg"),
+        ImageFormat::WebP => Some("webp"),
+        _ => None,
+    }
+}
+
+fn media_content_type(extension: &str) -> Option<&'static str> {
+    match extension {
+        "png" => Some("image/png"),
+        "jpg" => Some("image/jpeg"),
+        "webp" => Some("image/webp"),
+        _ => None,
+    }
+}
+
+// checks the file really is one of the accepted images, whatever the client called it, and
+// gives back its format with the thumbnail encoded in the same format
+fn make_thumbnail(bytes: &[u8]) -> Result<(ImageFormat, Vec<u8>), String> {
+    let format = image::guess_format(bytes)
+        .ok()
+        .filter(|format| media_extension(*format).is_some())
+        .ok_or("Only PNG, JPEG and WebP images can be uploaded")?;
+
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_IMAGE_DIMENSION);
+    limits.max_image_height = Some(MAX_IMAGE_DIMENSION);
+    limits.max_alloc = Some(MAX_IMAGE_ALLOC);
+    let mut reader = ImageReader::with_format(Cursor::new(bytes), format);
+    reader.limits(limits);
+    let image = reader.decode().map_err(|e| match e {
+        ImageError::Limits(_) => format!(
+            "Images can be at most {} pixels wide and high",
+            MAX_IMAGE_DIMENSION
+        ),
+        _ => "The file is not a valid image".to_string(),
+    })?;
+
+    let mut thumbnail = Vec::new();
+    image
+        .thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+        .write_to(&mut Cursor::new(&mut thumbnail), format)
+        .map_err(|e| format!("Failed to create thumbnail: {}", e))?;
+
+    Ok((format, thumbnail))
+}
+
+// takes the first file of a multipart form, any signed in user can upload
+pub async fn upload_media(
+    Extension(storage): Extension<SharedMediaStorage>,
+    headers: HeaderMap,
+    mut multipart: Multipart,
+) -> Response {
+    let token = headers
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|auth| auth.strip_prefix("Bearer "));
+    if token.is_none_or(|token| Auth::verify_token(token).is_err()) {
+        return (StatusCode::UNAUTHORIZED, "Invalid token".to_string()).into_response();
+    }
+
+    let field = match multipart.next_field().await {
+        Ok(Some(field)) => field,
+        Ok(None) => {
+            return (StatusCode::BAD_REQUEST, "No file uploaded".to_string()).into_response()
+        }
+        Err(e) => return (e.status(), e.body_text()).into_response(),
+    };
+    let bytes = match field.bytes().await {
+        Ok(bytes) => bytes,
+        Err(e) => return (e.status(), e.body_text()).into_response(),
+    };
+
+    if bytes.len() > media_max_bytes() {
+        return (
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!("Files can be at most {} bytes", media_max_bytes()),
+        )
+            .into_response();
+    }
+
+    let hash = hex::encode(Sha256::digest(&bytes));
+    let image_bytes = bytes.clone();
+    let (format, thumbnail) =
+        match tokio::task::spawn_blocking(move || make_thumbnail(&image_bytes)).await {
+            Ok(Ok(processed)) => processed,
+            Ok(Err(e)) => return (StatusCode::UNPROCESSABLE_ENTITY, e).into_response(),
+            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        };
+    let extension = media_extension(format).unwrap_or_default();
+
+    let file_name = format!("{}.{}", hash, extension);
+    let thumbnail_name = format!("{}_thumb.{}", hash, extension);
+    for (name, content) in [(&file_name, &bytes[..]), (&thumbnail_name, &thumbnail[..])] {
+        if let Err(e) = storage.put(name, content).await {
+            return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+        }
+    }
+
+    Json(UploadedMedia {
+        path: format!("/media/{}", file_name),
+        thumbnail_path: format!("/media/{}", thumbnail_name),
+    })
+    .into_response()
+}
+
+pub async fn serve_media(
+    Extension(storage): Extension<SharedMediaStorage>,
+    Path(file_name): Path<String>,
+) -> Response {
+    // only names the upload could have made, which also keeps requests inside the storage
+    let content_type = file_name
+        .split_once('.')
+        .filter(|(name, _)| name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'))
+        .and_then(|(_, extension)| media_content_type(extension));
+    let Some(content_type) = content_type else {
+        return (StatusCode::NOT_FOUND, "Media not found".to_string()).into_response();
+    };
+
+    match storage.get(&file_name).await {
+        Ok(Some(bytes)) => (
+            [
+                (header::CONTENT_TYPE, content_type),
+                (header::CACHE_CONTROL, MEDIA_CACHE_CONTROL),
+            ],
+            bytes,
+        )
+            .into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, "Media not found".to_string()).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
@@ -1,14 +1,25 @@
-use crate::entity::bills::Model as BillsModel;
+use crate::{
+    entity::{bill_adjustments::Model as BillAdjustmentsModel, bills::Model as BillsModel},
+    money::Money,
+};
 use async_graphql::{InputObject, SimpleObject};
 use sea_orm::prelude::DateTimeWithTimeZone;
 
+pub const BILL_STATUS_PENDING: &str = "PENDING";
+pub const BILL_STATUS_PAID: &str = "PAID";
+pub const BILL_STATUS_FAILED: &str = "FAILED";
+
+pub const BILL_ADJUSTMENT_PARTIAL_CANCELLATION: &str = "PARTIAL_CANCELLATION";
+
 #[derive(SimpleObject)]
 pub struct Bills {
     bill_date: Option<DateTimeWithTimeZone>,
     bill_id: i32,
     order_id: i32,
     payment_status: String,
-    total_amount: f64,
+    total_amount: Money,
+    tax_amount: Money,
+    shipping_amount: Money,
 }
 
 impl From<BillsModel> for Bills {
@@ -17,7 +28,9 @@ impl From<BillsModel> for Bills {
             bill_date: val.bill_date,
             bill_id: val.bill_id,
             payment_status: val.payment_status,
-            total_amount: f64::try_from(val.total_amount).unwrap(),
+            total_amount: Money::new(val.total_amount, &val.currency),
+            tax_amount: Money::new(val.tax_amount, &val.currency),
+            shipping_amount: Money::new(val.shipping_amount, &val.currency),
             order_id: val.order_id,
         }
     }
@@ -28,5 +41,26 @@ pub struct RegisterBill {
     bill_date: Option<DateTimeWithTimeZone>,
     order_id: i64,
     payment_status: String,
-    total_amount: f64,
+    total_amount: Money,
+}
+
+#[derive(SimpleObject)]
+pub struct BillAdjustments {
+    pub bill_adjustment_id: i32,
+    pub bill_id: i32,
+    pub amount: Money,
+    pub reason: String,
+    pub created_at: Option<DateTimeWithTimeZone>,
+}
+
+impl From<BillAdjustmentsModel> for BillAdjustments {
+    fn from(val: BillAdjustmentsModel) -> BillAdjustments {
+        BillAdjustments {
+            bill_adjustment_id: val.bill_adjustment_id,
+            bill_id: val.bill_id,
+            amount: Money::new(val.amount, &val.currency),
+            reason: val.reason,
+            created_at: val.created_at,
+        }
+    }
 }
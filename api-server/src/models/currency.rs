@@ -0,0 +1,122 @@
+use crate::{
+    entity::exchange_rates::{self, Model as ExchangeRatesModel},
+    money::{currency_code, round, DEFAULT_CURRENCY},
+};
+use async_graphql::SimpleObject;
+use chrono::Utc;
+use sea_orm::{
+    prelude::{DateTimeWithTimeZone, Decimal},
+    sea_query::OnConflict,
+    ActiveValue::Set,
+    ConnectionTrait, EntityTrait,
+};
+use std::str::FromStr;
+
+#[derive(SimpleObject)]
+pub struct ExchangeRates {
+    pub currency: String,
+    pub rate: Decimal,
+    pub updated_at: Option<DateTimeWithTimeZone>,
+}
+
+impl From<ExchangeRatesModel> for ExchangeRates {
+    fn from(val: ExchangeRatesModel) -> ExchangeRates {
+        ExchangeRates {
+            currency: val.currency,
+            rate: val.rate,
+            updated_at: val.updated_at,
+        }
+    }
+}
+
+// a rate is how many units of the currency one unit of the default currency buys
+pub async fn exchange_rate<C: ConnectionTrait>(
+    conn: &C,
+    currency: &str,
+) -> Result<Decimal, async_graphql::Error> {
+    if currency == DEFAULT_CURRENCY {
+        return Ok(Decimal::ONE);
+    }
+
+    exchange_rates::Entity::find_by_id(currency)
+        .one(conn)
+        .await?
+        .map(|exchange_rate| exchange_rate.rate)
+        .ok_or_else(|| format!("Unsupported currency {}", currency).into())
+}
+
+pub async fn convert<C: ConnectionTrait>(
+    conn: &C,
+    amount: Decimal,
+    from: &str,
+    to: &str,
+) -> Result<Decimal, async_graphql::Error> {
+    if from == to {
+        return Ok(amount);
+    }
+
+    let from_rate = exchange_rate(conn, from).await?;
+    let to_rate = exchange_rate(conn, to).await?;
+
+    Ok(round(amount / from_rate * to_rate))
+}
+
+pub async fn save_exchange_rate<C: ConnectionTrait>(
+    conn: &C,
+    currency: &str,
+    rate: Decimal,
+) -> Result<ExchangeRatesModel, async_graphql::Error> {
+    let currency = currency_code(currency).ok_or("Invalid currency code")?;
+
+    if currency == DEFAULT_CURRENCY {
+        return Err(format!(
+            "{} is the base currency, its rate is always 1",
+            DEFAULT_CURRENCY
+        )
+        .into());
+    }
+
+    if rate <= Decimal::ZERO {
+        return Err("Exchange rate must be positive".into());
+    }
+
+    let exchange_rate = exchange_rates::ActiveModel {
+        currency: Set(currency),
+        rate: Set(rate),
+        updated_at: Set(Some(Utc::now().fixed_offset())),
+    };
+
+    Ok(exchange_rates::Entity::insert(exchange_rate)
+        .on_conflict(
+            OnConflict::column(exchange_rates::Column::Currency)
+                .update_columns([
+                    exchange_rates::Column::Rate,
+                    exchange_rates::Column::UpdatedAt,
+                ])
+                .to_owned(),
+        )
+        .exec_with_returning(conn)
+        .await?)
+}
+
+// the import file has one `currency,rate` pair per line, an optional header line and blank lines are skipped
+pub fn parse_exchange_rates(content: &str) -> Result<Vec<(String, Decimal)>, async_graphql::Error> {
+    let mut rates = Vec::new();
+
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || (index == 0 && line.eq_ignore_ascii_case("currency,rate")) {
+            continue;
+        }
+
+        let (currency, rate) = line
+            .split_once(',')
+            .ok_or_else(|| format!("Line {}: expected currency,rate", index + 1))?;
+        let rate = Decimal::from_str(rate.trim())
+            .map_err(|_| format!("Line {}: invalid rate \"{}\"", index + 1, rate.trim()))?;
+
+        rates.push((currency.trim().to_string(), rate));
+    }
+
+    Ok(rates)
+}
@@ -0,0 +1,279 @@
+use crate::{
+    entity::{
+        gift_card_transactions::{self, Model as GiftCardTransactionsModel},
+        gift_cards::{self, Model as GiftCardsModel},
+        order_items, orders, products,
+    },
+    models::returns::refund_amount,
+    money::Money,
+};
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use async_graphql::SimpleObject;
+use sea_orm::{
+    prelude::{DateTimeWithTimeZone, Decimal},
+    sea_query::Expr,
+    ActiveValue::Set,
+    ColumnTrait, DatabaseTransaction, DbErr, EntityTrait, QueryFilter,
+};
+
+// no 0/O or 1/I so codes can be read out and typed in without mistakes
+const GIFT_CARD_CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const GIFT_CARD_CODE_LENGTH: usize = 16;
+
+#[derive(SimpleObject)]
+pub struct GiftCards {
+    pub gift_card_id: i32,
+    pub code: String,
+    pub initial_amount: Money,
+    pub balance: Money,
+    pub customer_id: Option<i32>,
+    pub order_item_id: Option<i32>,
+    pub refund_id: Option<i32>,
+    pub note: Option<String>,
+    pub created_at: Option<DateTimeWithTimeZone>,
+}
+
+impl From<GiftCardsModel> for GiftCards {
+    fn from(val: GiftCardsModel) -> GiftCards {
+        GiftCards {
+            gift_card_id: val.gift_card_id,
+            code: val.code,
+            initial_amount: Money::new(val.initial_amount, &val.currency),
+            balance: Money::new(val.balance, &val.currency),
+            customer_id: val.customer_id,
+            order_item_id: val.order_item_id,
+            refund_id: val.refund_id,
+            note: val.note,
+            created_at: val.created_at,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct GiftCardTransactions {
+    pub gift_card_transaction_id: i32,
+    pub gift_card_id: i32,
+    pub order_id: Option<i32>,
+    pub amount: Money,
+    pub balance_after: Money,
+    pub created_at: Option<DateTimeWithTimeZone>,
+}
+
+// transactions are in the currency of their gift card
+impl From<(GiftCardTransactionsModel, &str)> for GiftCardTransactions {
+    fn from((val, currency): (GiftCardTransactionsModel, &str)) -> GiftCardTransactions {
+        GiftCardTransactions {
+            gift_card_transaction_id: val.gift_card_transaction_id,
+            gift_card_id: val.gift_card_id,
+            order_id: val.order_id,
+            amount: Money::new(val.amount, currency),
+            balance_after: Money::new(val.balance_after, currency),
+            created_at: val.created_at,
+        }
+    }
+}
+
+// codes are stored upper cased without separators, customers may type them either way
+pub fn normalize_gift_card_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect::<String>()
+        .to_uppercase()
+}
+
+async fn generate_gift_card_code(txn: &DatabaseTransaction) -> Result<String, DbErr> {
+    loop {
+        let code: String = (0..GIFT_CARD_CODE_LENGTH)
+            .map(|_| {
+                let index = OsRng.next_u32() as usize % GIFT_CARD_CODE_ALPHABET.len();
+                GIFT_CARD_CODE_ALPHABET[index] as char
+            })
+            .collect();
+
+        if gift_cards::Entity::find()
+            .filter(gift_cards::Column::Code.eq(&code))
+            .one(txn)
+            .await?
+            .is_none()
+        {
+            return Ok(code);
+        }
+    }
+}
+
+pub async fn issue_gift_card(
+    txn: &DatabaseTransaction,
+    amount: &Money,
+    customer_id: Option<i32>,
+    order_item_id: Option<i32>,
+    refund_id: Option<i32>,
+    note: Option<String>,
+) -> Result<GiftCardsModel, DbErr> {
+    let gift_card = gift_cards::ActiveModel {
+        code: Set(generate_gift_card_code(txn).await?),
+        initial_amount: Set(amount.amount),
+        balance: Set(amount.amount),
+        currency: Set(amount.currency.clone()),
+        customer_id: Set(customer_id),
+        order_item_id: Set(order_item_id),
+        refund_id: Set(refund_id),
+        note: Set(note),
+        ..Default::default()
+    };
+    let gift_card = gift_cards::Entity::insert(gift_card)
+        .exec_with_returning(txn)
+        .await?;
+
+    let gift_card_transaction = gift_card_transactions::ActiveModel {
+        gift_card_id: Set(gift_card.gift_card_id),
+        amount: Set(gift_card.initial_amount),
+        balance_after: Set(gift_card.balance),
+        ..Default::default()
+    };
+    gift_card_transactions::Entity::insert(gift_card_transaction)
+        .exec(txn)
+        .await?;
+
+    Ok(gift_card)
+}
+
+// works like the wallet, a redemption only goes through while the balance covers it
+pub async fn change_gift_card_balance(
+    txn: &DatabaseTransaction,
+    gift_card_id: i32,
+    amount: Decimal,
+    order_id: Option<i32>,
+) -> Result<GiftCardTransactionsModel, async_graphql::Error> {
+    let mut update = gift_cards::Entity::update_many()
+        .col_expr(
+            gift_cards::Column::Balance,
+            Expr::col(gift_cards::Column::Balance).add(amount),
+        )
+        .filter(gift_cards::Column::GiftCardId.eq(gift_card_id));
+    if amount < Decimal::ZERO {
+        update = update.filter(gift_cards::Column::Balance.gte(-amount));
+    }
+
+    let gift_card = update
+        .exec_with_returning(txn)
+        .await?
+        .pop()
+        .ok_or("Insufficient gift card balance")?;
+
+    let gift_card_transaction = gift_card_transactions::ActiveModel {
+        gift_card_id: Set(gift_card_id),
+        order_id: Set(order_id),
+        amount: Set(amount),
+        balance_after: Set(gift_card.balance),
+        ..Default::default()
+    };
+
+    Ok(
+        gift_card_transactions::Entity::insert(gift_card_transaction)
+            .exec_with_returning(txn)
+            .await?,
+    )
+}
+
+// puts an amount taken for an order back on the card it was redeemed from
+pub async fn return_to_gift_card(
+    txn: &DatabaseTransaction,
+    order_id: i32,
+    amount: Decimal,
+) -> Result<(), async_graphql::Error> {
+    let redemption = gift_card_transactions::Entity::find()
+        .filter(gift_card_transactions::Column::OrderId.eq(order_id))
+        .filter(gift_card_transactions::Column::Amount.lt(Decimal::ZERO))
+        .one(txn)
+        .await?
+        .ok_or("No gift card was redeemed for this order")?;
+
+    change_gift_card_balance(txn, redemption.gift_card_id, amount, Some(order_id)).await?;
+
+    Ok(())
+}
+
+// once an order is paid every gift card unit in it becomes a card worth what was paid for the unit,
+// after the order discount, owned by the buyer who can pass the code on. Items that already have
+// their cards are skipped.
+pub async fn issue_purchased_gift_cards(
+    txn: &DatabaseTransaction,
+    order_id: i32,
+) -> Result<(), DbErr> {
+    let Some(order) = orders::Entity::find_by_id(order_id).one(txn).await? else {
+        return Ok(());
+    };
+
+    let order_items = order_items::Entity::find()
+        .find_also_related(products::Entity)
+        .filter(order_items::Column::OrderId.eq(order_id))
+        .filter(products::Column::IsGiftCard.eq(true))
+        .all(txn)
+        .await?;
+
+    for (order_item, _) in order_items {
+        if gift_cards::Entity::find()
+            .filter(gift_cards::Column::OrderItemId.eq(order_item.order_item_id))
+            .one(txn)
+            .await?
+            .is_some()
+        {
+            continue;
+        }
+
+        for _ in 0..order_item.quantity {
+            issue_gift_card(
+                txn,
+                &Money::new(refund_amount(&order_item, 1), &order.currency),
+                Some(order.customer_id),
+                Some(order_item.order_item_id),
+                None,
+                None,
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+// cancelling gift card units takes the balance off as many of the cards bought with them, a card
+// that has been spent from can't be cancelled any more. Must be called before the order item is
+// reduced or deleted.
+pub async fn revoke_purchased_gift_cards(
+    txn: &DatabaseTransaction,
+    order_item: &order_items::Model,
+    quantity: i32,
+) -> Result<(), async_graphql::Error> {
+    let gift_cards = gift_cards::Entity::find()
+        .filter(gift_cards::Column::OrderItemId.eq(order_item.order_item_id))
+        .all(txn)
+        .await?;
+    // cards are only issued once the order is paid
+    if gift_cards.is_empty() {
+        return Ok(());
+    }
+
+    let unused: Vec<_> = gift_cards
+        .into_iter()
+        .filter(|gift_card| {
+            gift_card.balance > Decimal::ZERO && gift_card.balance == gift_card.initial_amount
+        })
+        .take(quantity as usize)
+        .collect();
+    if unused.len() < quantity as usize {
+        return Err("Gift cards bought in this order have already been used".into());
+    }
+
+    for gift_card in unused {
+        change_gift_card_balance(
+            txn,
+            gift_card.gift_card_id,
+            -gift_card.balance,
+            Some(order_item.order_id),
+        )
+        .await?;
+    }
+
+    Ok(())
+}
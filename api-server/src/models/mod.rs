@@ -1,10 +1,26 @@
 pub mod addresses;
 pub mod bills;
 pub mod carts;
+pub mod currency;
+pub mod gift_cards;
 pub mod orders;
+pub mod payment_intents;
+pub mod payment_validation;
 pub mod payments;
+pub mod product_import;
 pub mod products;
+pub mod reservations;
+pub mod returns;
+pub mod shipments;
+pub mod shipping;
+pub mod stock_alerts;
+pub mod stock_movements;
+pub mod supplier_analytics;
+pub mod supplier_ledger;
+pub mod taxes;
 pub mod user;
+pub mod wallet;
+pub mod warehouses;
 
 pub mod order_und_pagination {
     use async_graphql::{Enum, InputObject, SimpleObject};
@@ -1,13 +1,38 @@
-use crate::entity::orders::Model as OrdersModel;
+use crate::{
+    entity::{
+        discounts::Model as DiscountsModel, order_items::Model as OrderItemsModel,
+        orders::Model as OrdersModel,
+    },
+    money::{round, Money},
+};
 use async_graphql::{InputObject, SimpleObject};
-use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::{
+    prelude::{DateTimeWithTimeZone, Decimal},
+    ColumnTrait, DatabaseTransaction, EntityTrait, QueryFilter,
+};
+
+pub const ORDER_STATUS_PENDING: &str = "PENDING";
+pub const ORDER_STATUS_PAID: &str = "PAID";
+pub const ORDER_STATUS_SHIPPED: &str = "SHIPPED";
+pub const ORDER_STATUS_DELIVERED: &str = "DELIVERED";
+pub const ORDER_STATUS_CANCELLED: &str = "CANCELLED";
+
+// retries with the same idempotency key within this window return the original order
+pub const IDEMPOTENCY_WINDOW_HOURS: i64 = 24;
+
+pub struct IdempotencyKey(pub String);
 
 #[derive(SimpleObject)]
 pub struct Orders {
     pub order_id: i32,
     pub customer_id: i32,
     pub order_date: Option<DateTimeWithTimeZone>,
-    pub total_amount: f64,
+    pub total_amount: Money,
+    pub tax_amount: Money,
+    pub shipping_amount: Money,
+    pub wallet_amount: Money,
+    pub gift_card_amount: Money,
+    pub exchange_rate: Decimal,
     pub status: String,
     pub shipping_address_id: i32,
     pub payment_method_id: i32,
@@ -20,7 +45,12 @@ impl From<OrdersModel> for Orders {
             order_id: val.order_id,
             customer_id: val.customer_id,
             order_date: val.order_date,
-            total_amount: val.total_amount.to_string().parse::<f64>().unwrap(),
+            total_amount: Money::new(val.total_amount, &val.currency),
+            tax_amount: Money::new(val.tax_amount, &val.currency),
+            shipping_amount: Money::new(val.shipping_amount, &val.currency),
+            wallet_amount: Money::new(val.wallet_amount, &val.currency),
+            gift_card_amount: Money::new(val.gift_card_amount, &val.currency),
+            exchange_rate: val.exchange_rate,
             status: val.status,
             shipping_address_id: val.shipping_address_id,
             payment_method_id: val.payment_method_id,
@@ -34,7 +64,17 @@ pub struct RegisterOrder {
     pub shipping_address_id: i32,
     pub payment_method_id: i32,
     pub discount_code: Option<String>,
+    // paid from the customer's wallet, the payment method covers the rest
+    pub wallet_amount: Option<Money>,
+    // redeemed before the payment method, for up to `gift_card_amount` or whatever the card covers
+    pub gift_card_code: Option<String>,
+    pub gift_card_amount: Option<Money>,
+    pub currency: Option<String>,
     pub order_items: Vec<RegisterOrderItem>,
+    // one of the delivery options of every supplier in the order
+    #[graphql(default)]
+    pub shipping_profile_ids: Vec<i32>,
+    pub idempotency_key: Option<String>,
 }
 
 #[derive(SimpleObject)]
@@ -43,8 +83,10 @@ pub struct OrderItems {
     pub order_id: i32,
     pub product_id: i32,
     pub quantity: i32,
-    pub unit_price: i32,
-    pub discount_amount: i32,
+    pub unit_price: Money,
+    pub discount_amount: Money,
+    pub tax_amount: Money,
+    pub warehouse_id: Option<i32>,
 }
 
 #[derive(InputObject)]
@@ -52,3 +94,91 @@ pub struct RegisterOrderItem {
     pub product_id: i32,
     pub quantity: i32,
 }
+
+#[derive(InputObject)]
+pub struct CancelOrderItem {
+    pub order_item_id: i32,
+    pub quantity: i32,
+}
+
+// a discount stops applying once the remaining items no longer meet its conditions
+pub fn discount_applies(discount: &DiscountsModel, items: &[OrderItemsModel]) -> bool {
+    let quantity: i32 = items
+        .iter()
+        .filter(|item| {
+            discount
+                .product_id
+                .is_none_or(|product_id| item.product_id == product_id)
+        })
+        .map(|item| item.quantity)
+        .sum();
+
+    quantity > 0
+        && discount
+            .min_quantity
+            .is_none_or(|min_quantity| quantity >= min_quantity)
+}
+
+fn line_amount(item: &OrderItemsModel) -> Decimal {
+    item.unit_price * Decimal::from(item.quantity)
+}
+
+// discounts come off the price before tax, fixed ones are in the default currency so they are
+// converted at the order's locked rate. The discount is shared out over the lines by their amount,
+// the last line taking what rounding leaves, so returns, cancellations and the suppliers' sales
+// each account for their own part of it.
+pub fn spread_discount(
+    items: &mut [OrderItemsModel],
+    discount: Option<&DiscountsModel>,
+    exchange_rate: Decimal,
+) {
+    let subtotal: Decimal = items.iter().map(line_amount).sum();
+    let discount_total = match discount {
+        Some(discount) if discount.discount_type == "PERCENTAGE" => {
+            subtotal * discount.discount_value / Decimal::ONE_HUNDRED
+        }
+        Some(discount) => discount.discount_value * exchange_rate,
+        None => Decimal::ZERO,
+    };
+    let discount_total = round(discount_total.min(subtotal).max(Decimal::ZERO));
+
+    let mut left = discount_total;
+    let lines = items.len();
+    for (index, item) in items.iter_mut().enumerate() {
+        let share = if index + 1 == lines || subtotal.is_zero() {
+            left
+        } else {
+            round(discount_total * line_amount(item) / subtotal)
+        };
+        item.discount_amount = share.min(left).min(line_amount(item));
+        left -= item.discount_amount;
+    }
+}
+
+pub fn order_total(items: &[OrderItemsModel]) -> Decimal {
+    round(
+        items
+            .iter()
+            .map(|item| line_amount(item) - item.discount_amount + item.tax_amount)
+            .sum(),
+    )
+}
+
+pub async fn check_if_supplier_in_order(
+    txn: &DatabaseTransaction,
+    supplier_id: i32,
+    order_id: i32,
+) -> Result<(), async_graphql::Error> {
+    use crate::entity::{order_items, products};
+    if order_items::Entity::find()
+        .inner_join(products::Entity)
+        .filter(order_items::Column::OrderId.eq(order_id))
+        .filter(products::Column::SupplierId.eq(supplier_id))
+        .one(txn)
+        .await?
+        .is_none()
+    {
+        return Err("Supplier has no products in this order".into());
+    }
+    Ok(())
+}
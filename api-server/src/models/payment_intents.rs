@@ -0,0 +1,230 @@
+use crate::{
+    entity::{
+        bills, orders,
+        payment_events::Model as PaymentEventsModel,
+        payment_intents::{self, Model as PaymentIntentsModel},
+        refunds,
+    },
+    models::{
+        bills::BILL_STATUS_PAID,
+        gift_cards::issue_purchased_gift_cards,
+        orders::{ORDER_STATUS_PAID, ORDER_STATUS_PENDING},
+        returns::REFUND_STATUS_PENDING,
+        supplier_ledger::record_order_sales,
+    },
+    money::Money,
+    payment_gateway::SharedPaymentProvider,
+};
+use async_graphql::SimpleObject;
+use chrono::Utc;
+use sea_orm::{
+    prelude::{DateTimeWithTimeZone, Decimal},
+    ActiveModelTrait,
+    ActiveValue::Set,
+    ColumnTrait, DatabaseConnection, DatabaseTransaction, DbErr, EntityTrait, QueryFilter,
+};
+use serde::Deserialize;
+
+pub const PAYMENT_INTENT_STATUS_AUTHORIZED: &str = "AUTHORIZED";
+pub const PAYMENT_INTENT_STATUS_CAPTURED: &str = "CAPTURED";
+pub const PAYMENT_INTENT_STATUS_VOIDED: &str = "VOIDED";
+pub const PAYMENT_INTENT_STATUS_FAILED: &str = "FAILED";
+
+pub fn check_payment_event_status(status: &str) -> Result<(), &'static str> {
+    match status {
+        PAYMENT_INTENT_STATUS_CAPTURED
+        | PAYMENT_INTENT_STATUS_VOIDED
+        | PAYMENT_INTENT_STATUS_FAILED => Ok(()),
+        _ => Err("Invalid payment event status"),
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct PaymentIntents {
+    pub payment_intent_id: i32,
+    pub order_id: i32,
+    pub bill_id: i32,
+    pub provider: String,
+    pub provider_reference: Option<String>,
+    pub amount: Money,
+    pub captured_amount: Money,
+    pub refunded_amount: Money,
+    pub status: String,
+    pub failure_reason: Option<String>,
+    pub created_at: Option<DateTimeWithTimeZone>,
+    pub updated_at: Option<DateTimeWithTimeZone>,
+}
+
+impl From<PaymentIntentsModel> for PaymentIntents {
+    fn from(val: PaymentIntentsModel) -> PaymentIntents {
+        PaymentIntents {
+            payment_intent_id: val.payment_intent_id,
+            order_id: val.order_id,
+            bill_id: val.bill_id,
+            provider: val.provider,
+            provider_reference: val.provider_reference,
+            amount: Money::new(val.amount, &val.currency),
+            captured_amount: Money::new(val.captured_amount, &val.currency),
+            refunded_amount: Money::new(val.refunded_amount, &val.currency),
+            status: val.status,
+            failure_reason: val.failure_reason,
+            created_at: val.created_at,
+            updated_at: val.updated_at,
+        }
+    }
+}
+
+// an order has at most one intent that is still authorized or has been captured, failed and
+// voided ones are kept for the history
+pub async fn live_payment_intent(
+    txn: &DatabaseTransaction,
+    order_id: i32,
+) -> Result<Option<PaymentIntentsModel>, DbErr> {
+    payment_intents::Entity::find()
+        .filter(payment_intents::Column::OrderId.eq(order_id))
+        .filter(payment_intents::Column::Status.is_in([
+            PAYMENT_INTENT_STATUS_AUTHORIZED,
+            PAYMENT_INTENT_STATUS_CAPTURED,
+        ]))
+        .one(txn)
+        .await
+}
+
+// money the customer already paid for cancelled items goes back as a refund, which is then
+// processed against the captured payment
+pub fn cancellation_refund(
+    intent: &PaymentIntentsModel,
+    payment_method_id: i32,
+    amount: Decimal,
+) -> refunds::ActiveModel {
+    refunds::ActiveModel {
+        return_id: Set(None),
+        bill_id: Set(intent.bill_id),
+        payment_method_id: Set(payment_method_id),
+        amount: Set(amount),
+        currency: Set(intent.currency.clone()),
+        status: Set(REFUND_STATUS_PENDING.to_string()),
+        ..Default::default()
+    }
+}
+
+// releases the payment of an order whose cancellation is already saved, a failure is kept on the
+// payment so it can be voided by hand
+pub async fn void_cancelled_payment(
+    db: &DatabaseConnection,
+    payment_provider: &SharedPaymentProvider,
+    payment_intent: PaymentIntentsModel,
+) -> Result<(), async_graphql::Error> {
+    let reference = payment_intent
+        .provider_reference
+        .clone()
+        .ok_or("Payment has no provider reference")?;
+    let voided = payment_provider
+        .void(
+            &reference,
+            &format!("void_{}", payment_intent.payment_intent_id),
+        )
+        .await;
+
+    let mut payment_intent: payment_intents::ActiveModel = payment_intent.into();
+    payment_intent.updated_at = Set(Some(Utc::now().fixed_offset()));
+    match voided {
+        Ok(_) => {
+            payment_intent.status = Set(PAYMENT_INTENT_STATUS_VOIDED.to_string());
+            payment_intent.update(db).await?;
+            Ok(())
+        }
+        Err(e) => {
+            payment_intent.failure_reason = Set(Some(e.to_string()));
+            payment_intent.update(db).await?;
+            Err(format!(
+                "Order cancelled but the payment could not be released: {}",
+                e
+            )
+            .into())
+        }
+    }
+}
+
+// the captured payment settles the bill and the pending order counts as paid from then on, an
+// order that was cancelled or already paid can't be captured for
+pub async fn capture_payment_intent(
+    txn: &DatabaseTransaction,
+    payment_intent: PaymentIntentsModel,
+    amount: Decimal,
+) -> Result<PaymentIntentsModel, DbErr> {
+    let bill_id = payment_intent.bill_id;
+    let order_id = payment_intent.order_id;
+
+    if amount <= Decimal::ZERO {
+        return Err(DbErr::Custom(
+            "Captured amount must be greater than zero".to_string(),
+        ));
+    }
+
+    let paid_order = orders::Entity::update_many()
+        .col_expr(orders::Column::Status, ORDER_STATUS_PAID.into())
+        .filter(orders::Column::OrderId.eq(order_id))
+        .filter(orders::Column::Status.eq(ORDER_STATUS_PENDING))
+        .exec(txn)
+        .await?;
+    if paid_order.rows_affected == 0 {
+        return Err(DbErr::Custom(
+            "Only pending orders can be paid for".to_string(),
+        ));
+    }
+
+    let mut payment_intent: payment_intents::ActiveModel = payment_intent.into();
+    payment_intent.status = Set(PAYMENT_INTENT_STATUS_CAPTURED.to_string());
+    payment_intent.captured_amount = Set(amount);
+    payment_intent.failure_reason = Set(None);
+    payment_intent.updated_at = Set(Some(Utc::now().fixed_offset()));
+    let payment_intent = payment_intent.update(txn).await?;
+
+    bills::Entity::update_many()
+        .col_expr(bills::Column::PaymentStatus, BILL_STATUS_PAID.into())
+        .filter(bills::Column::BillId.eq(bill_id))
+        .exec(txn)
+        .await?;
+
+    issue_purchased_gift_cards(txn, order_id).await?;
+    record_order_sales(txn, order_id).await?;
+
+    Ok(payment_intent)
+}
+
+// payload pushed by the payment provider to the webhook endpoint
+#[derive(Deserialize)]
+pub struct PaymentEvent {
+    pub event_id: String,
+    pub provider_reference: String,
+    pub status: String,
+    pub amount: Option<Decimal>,
+    pub currency: Option<String>,
+    pub failure_reason: Option<String>,
+}
+
+#[derive(SimpleObject)]
+pub struct PaymentEvents {
+    pub payment_event_id: i32,
+    pub provider_event_id: String,
+    pub provider_reference: String,
+    pub status: String,
+    pub error: Option<String>,
+    pub received_at: Option<DateTimeWithTimeZone>,
+    pub processed_at: Option<DateTimeWithTimeZone>,
+}
+
+impl From<PaymentEventsModel> for PaymentEvents {
+    fn from(val: PaymentEventsModel) -> PaymentEvents {
+        PaymentEvents {
+            payment_event_id: val.payment_event_id,
+            provider_event_id: val.provider_event_id,
+            provider_reference: val.provider_reference,
+            status: val.status,
+            error: val.error,
+            received_at: val.received_at,
+            processed_at: val.processed_at,
+        }
+    }
+}
@@ -0,0 +1,307 @@
+use crate::{
+    entity::sea_orm_active_enums::PaymentMethodType, models::payments::RegisterPaymentMethod,
+};
+use async_graphql::{value, ErrorExtensions, Value};
+use chrono::{Datelike, Utc};
+use lazy_regex::regex;
+
+pub struct FieldError {
+    pub field: &'static str,
+    pub message: &'static str,
+}
+
+impl FieldError {
+    fn new(field: &'static str, message: &'static str) -> Self {
+        Self { field, message }
+    }
+}
+
+// every invalid field is reported at once, in the `fields` extension as `{ field, message }`
+pub struct PaymentValidationError(pub Vec<FieldError>);
+
+impl From<PaymentValidationError> for async_graphql::Error {
+    fn from(val: PaymentValidationError) -> async_graphql::Error {
+        let message = val
+            .0
+            .iter()
+            .map(|error| format!("{}: {}", error.field, error.message))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        async_graphql::Error::new(format!("Invalid payment method: {}", message)).extend_with(
+            |_, e| {
+                e.set("code", "INVALID_PAYMENT_METHOD");
+                e.set(
+                    "fields",
+                    Value::List(
+                        val.0
+                            .iter()
+                            .map(|error| value!({ "field": error.field, "message": error.message }))
+                            .collect(),
+                    ),
+                );
+            },
+        )
+    }
+}
+
+// checks the fields the payment type needs and normalizes them (spaces removed, codes upper cased)
+pub fn validate_payment_method(
+    payment_type: &PaymentMethodType,
+    input: &mut RegisterPaymentMethod,
+) -> Result<(), PaymentValidationError> {
+    let errors = match payment_type {
+        PaymentMethodType::Card => validate_card(input),
+        PaymentMethodType::Iban => validate_iban(input),
+        PaymentMethodType::Netbanking => validate_netbanking(input),
+        PaymentMethodType::Upi => validate_upi(input),
+        PaymentMethodType::Cod | PaymentMethodType::Wallet => Vec::new(),
+    };
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(PaymentValidationError(errors))
+    }
+}
+
+fn validate_card(input: &mut RegisterPaymentMethod) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+
+    match input.card_number.as_mut() {
+        None => errors.push(FieldError::new("cardNumber", "Card number is required")),
+        Some(card_number) => {
+            card_number.retain(|c| c != ' ' && c != '-');
+            if !(12..=19).contains(&card_number.len())
+                || !card_number.chars().all(|c| c.is_ascii_digit())
+            {
+                errors.push(FieldError::new(
+                    "cardNumber",
+                    "Card number must be 12 to 19 digits",
+                ));
+            } else if !luhn_valid(card_number) {
+                errors.push(FieldError::new("cardNumber", "Card number is not valid"));
+            }
+        }
+    }
+
+    // a card is good until the end of the month it expires in
+    match input.card_expiration_date {
+        None => errors.push(FieldError::new(
+            "cardExpirationDate",
+            "Card expiration date is required",
+        )),
+        Some(expiration_date) => {
+            let today = Utc::now().date_naive();
+            if (expiration_date.year(), expiration_date.month()) < (today.year(), today.month()) {
+                errors.push(FieldError::new("cardExpirationDate", "Card has expired"));
+            }
+        }
+    }
+
+    errors
+}
+
+fn validate_iban(input: &mut RegisterPaymentMethod) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+
+    match input.iban.as_mut() {
+        None => errors.push(FieldError::new("iban", "IBAN number is required")),
+        Some(iban) => {
+            *iban = iban.replace(' ', "").to_uppercase();
+            if !regex!(r"^[A-Z]{2}[0-9]{2}[A-Z0-9]{11,30}$").is_match(iban) {
+                errors.push(FieldError::new(
+                    "iban",
+                    "IBAN must be a country code, two check digits and the account number",
+                ));
+            } else if !iban_checksum_valid(iban) {
+                errors.push(FieldError::new("iban", "IBAN check digits don't match"));
+            }
+        }
+    }
+
+    errors
+}
+
+fn validate_netbanking(input: &mut RegisterPaymentMethod) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+
+    if input
+        .bank_name
+        .as_ref()
+        .is_none_or(|bank_name| bank_name.trim().is_empty())
+    {
+        errors.push(FieldError::new("bankName", "Bank name is required"));
+    }
+
+    if input
+        .account_holder_name
+        .as_ref()
+        .is_none_or(|account_holder_name| account_holder_name.trim().is_empty())
+    {
+        errors.push(FieldError::new(
+            "accountHolderName",
+            "Account holder name is required",
+        ));
+    }
+
+    match input.bank_account_number.as_mut() {
+        None => errors.push(FieldError::new(
+            "bankAccountNumber",
+            "Bank account number is required",
+        )),
+        Some(bank_account_number) => {
+            bank_account_number.retain(|c| c != ' ');
+            if !regex!(r"^[0-9]{9,18}$").is_match(bank_account_number) {
+                errors.push(FieldError::new(
+                    "bankAccountNumber",
+                    "Bank account number must be 9 to 18 digits",
+                ));
+            }
+        }
+    }
+
+    // four letters for the bank, a zero, then six characters for the branch
+    match input.ifsc_code.as_mut() {
+        None => errors.push(FieldError::new("ifscCode", "IFSC code is required")),
+        Some(ifsc_code) => {
+            *ifsc_code = ifsc_code.trim().to_uppercase();
+            if !regex!(r"^[A-Z]{4}0[A-Z0-9]{6}$").is_match(ifsc_code) {
+                errors.push(FieldError::new("ifscCode", "IFSC code is not valid"));
+            }
+        }
+    }
+
+    errors
+}
+
+fn validate_upi(input: &mut RegisterPaymentMethod) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+
+    match input.upi_id.as_mut() {
+        None => errors.push(FieldError::new("upiId", "UPI ID is required")),
+        Some(upi_id) => {
+            *upi_id = upi_id.trim().to_string();
+            if !regex!(r"^[a-zA-Z0-9._-]{2,256}@[a-zA-Z][a-zA-Z0-9]{1,63}$").is_match(upi_id) {
+                errors.push(FieldError::new("upiId", "UPI ID must look like name@bank"));
+            }
+        }
+    }
+
+    errors
+}
+
+fn luhn_valid(card_number: &str) -> bool {
+    let sum: u32 = card_number
+        .chars()
+        .rev()
+        .filter_map(|c| c.to_digit(10))
+        .enumerate()
+        .map(|(i, digit)| match (i % 2, digit * 2) {
+            (1, doubled) if doubled > 9 => doubled - 9,
+            (1, doubled) => doubled,
+            _ => digit,
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+// the first four characters move to the end, letters become 10 to 35 and the number mod 97 must be 1
+fn iban_checksum_valid(iban: &str) -> bool {
+    let rearranged = iban[4..].chars().chain(iban[..4].chars());
+    let mut remainder = 0u32;
+    for c in rearranged {
+        let Some(value) = c.to_digit(36) else {
+            return false;
+        };
+        remainder = if value < 10 {
+            (remainder * 10 + value) % 97
+        } else {
+            (remainder * 100 + value) % 97
+        };
+    }
+    remainder == 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{iban_checksum_valid, luhn_valid, validate_netbanking, validate_upi};
+    use crate::models::payments::RegisterPaymentMethod;
+
+    fn payment_method() -> RegisterPaymentMethod {
+        RegisterPaymentMethod {
+            payment_type: String::new(),
+            is_default: None,
+            bank_name: Some("State Bank of India".to_string()),
+            account_holder_name: Some("Test Customer".to_string()),
+            card_number: None,
+            card_expiration_date: None,
+            iban: None,
+            upi_id: None,
+            bank_account_number: Some("123456789012".to_string()),
+            ifsc_code: None,
+        }
+    }
+
+    fn ifsc_valid(ifsc_code: &str) -> bool {
+        let mut input = payment_method();
+        input.ifsc_code = Some(ifsc_code.to_string());
+        validate_netbanking(&mut input).is_empty()
+    }
+
+    fn upi_valid(upi_id: &str) -> bool {
+        let mut input = payment_method();
+        input.upi_id = Some(upi_id.to_string());
+        validate_upi(&mut input).is_empty()
+    }
+
+    #[test]
+    fn luhn() {
+        assert!(luhn_valid("4111111111111111"));
+        assert!(luhn_valid("5555555555554444"));
+        assert!(luhn_valid("378282246310005"));
+        assert!(luhn_valid("79927398713"));
+
+        assert!(!luhn_valid("4111111111111112"));
+        assert!(!luhn_valid("5555555555554443"));
+        assert!(!luhn_valid("79927398710"));
+    }
+
+    #[test]
+    fn iban_checksum() {
+        assert!(iban_checksum_valid("GB82WEST12345698765432"));
+        assert!(iban_checksum_valid("DE89370400440532013000"));
+        assert!(iban_checksum_valid("FR1420041010050500013M02606"));
+
+        assert!(!iban_checksum_valid("GB82WEST12345698765431"));
+        assert!(!iban_checksum_valid("GB28WEST12345698765432"));
+        assert!(!iban_checksum_valid("DE89370400440532013001"));
+    }
+
+    #[test]
+    fn ifsc_code() {
+        assert!(ifsc_valid("SBIN0001234"));
+        assert!(ifsc_valid("HDFC0ABC123"));
+        assert!(ifsc_valid(" hdfc0abc123 "));
+
+        assert!(!ifsc_valid("SBIN1001234"));
+        assert!(!ifsc_valid("SBI00001234"));
+        assert!(!ifsc_valid("SBIN000123"));
+        assert!(!ifsc_valid("SBIN00012345"));
+        assert!(!ifsc_valid("SBIN000123!"));
+    }
+
+    #[test]
+    fn upi_id() {
+        assert!(upi_valid("name@okaxis"));
+        assert!(upi_valid("first.last-1@ybl"));
+        assert!(upi_valid(" 9876543210@paytm "));
+
+        assert!(!upi_valid("name"));
+        assert!(!upi_valid("n@okaxis"));
+        assert!(!upi_valid("name@1bank"));
+        assert!(!upi_valid("name@b"));
+        assert!(!upi_valid("na me@okaxis"));
+        assert!(!upi_valid("name@ok@axis"));
+    }
+}
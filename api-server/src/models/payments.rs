@@ -1,7 +1,11 @@
-use crate::entity::{
-    card_types::{self, Model as CardTypesModel},
-    payment_methods::{self, Model as PaymentMethodsModel},
-    sea_orm_active_enums::PaymentMethodType,
+use crate::{
+    entity::{
+        card_types::{self, Model as CardTypesModel},
+        payment_methods::{self, Model as PaymentMethodsModel},
+        sea_orm_active_enums::PaymentMethodType,
+    },
+    models::payment_validation::validate_payment_method,
+    payment_gateway::SharedPaymentProvider,
 };
 use async_graphql::{InputObject, SimpleObject};
 use sea_orm::{
@@ -17,7 +21,8 @@ pub struct PaymentMethods {
     pub is_default: Option<bool>,
     pub bank_name: Option<String>,
     pub account_holder_name: Option<String>,
-    pub card_number: Option<String>,
+    pub card_last4: Option<String>,
+    pub card_brand: Option<String>,
     pub card_expiration_date: Option<Date>,
     pub iban: Option<String>,
     pub upi_id: Option<String>,
@@ -35,7 +40,8 @@ impl From<PaymentMethodsModel> for PaymentMethods {
             is_default: payment_method.is_default,
             bank_name: payment_method.bank_name,
             account_holder_name: payment_method.account_holder_name,
-            card_number: payment_method.card_number,
+            card_last4: payment_method.card_last4,
+            card_brand: payment_method.card_brand,
             card_expiration_date: payment_method.card_expiration_date,
             iban: payment_method.iban,
             upi_id: payment_method.upi_id,
@@ -46,6 +52,8 @@ impl From<PaymentMethodsModel> for PaymentMethods {
     }
 }
 
+// the card number is only passed on to the payment provider, what gets stored is the provider's
+// token with the last four digits and the brand for display
 #[derive(InputObject)]
 pub struct RegisterPaymentMethod {
     pub payment_type: String,
@@ -58,15 +66,19 @@ pub struct RegisterPaymentMethod {
     pub upi_id: Option<String>,
     pub bank_account_number: Option<String>,
     pub ifsc_code: Option<String>,
-    pub card_type_name: Option<String>,
 }
 
 pub async fn create_payment_method(
     customer_id: i32,
     is_default: Option<bool>,
-    input: RegisterPaymentMethod,
+    mut input: RegisterPaymentMethod,
+    payment_provider: &SharedPaymentProvider,
     txn: &DatabaseTransaction,
 ) -> Result<payment_methods::ActiveModel, async_graphql::Error> {
+    let payment_type = PaymentMethodType::try_from_value(&input.payment_type)
+        .map_err(|_| "Invalid payment type")?;
+    validate_payment_method(&payment_type, &mut input)?;
+
     // check if any default payment method exists and update it to not default
     if is_default.unwrap_or(false) {
         let default_payment_method = payment_methods::Entity::find()
@@ -83,51 +95,60 @@ pub async fn create_payment_method(
         }
     }
 
-    match input.payment_type.as_str() {
-        "card" => {
-            let card_type_id = Some(
-                card_types::Entity::insert(card_types::ActiveModel {
-                    name: Set(input
-                        .card_type_name
-                        .clone()
-                        .unwrap_or_else(|| "Unknown".to_string())),
-                    ..Default::default()
-                })
-                .exec(txn)
+    match payment_type {
+        PaymentMethodType::Card => {
+            let card_number = input.card_number.ok_or("Card number is required")?;
+            let card_expiration_date = input
+                .card_expiration_date
+                .ok_or("Card expiration date is required")?;
+            let card_token = payment_provider
+                .tokenize_card(&card_number, card_expiration_date)
+                .await?;
+            let card_last4: String = card_number
+                .chars()
+                .skip(card_number.chars().count().saturating_sub(4))
+                .collect();
+
+            let card_brand = detect_card_brand(&card_number);
+            let card_type_id = card_types::Entity::find()
+                .filter(card_types::Column::Name.eq(card_brand))
+                .one(txn)
                 .await?
-                .last_insert_id,
-            );
+                .map(|card_type| card_type.card_type_id);
             Ok(payment_methods::ActiveModel {
                 customer_id: Set(customer_id),
                 payment_type: Set(PaymentMethodType::Card),
                 is_default: Set(is_default),
-                card_number: Set(Some(
-                    input.card_number.clone().ok_or("Card number is required")?,
-                )),
-                card_expiration_date: Set(Some(
-                    input
-                        .card_expiration_date
-                        .ok_or("Card expiration date is required")?,
-                )),
+                card_token: Set(Some(card_token)),
+                card_last4: Set(Some(card_last4)),
+                card_brand: Set(Some(card_brand.to_string())),
+                card_expiration_date: Set(Some(card_expiration_date)),
                 card_type_id: Set(card_type_id),
                 ..Default::default()
             })
         }
-        "upi" => Ok(payment_methods::ActiveModel {
+        PaymentMethodType::Upi => Ok(payment_methods::ActiveModel {
             customer_id: Set(customer_id),
             payment_type: Set(PaymentMethodType::Upi),
             is_default: Set(is_default),
             upi_id: Set(Some(input.upi_id.clone().ok_or("UPI ID is required")?)),
             ..Default::default()
         }),
-        "iban" => Ok(payment_methods::ActiveModel {
+        PaymentMethodType::Iban => Ok(payment_methods::ActiveModel {
             customer_id: Set(customer_id),
             payment_type: Set(PaymentMethodType::Iban),
             is_default: Set(is_default),
             iban: Set(Some(input.iban.clone().ok_or("IBAN number is required")?)),
             ..Default::default()
         }),
-        "netbanking" => Ok(payment_methods::ActiveModel {
+        // cash on delivery and the wallet need nothing from the customer up front
+        PaymentMethodType::Cod | PaymentMethodType::Wallet => Ok(payment_methods::ActiveModel {
+            customer_id: Set(customer_id),
+            payment_type: Set(payment_type),
+            is_default: Set(is_default),
+            ..Default::default()
+        }),
+        PaymentMethodType::Netbanking => Ok(payment_methods::ActiveModel {
             customer_id: Set(customer_id),
             payment_type: Set(PaymentMethodType::Netbanking),
             is_default: Set(is_default),
@@ -151,7 +172,44 @@ pub async fn create_payment_method(
             )),
             ..Default::default()
         }),
-        _ => Err("Invalid payment type".into()),
+    }
+}
+
+pub const CARD_BRAND_VISA: &str = "Visa";
+pub const CARD_BRAND_MASTERCARD: &str = "Mastercard";
+pub const CARD_BRAND_AMEX: &str = "American Express";
+pub const CARD_BRAND_DISCOVER: &str = "Discover";
+pub const CARD_BRAND_DINERS: &str = "Diners Club";
+pub const CARD_BRAND_JCB: &str = "JCB";
+pub const CARD_BRAND_MAESTRO: &str = "Maestro";
+pub const CARD_BRAND_RUPAY: &str = "RuPay";
+pub const CARD_BRAND_UNIONPAY: &str = "UnionPay";
+pub const CARD_BRAND_UNKNOWN: &str = "Unknown";
+
+// the brand comes from the issuer identification number, the leading digits of the card.
+// RuPay shares prefixes with Discover and Maestro, so its ranges are checked first. RuPay cards
+// co-branded with JCB are issued in JCB's 3528-3589 range and are reported as JCB
+pub fn detect_card_brand(card_number: &str) -> &'static str {
+    let prefix = |len: usize| -> u32 {
+        card_number
+            .get(..len)
+            .and_then(|digits| digits.parse().ok())
+            .unwrap_or(0)
+    };
+
+    match (prefix(1), prefix(2), prefix(3), prefix(4), prefix(6)) {
+        (4, ..) => CARD_BRAND_VISA,
+        (_, 34 | 37, ..) => CARD_BRAND_AMEX,
+        (_, 51..=55, ..) | (.., 2221..=2720, _) => CARD_BRAND_MASTERCARD,
+        (_, 60 | 81 | 82, ..) if prefix(4) != 6011 => CARD_BRAND_RUPAY,
+        (_, _, 508, ..) | (.., 6521 | 6522, _) => CARD_BRAND_RUPAY,
+        (.., 6011, _) | (_, _, 644..=649, ..) | (_, 65, ..) => CARD_BRAND_DISCOVER,
+        (.., 3528..=3589, _) => CARD_BRAND_JCB,
+        (_, 36 | 38 | 39, ..) | (_, _, 300..=305, ..) => CARD_BRAND_DINERS,
+        (_, 62, ..) => CARD_BRAND_UNIONPAY,
+        (_, 50 | 56..=58, ..) | (_, _, 639, ..) | (.., 6304 | 6759, _) => CARD_BRAND_MAESTRO,
+        (.., 676770 | 676774) => CARD_BRAND_MAESTRO,
+        _ => CARD_BRAND_UNKNOWN,
     }
 }
 
@@ -169,3 +227,106 @@ impl From<CardTypesModel> for CardTypes {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the brand only depends on the leading digits, the rest of the number is padded with zeros
+    fn brand(prefix: &str) -> &'static str {
+        detect_card_brand(&format!("{:0<16}", prefix))
+    }
+
+    #[test]
+    fn visa() {
+        assert_eq!(brand("4"), CARD_BRAND_VISA);
+        assert_eq!(brand("4999"), CARD_BRAND_VISA);
+        assert_ne!(brand("3999"), CARD_BRAND_VISA);
+    }
+
+    #[test]
+    fn amex() {
+        assert_eq!(brand("34"), CARD_BRAND_AMEX);
+        assert_eq!(brand("37"), CARD_BRAND_AMEX);
+        assert_eq!(brand("33"), CARD_BRAND_UNKNOWN);
+        assert_ne!(brand("35"), CARD_BRAND_AMEX);
+    }
+
+    #[test]
+    fn mastercard() {
+        assert_eq!(brand("51"), CARD_BRAND_MASTERCARD);
+        assert_eq!(brand("55"), CARD_BRAND_MASTERCARD);
+        assert_eq!(brand("2221"), CARD_BRAND_MASTERCARD);
+        assert_eq!(brand("2720"), CARD_BRAND_MASTERCARD);
+        assert_eq!(brand("2220"), CARD_BRAND_UNKNOWN);
+        assert_eq!(brand("2721"), CARD_BRAND_UNKNOWN);
+        assert_ne!(brand("56"), CARD_BRAND_MASTERCARD);
+    }
+
+    #[test]
+    fn rupay() {
+        assert_eq!(brand("6012"), CARD_BRAND_RUPAY);
+        assert_eq!(brand("60"), CARD_BRAND_RUPAY);
+        assert_eq!(brand("81"), CARD_BRAND_RUPAY);
+        assert_eq!(brand("82"), CARD_BRAND_RUPAY);
+        assert_eq!(brand("508"), CARD_BRAND_RUPAY);
+        assert_eq!(brand("6521"), CARD_BRAND_RUPAY);
+        assert_eq!(brand("6522"), CARD_BRAND_RUPAY);
+        assert_eq!(brand("80"), CARD_BRAND_UNKNOWN);
+        assert_eq!(brand("83"), CARD_BRAND_UNKNOWN);
+        assert_ne!(brand("507"), CARD_BRAND_RUPAY);
+        assert_ne!(brand("509"), CARD_BRAND_RUPAY);
+    }
+
+    #[test]
+    fn discover() {
+        assert_eq!(brand("6011"), CARD_BRAND_DISCOVER);
+        assert_eq!(brand("644"), CARD_BRAND_DISCOVER);
+        assert_eq!(brand("649"), CARD_BRAND_DISCOVER);
+        assert_eq!(brand("6520"), CARD_BRAND_DISCOVER);
+        assert_eq!(brand("6523"), CARD_BRAND_DISCOVER);
+        assert_eq!(brand("643"), CARD_BRAND_UNKNOWN);
+    }
+
+    #[test]
+    fn jcb() {
+        assert_eq!(brand("3528"), CARD_BRAND_JCB);
+        assert_eq!(brand("3530"), CARD_BRAND_JCB);
+        assert_eq!(brand("3560"), CARD_BRAND_JCB);
+        assert_eq!(brand("3589"), CARD_BRAND_JCB);
+        assert_eq!(brand("3527"), CARD_BRAND_UNKNOWN);
+        assert_eq!(brand("3590"), CARD_BRAND_UNKNOWN);
+    }
+
+    #[test]
+    fn diners() {
+        assert_eq!(brand("300"), CARD_BRAND_DINERS);
+        assert_eq!(brand("305"), CARD_BRAND_DINERS);
+        assert_eq!(brand("36"), CARD_BRAND_DINERS);
+        assert_eq!(brand("38"), CARD_BRAND_DINERS);
+        assert_eq!(brand("39"), CARD_BRAND_DINERS);
+        assert_eq!(brand("306"), CARD_BRAND_UNKNOWN);
+    }
+
+    #[test]
+    fn unionpay() {
+        assert_eq!(brand("62"), CARD_BRAND_UNIONPAY);
+        assert_eq!(brand("6299"), CARD_BRAND_UNIONPAY);
+        assert_eq!(brand("61"), CARD_BRAND_UNKNOWN);
+        assert_eq!(brand("63"), CARD_BRAND_UNKNOWN);
+    }
+
+    #[test]
+    fn maestro() {
+        assert_eq!(brand("50"), CARD_BRAND_MAESTRO);
+        assert_eq!(brand("56"), CARD_BRAND_MAESTRO);
+        assert_eq!(brand("58"), CARD_BRAND_MAESTRO);
+        assert_eq!(brand("639"), CARD_BRAND_MAESTRO);
+        assert_eq!(brand("6304"), CARD_BRAND_MAESTRO);
+        assert_eq!(brand("6759"), CARD_BRAND_MAESTRO);
+        assert_eq!(brand("676770"), CARD_BRAND_MAESTRO);
+        assert_eq!(brand("676774"), CARD_BRAND_MAESTRO);
+        assert_eq!(brand("59"), CARD_BRAND_UNKNOWN);
+        assert_eq!(brand("676771"), CARD_BRAND_UNKNOWN);
+    }
+}
@@ -0,0 +1,457 @@
+use crate::{
+    entity::{
+        categories,
+        product_variant_options::{self, Model as ProductVariantOptionsModel},
+        products::{self, Model as ProductsModel},
+    },
+    models::{
+        currency::exchange_rate,
+        products::{
+            create_product_model, update_product_model, RegisterProduct, UpdateProduct,
+            PRODUCT_STATUS_ARCHIVED,
+        },
+        stock_alerts::notify_back_in_stock,
+        stock_movements::{adjust_stock_to, STOCK_MOVEMENT_IMPORT},
+    },
+    money::{currency_code, Money, DEFAULT_CURRENCY},
+};
+use async_graphql::{Enum, MaybeUndefined, SimpleObject};
+use sea_orm::{
+    prelude::Decimal, ActiveValue::Set, ColumnTrait, DatabaseConnection, DatabaseTransaction,
+    EntityTrait, QueryFilter, QueryOrder, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum CatalogueFormat {
+    Csv,
+    JsonLines,
+}
+
+// one product of an import or an export, CSV and JSON Lines have the same columns. Columns left
+// empty keep the value a product already has, or the default for a new product.
+#[derive(Serialize, Deserialize)]
+pub struct CatalogueRow {
+    #[serde(default)]
+    pub sku: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub price: Option<Decimal>,
+    pub currency: Option<String>,
+    pub category_id: Option<i32>,
+    // the SKU of the product this one is a variant of
+    pub base_sku: Option<String>,
+    pub status: Option<String>,
+    pub stock_quantity: Option<i32>,
+    pub reorder_threshold: Option<i32>,
+    pub weight_grams: Option<i32>,
+    pub length_mm: Option<i32>,
+    pub width_mm: Option<i32>,
+    pub height_mm: Option<i32>,
+    pub is_gift_card: Option<bool>,
+    // separated by `|`
+    pub media_paths: Option<String>,
+    // `name=value` or `name=value=price adjustment`, separated by `;`, replace the ones the product has
+    pub variant_options: Option<String>,
+}
+
+#[derive(SimpleObject)]
+pub struct ProductImportError {
+    pub row: i32,
+    pub sku: Option<String>,
+    pub message: String,
+}
+
+#[derive(SimpleObject)]
+pub struct ProductImportReport {
+    pub dry_run: bool,
+    pub created: i32,
+    pub updated: i32,
+    pub errors: Vec<ProductImportError>,
+}
+
+// rows are numbered from 1, the CSV header doesn't count and in JSON Lines a row is a line
+fn parse_catalogue(
+    format: CatalogueFormat,
+    data: &str,
+) -> Vec<(i32, Result<CatalogueRow, String>)> {
+    match format {
+        CatalogueFormat::Csv => csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(data.as_bytes())
+            .into_deserialize()
+            .zip(1..)
+            .map(|(row, number)| (number, row.map_err(|e| e.to_string())))
+            .collect(),
+        CatalogueFormat::JsonLines => data
+            .lines()
+            .zip(1..)
+            .filter(|(line, _)| !line.trim().is_empty())
+            .map(|(line, number)| {
+                (
+                    number,
+                    serde_json::from_str(line).map_err(|e| e.to_string()),
+                )
+            })
+            .collect(),
+    }
+}
+
+fn parse_variant_options(
+    options: &str,
+) -> Result<Vec<product_variant_options::ActiveModel>, async_graphql::Error> {
+    options
+        .split(';')
+        .map(str::trim)
+        .filter(|option| !option.is_empty())
+        .map(|option| {
+            let mut parts = option.split('=').map(str::trim);
+            let (Some(name), Some(value)) = (parts.next(), parts.next()) else {
+                return Err(format!("Variant option {} must be name=value", option).into());
+            };
+            let price_adjustment = parts
+                .next()
+                .map(|adjustment| adjustment.parse::<Decimal>())
+                .transpose()
+                .map_err(|_| format!("Invalid price adjustment in {}", option))?;
+            if name.is_empty() || value.is_empty() || parts.next().is_some() {
+                return Err(format!("Variant option {} must be name=value", option).into());
+            }
+            Ok(product_variant_options::ActiveModel {
+                option_name: Set(name.to_string()),
+                option_value: Set(value.to_string()),
+                price_adjustment: Set(price_adjustment),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+fn format_variant_options(options: &[ProductVariantOptionsModel]) -> Option<String> {
+    if options.is_empty() {
+        return None;
+    }
+    Some(
+        options
+            .iter()
+            .map(|option| match option.price_adjustment {
+                Some(adjustment) => format!(
+                    "{}={}={}",
+                    option.option_name, option.option_value, adjustment
+                ),
+                None => format!("{}={}", option.option_name, option.option_value),
+            })
+            .collect::<Vec<_>>()
+            .join(";"),
+    )
+}
+
+// an empty column leaves the product's value alone rather than clearing it
+fn value_or_undefined<T>(value: Option<T>) -> MaybeUndefined<T> {
+    value.map(Some).into()
+}
+
+async fn find_by_sku(
+    txn: &DatabaseTransaction,
+    supplier_id: i32,
+    sku: &str,
+) -> Result<Option<ProductsModel>, async_graphql::Error> {
+    Ok(products::Entity::find()
+        .filter(products::Column::SupplierId.eq(supplier_id))
+        .filter(products::Column::Sku.eq(sku))
+        .one(txn)
+        .await?)
+}
+
+// creates or updates the supplier's product with the row's SKU, true when it was created
+async fn import_row(
+    txn: &DatabaseTransaction,
+    supplier_id: i32,
+    user_id: i32,
+    row: CatalogueRow,
+) -> Result<(bool, ProductsModel), async_graphql::Error> {
+    if row.sku.is_empty() {
+        return Err("SKU is required".into());
+    }
+    let existing = find_by_sku(txn, supplier_id, &row.sku).await?;
+
+    let base_product_id = match &row.base_sku {
+        Some(base_sku) if *base_sku == row.sku => {
+            return Err("A product cannot be a variant of itself".into())
+        }
+        Some(base_sku) => Some(
+            find_by_sku(txn, supplier_id, base_sku)
+                .await?
+                .ok_or("Base product not found")?
+                .product_id,
+        ),
+        None => None,
+    };
+    if let Some(category_id) = row.category_id {
+        categories::Entity::find_by_id(category_id)
+            .one(txn)
+            .await?
+            .ok_or("Category not found")?;
+    }
+    let currency = match &row.currency {
+        Some(currency) => {
+            let currency = currency_code(currency).ok_or("Invalid currency code")?;
+            exchange_rate(txn, &currency).await?;
+            Some(currency)
+        }
+        None => None,
+    };
+    let media_paths = row.media_paths.map(|media_paths| {
+        media_paths
+            .split('|')
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    });
+    let variant_options = row
+        .variant_options
+        .as_deref()
+        .map(parse_variant_options)
+        .transpose()?;
+
+    let (created, product) = match existing {
+        None => {
+            let input = RegisterProduct {
+                name: row.name.ok_or("Name is required for a new product")?,
+                description: row.description,
+                base_price: Money::new(
+                    row.price.ok_or("Price is required for a new product")?,
+                    currency.as_deref().unwrap_or(DEFAULT_CURRENCY),
+                ),
+                category_id: row.category_id,
+                supplier_id: None,
+                stock_quantity: row.stock_quantity.unwrap_or(0),
+                media_paths,
+                base_product_id,
+                weight_grams: row.weight_grams,
+                length_mm: row.length_mm,
+                width_mm: row.width_mm,
+                height_mm: row.height_mm,
+                is_gift_card: row.is_gift_card,
+                reorder_threshold: row.reorder_threshold,
+                status: row.status,
+                sku: Some(row.sku),
+            };
+            let stock_quantity = input.stock_quantity;
+            let product = products::Entity::insert(create_product_model(input, supplier_id)?)
+                .exec_with_returning(txn)
+                .await?;
+            adjust_stock_to(
+                txn,
+                &product,
+                stock_quantity,
+                STOCK_MOVEMENT_IMPORT,
+                user_id,
+            )
+            .await?;
+            (true, product)
+        }
+        Some(product) => {
+            // a price without a currency is in the product's currency and the other way around
+            let base_price = (row.price.is_some() || currency.is_some()).then(|| {
+                Money::new(
+                    row.price.unwrap_or(product.base_price),
+                    currency.as_deref().unwrap_or(&product.currency),
+                )
+            });
+            let input = UpdateProduct {
+                name: row.name,
+                description: value_or_undefined(row.description),
+                base_price,
+                category_id: value_or_undefined(row.category_id),
+                stock_quantity: row.stock_quantity,
+                media_paths: value_or_undefined(media_paths),
+                base_product_id: value_or_undefined(base_product_id),
+                weight_grams: value_or_undefined(row.weight_grams),
+                length_mm: value_or_undefined(row.length_mm),
+                width_mm: value_or_undefined(row.width_mm),
+                height_mm: value_or_undefined(row.height_mm),
+                is_gift_card: row.is_gift_card,
+                reorder_threshold: value_or_undefined(row.reorder_threshold),
+                status: row.status,
+                sku: MaybeUndefined::Undefined,
+            };
+            let stock_quantity = input.stock_quantity;
+            let mut update = update_product_model(input)?;
+            update.product_id = Set(product.product_id);
+            let product = products::Entity::update(update)
+                .filter(products::Column::ProductId.eq(product.product_id))
+                .exec(txn)
+                .await?;
+            if let Some(stock_quantity) = stock_quantity {
+                adjust_stock_to(
+                    txn,
+                    &product,
+                    stock_quantity,
+                    STOCK_MOVEMENT_IMPORT,
+                    user_id,
+                )
+                .await?;
+            }
+            (false, product)
+        }
+    };
+
+    if let Some(variant_options) = variant_options {
+        product_variant_options::Entity::delete_many()
+            .filter(product_variant_options::Column::ProductId.eq(product.product_id))
+            .exec(txn)
+            .await?;
+        if !variant_options.is_empty() {
+            product_variant_options::Entity::insert_many(variant_options.into_iter().map(
+                |mut option| {
+                    option.product_id = Set(product.product_id);
+                    option
+                },
+            ))
+            .exec(txn)
+            .await?;
+        }
+    }
+
+    Ok((created, product))
+}
+
+// every row is imported on its own savepoint, a row with an error is reported and left out while
+// the others go in. A dry run does the same and rolls all of it back, so the report is exactly what
+// the import would do.
+pub async fn import_products(
+    db: &DatabaseConnection,
+    supplier_id: i32,
+    user_id: i32,
+    format: CatalogueFormat,
+    data: &str,
+    dry_run: bool,
+) -> Result<ProductImportReport, async_graphql::Error> {
+    let mut report = ProductImportReport {
+        dry_run,
+        created: 0,
+        updated: 0,
+        errors: Vec::new(),
+    };
+    let mut seen_skus = HashSet::new();
+    let mut restocked = Vec::new();
+
+    let txn = db.begin().await?;
+    for (number, row) in parse_catalogue(format, data) {
+        let row = match row {
+            Ok(row) => row,
+            Err(message) => {
+                report.errors.push(ProductImportError {
+                    row: number,
+                    sku: None,
+                    message,
+                });
+                continue;
+            }
+        };
+        if !row.sku.is_empty() && !seen_skus.insert(row.sku.clone()) {
+            report.errors.push(ProductImportError {
+                row: number,
+                sku: Some(row.sku),
+                message: "SKU appears more than once in the file".to_string(),
+            });
+            continue;
+        }
+
+        let sku = row.sku.clone();
+        let restocks = row.stock_quantity.is_some_and(|stock| stock > 0);
+        let row_txn = txn.begin().await?;
+        match import_row(&row_txn, supplier_id, user_id, row).await {
+            Ok((created, product)) => {
+                row_txn.commit().await?;
+                if created {
+                    report.created += 1;
+                } else {
+                    report.updated += 1;
+                }
+                if restocks {
+                    restocked.push(product.product_id);
+                }
+            }
+            Err(e) => {
+                row_txn.rollback().await?;
+                report.errors.push(ProductImportError {
+                    row: number,
+                    sku: Some(sku),
+                    message: e.message,
+                });
+            }
+        }
+    }
+
+    if dry_run {
+        txn.rollback().await?;
+        return Ok(report);
+    }
+    txn.commit().await?;
+
+    for product_id in restocked {
+        notify_back_in_stock(db, product_id).await?;
+    }
+    Ok(report)
+}
+
+// the supplier's products that aren't archived, in the format the import takes
+pub async fn export_products(
+    db: &DatabaseConnection,
+    supplier_id: i32,
+    format: CatalogueFormat,
+) -> Result<String, async_graphql::Error> {
+    let products = products::Entity::find()
+        .filter(products::Column::SupplierId.eq(supplier_id))
+        .order_by_asc(products::Column::ProductId)
+        .find_with_related(product_variant_options::Entity)
+        .all(db)
+        .await?;
+    let skus: HashMap<i32, Option<String>> = products
+        .iter()
+        .map(|(product, _)| (product.product_id, product.sku.clone()))
+        .collect();
+
+    let rows = products
+        .into_iter()
+        .filter(|(product, _)| product.status != PRODUCT_STATUS_ARCHIVED)
+        .map(|(product, options)| CatalogueRow {
+            sku: product.sku.unwrap_or_default(),
+            name: Some(product.name),
+            description: product.description,
+            price: Some(product.base_price),
+            currency: Some(product.currency),
+            category_id: product.category_id,
+            base_sku: product
+                .base_product_id
+                .and_then(|base_product_id| skus.get(&base_product_id).cloned().flatten()),
+            status: Some(product.status),
+            stock_quantity: Some(product.stock_quantity),
+            reorder_threshold: product.reorder_threshold,
+            weight_grams: product.weight_grams,
+            length_mm: product.length_mm,
+            width_mm: product.width_mm,
+            height_mm: product.height_mm,
+            is_gift_card: Some(product.is_gift_card),
+            media_paths: product.media_paths.map(|media_paths| media_paths.join("|")),
+            variant_options: format_variant_options(&options),
+        });
+
+    match format {
+        CatalogueFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            for row in rows {
+                writer.serialize(row)?;
+            }
+            Ok(String::from_utf8(writer.into_inner()?)?)
+        }
+        CatalogueFormat::JsonLines => Ok(rows
+            .map(|row| serde_json::to_string(&row))
+            .collect::<Result<Vec<_>, _>>()?
+            .join("\n")),
+    }
+}
@@ -1,29 +1,50 @@
 use crate::{
     entity::{
-        categories::Model as CategoriesModel, discounts::Model as DiscountsModel, products,
-        products::Entity as ProductsEntity, products::Model as ProductsModel,
+        categories::Model as CategoriesModel,
+        discounts::Model as DiscountsModel,
+        product_variant_options::{self, Model as ProductVariantOptionsModel},
+        products,
+        products::Entity as ProductsEntity,
+        products::Model as ProductsModel,
         reviews::Model as ReviewsModel,
+        stock_movements::Model as StockMovementsModel,
     },
-    models::order_und_pagination::{OrderAndPagination, OrderByColumn, OrderByOrder, PageInfo},
+    models::{
+        currency::convert,
+        order_und_pagination::{OrderAndPagination, OrderByColumn, OrderByOrder, PageInfo},
+        stock_movements::{change_stock, StockMovement},
+    },
+    money::{currency_code, Money},
 };
-use async_graphql::{InputObject, SimpleObject};
+use async_graphql::{ComplexObject, Context, InputObject, MaybeUndefined, SimpleObject};
 use sea_orm::{
-    prelude::DateTimeWithTimeZone, sea_query::error::Error, ActiveValue::Set, ColumnTrait,
-    DatabaseConnection, DatabaseTransaction, EntityTrait, QueryFilter, QueryOrder, Select,
+    prelude::{DateTimeWithTimeZone, Decimal},
+    sea_query::error::Error,
+    ActiveValue::{self, NotSet, Set},
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DatabaseTransaction, DbErr, EntityTrait,
+    QueryFilter, QueryOrder, Select,
 };
-use std::string::ToString;
 
 #[derive(SimpleObject)]
+#[graphql(complex)]
 pub struct Products {
     pub product_id: i32,
     pub name: String,
     pub description: Option<String>,
-    pub base_price: String,
+    pub base_price: Money,
     pub category_id: Option<i32>,
     pub supplier_id: Option<i32>,
     pub stock_quantity: i32,
     pub media_paths: Option<Vec<String>>,
     pub base_product_id: Option<i32>,
+    pub weight_grams: Option<i32>,
+    pub length_mm: Option<i32>,
+    pub width_mm: Option<i32>,
+    pub height_mm: Option<i32>,
+    pub is_gift_card: bool,
+    pub reorder_threshold: Option<i32>,
+    pub status: String,
+    pub sku: Option<String>,
 }
 
 impl From<ProductsModel> for Products {
@@ -32,12 +53,87 @@ impl From<ProductsModel> for Products {
             product_id: val.product_id,
             name: val.name,
             description: val.description,
-            base_price: val.base_price.to_string(),
+            base_price: Money::new(val.base_price, &val.currency),
             category_id: val.category_id,
             supplier_id: val.supplier_id,
             stock_quantity: val.stock_quantity,
             media_paths: val.media_paths,
             base_product_id: val.base_product_id,
+            weight_grams: val.weight_grams,
+            length_mm: val.length_mm,
+            width_mm: val.width_mm,
+            height_mm: val.height_mm,
+            is_gift_card: val.is_gift_card,
+            reorder_threshold: val.reorder_threshold,
+            status: val.status,
+            sku: val.sku,
+        }
+    }
+}
+
+#[ComplexObject]
+impl Products {
+    // the base price at today's exchange rate, in the product's own currency when none is asked for
+    async fn price(
+        &self,
+        ctx: &Context<'_>,
+        currency: Option<String>,
+    ) -> Result<Money, async_graphql::Error> {
+        let Some(currency) = currency else {
+            return Ok(self.base_price.clone());
+        };
+        let currency = currency_code(&currency).ok_or("Invalid currency code")?;
+        let db = ctx.data::<DatabaseConnection>()?;
+
+        let amount = convert(
+            db,
+            self.base_price.amount,
+            &self.base_price.currency,
+            &currency,
+        )
+        .await?;
+
+        Ok(Money::new(amount, &currency))
+    }
+
+    async fn variant_options(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<Vec<ProductVariantOptions>, async_graphql::Error> {
+        use crate::entity::prelude::ProductVariantOptions as ProductVariantOptionsEntity;
+        let db = ctx.data::<DatabaseConnection>()?;
+
+        let options = ProductVariantOptionsEntity::find()
+            .filter(product_variant_options::Column::ProductId.eq(self.product_id))
+            .order_by_asc(product_variant_options::Column::OptionId)
+            .all(db)
+            .await?;
+
+        Ok(options
+            .into_iter()
+            .map(|option| (option, self.base_price.currency.as_str()).into())
+            .collect())
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct ProductVariantOptions {
+    pub option_id: i32,
+    pub option_name: String,
+    pub option_value: String,
+    pub price_adjustment: Option<Money>,
+}
+
+// the price adjustment is in the currency of the product
+impl From<(ProductVariantOptionsModel, &str)> for ProductVariantOptions {
+    fn from((val, currency): (ProductVariantOptionsModel, &str)) -> ProductVariantOptions {
+        ProductVariantOptions {
+            option_id: val.option_id,
+            option_name: val.option_name,
+            option_value: val.option_value,
+            price_adjustment: val
+                .price_adjustment
+                .map(|price_adjustment| Money::new(price_adjustment, currency)),
         }
     }
 }
@@ -48,6 +144,45 @@ pub struct ProductsPaginate {
     pub page_info: PageInfo,
 }
 
+pub const PRODUCT_STATUS_DRAFT: &str = "DRAFT";
+pub const PRODUCT_STATUS_ACTIVE: &str = "ACTIVE";
+pub const PRODUCT_STATUS_ARCHIVED: &str = "ARCHIVED";
+
+// drafts are being prepared and archived products are kept for the orders that have them,
+// only active products can be put in a cart or ordered
+pub async fn check_product_on_sale<C: ConnectionTrait>(
+    conn: &C,
+    product_id: i32,
+) -> Result<ProductsModel, async_graphql::Error> {
+    Ok(products::Entity::find_by_id(product_id)
+        .one(conn)
+        .await?
+        .filter(|product| product.status == PRODUCT_STATUS_ACTIVE)
+        .ok_or("Product not available")?)
+}
+
+// suppliers publish and unpublish with these, archiving is what deleting a product does
+fn check_publish_status(status: &str) -> Result<(), async_graphql::Error> {
+    if status != PRODUCT_STATUS_DRAFT && status != PRODUCT_STATUS_ACTIVE {
+        return Err("Status must be DRAFT or ACTIVE".into());
+    }
+    Ok(())
+}
+
+pub async fn set_product_status(
+    db: &DatabaseConnection,
+    product_id: i32,
+    status: &str,
+) -> Result<ProductsModel, async_graphql::Error> {
+    Ok(products::Entity::update_many()
+        .col_expr(products::Column::Status, status.into())
+        .filter(products::Column::ProductId.eq(product_id))
+        .exec_with_returning(db)
+        .await?
+        .pop()
+        .ok_or("Product not found")?)
+}
+
 pub async fn check_product_exists(
     txn: &DatabaseTransaction,
     product_id: i32,
@@ -63,6 +198,25 @@ pub async fn check_product_exists(
     Ok(())
 }
 
+// gives back the stock movement, or None when there isn't enough stock
+pub async fn decrement_stock<C: ConnectionTrait>(
+    conn: &C,
+    product_id: i32,
+    quantity: i32,
+    movement: &StockMovement<'_>,
+) -> Result<Option<StockMovementsModel>, DbErr> {
+    change_stock(conn, product_id, -quantity, movement).await
+}
+
+pub async fn increment_stock<C: ConnectionTrait>(
+    conn: &C,
+    product_id: i32,
+    quantity: i32,
+    movement: &StockMovement<'_>,
+) -> Result<Option<StockMovementsModel>, DbErr> {
+    change_stock(conn, product_id, quantity, movement).await
+}
+
 pub async fn paginate_products(
     paginator: OrderAndPagination,
     entity: Select<ProductsEntity>,
@@ -90,28 +244,175 @@ pub async fn paginate_products(
 pub struct RegisterProduct {
     pub name: String,
     pub description: Option<String>,
-    pub base_price: String,
+    pub base_price: Money,
     pub category_id: Option<i32>,
     pub supplier_id: Option<i32>,
     pub stock_quantity: i32,
     pub media_paths: Option<Vec<String>>,
     pub base_product_id: Option<i32>,
+    pub weight_grams: Option<i32>,
+    pub length_mm: Option<i32>,
+    pub width_mm: Option<i32>,
+    pub height_mm: Option<i32>,
+    // each unit bought becomes a gift card worth the price paid for it
+    pub is_gift_card: Option<bool>,
+    // the supplier is notified when an order takes the stock below it
+    pub reorder_threshold: Option<i32>,
+    // ACTIVE when left out, a DRAFT is hidden from customers until it is published
+    pub status: Option<String>,
+    // the supplier's own code for the product, bulk imports update the product with the same one
+    pub sku: Option<String>,
 }
 
+// stock is left out, it only changes through stock movements
 pub fn create_product_model(
     input: RegisterProduct,
     supplier_id: i32,
-) -> Result<products::ActiveModel, Error> {
+) -> Result<products::ActiveModel, async_graphql::Error> {
     use crate::entity::products;
+    if input.base_price.amount < Decimal::ZERO {
+        return Err("Price cannot be negative".into());
+    }
+    if [
+        input.weight_grams,
+        input.length_mm,
+        input.width_mm,
+        input.height_mm,
+    ]
+    .iter()
+    .flatten()
+    .any(|measure| *measure < 0)
+    {
+        return Err("Weight and dimensions cannot be negative".into());
+    }
+    if input.stock_quantity < 0
+        || input
+            .reorder_threshold
+            .is_some_and(|threshold| threshold < 0)
+    {
+        return Err("Stock quantity and reorder threshold cannot be negative".into());
+    }
+    let status = input
+        .status
+        .unwrap_or_else(|| PRODUCT_STATUS_ACTIVE.to_string());
+    check_publish_status(&status)?;
     Ok(products::ActiveModel {
         name: Set(input.name.clone()),
         description: Set(input.description.clone()),
-        base_price: Set(input.base_price.parse::<f32>().unwrap().try_into().unwrap()),
+        base_price: Set(input.base_price.amount),
+        currency: Set(input.base_price.currency),
         supplier_id: Set(Some(supplier_id)),
         category_id: Set(input.category_id),
         base_product_id: Set(input.base_product_id),
         media_paths: Set(input.media_paths),
-        stock_quantity: Set(input.stock_quantity),
+        weight_grams: Set(input.weight_grams),
+        length_mm: Set(input.length_mm),
+        width_mm: Set(input.width_mm),
+        height_mm: Set(input.height_mm),
+        is_gift_card: Set(input.is_gift_card.unwrap_or(false)),
+        reorder_threshold: Set(input.reorder_threshold),
+        status: Set(status),
+        sku: Set(input.sku),
+        ..Default::default()
+    })
+}
+
+// fields left out of an update keep their value, an explicit null clears a nullable column
+#[derive(InputObject)]
+pub struct UpdateProduct {
+    pub name: Option<String>,
+    pub description: MaybeUndefined<String>,
+    pub base_price: Option<Money>,
+    pub category_id: MaybeUndefined<i32>,
+    pub stock_quantity: Option<i32>,
+    pub media_paths: MaybeUndefined<Vec<String>>,
+    pub base_product_id: MaybeUndefined<i32>,
+    pub weight_grams: MaybeUndefined<i32>,
+    pub length_mm: MaybeUndefined<i32>,
+    pub width_mm: MaybeUndefined<i32>,
+    pub height_mm: MaybeUndefined<i32>,
+    pub is_gift_card: Option<bool>,
+    pub reorder_threshold: MaybeUndefined<i32>,
+    pub status: Option<String>,
+    pub sku: MaybeUndefined<String>,
+}
+
+fn set_if_some<T>(value: Option<T>) -> ActiveValue<T>
+where
+    T: Into<sea_orm::Value>,
+{
+    match value {
+        Some(value) => Set(value),
+        None => NotSet,
+    }
+}
+
+fn set_if_defined<T>(value: MaybeUndefined<T>) -> ActiveValue<Option<T>>
+where
+    Option<T>: Into<sea_orm::Value>,
+{
+    match value {
+        MaybeUndefined::Undefined => NotSet,
+        MaybeUndefined::Null => Set(None),
+        MaybeUndefined::Value(value) => Set(Some(value)),
+    }
+}
+
+// only the columns given in the input are set, stock is left to the caller like on registration
+pub fn update_product_model(
+    input: UpdateProduct,
+) -> Result<products::ActiveModel, async_graphql::Error> {
+    if input
+        .base_price
+        .as_ref()
+        .is_some_and(|base_price| base_price.amount < Decimal::ZERO)
+    {
+        return Err("Price cannot be negative".into());
+    }
+    if [
+        &input.weight_grams,
+        &input.length_mm,
+        &input.width_mm,
+        &input.height_mm,
+    ]
+    .iter()
+    .filter_map(|measure| measure.value())
+    .any(|measure| *measure < 0)
+    {
+        return Err("Weight and dimensions cannot be negative".into());
+    }
+    if input.stock_quantity.is_some_and(|stock| stock < 0)
+        || input
+            .reorder_threshold
+            .value()
+            .is_some_and(|threshold| *threshold < 0)
+    {
+        return Err("Stock quantity and reorder threshold cannot be negative".into());
+    }
+    if let Some(status) = &input.status {
+        check_publish_status(status)?;
+    }
+
+    let (base_price, currency) = match input.base_price {
+        Some(base_price) => (Set(base_price.amount), Set(base_price.currency)),
+        None => (NotSet, NotSet),
+    };
+    Ok(products::ActiveModel {
+        name: set_if_some(input.name),
+        description: set_if_defined(input.description),
+        base_price,
+        currency,
+        category_id: set_if_defined(input.category_id),
+        base_product_id: set_if_defined(input.base_product_id),
+        media_paths: set_if_defined(input.media_paths),
+        weight_grams: set_if_defined(input.weight_grams),
+        length_mm: set_if_defined(input.length_mm),
+        width_mm: set_if_defined(input.width_mm),
+        height_mm: set_if_defined(input.height_mm),
+        is_gift_card: set_if_some(input.is_gift_card),
+        reorder_threshold: set_if_defined(input.reorder_threshold),
+        status: set_if_some(input.status),
+        sku: set_if_defined(input.sku),
         ..Default::default()
     })
 }
@@ -138,6 +439,8 @@ pub struct Categories {
     pub category_id: i32,
     pub name: String,
     pub parent_category_id: Option<i32>,
+    pub tax_class: String,
+    pub commission_rate: Option<Decimal>,
 }
 
 impl From<CategoriesModel> for Categories {
@@ -146,6 +449,8 @@ impl From<CategoriesModel> for Categories {
             category_id: val.category_id,
             name: val.name,
             parent_category_id: val.parent_category_id,
+            tax_class: val.tax_class,
+            commission_rate: val.commission_rate,
         }
     }
 }
@@ -161,7 +466,7 @@ pub struct Discounts {
     pub discount_id: i32,
     pub code: Option<String>,
     pub description: Option<String>,
-    pub discount_value: f32,
+    pub discount_value: Decimal,
     pub discount_type: String,
     pub valid_from: Option<DateTimeWithTimeZone>,
     pub valid_until: Option<DateTimeWithTimeZone>,
@@ -178,7 +483,7 @@ impl From<DiscountsModel> for Discounts {
             discount_id: val.discount_id,
             code: val.code,
             description: val.description,
-            discount_value: f32::try_from(val.discount_value).unwrap(),
+            discount_value: val.discount_value,
             discount_type: val.discount_type,
             valid_from: val.valid_from,
             valid_until: val.valid_until,
@@ -195,7 +500,7 @@ impl From<DiscountsModel> for Discounts {
 pub struct RegisterDiscount {
     pub code: Option<String>,
     pub description: Option<String>,
-    pub discount_value: i32,
+    pub discount_value: Decimal,
     pub discount_type: String,
     pub valid_from: Option<DateTimeWithTimeZone>,
     pub valid_until: Option<DateTimeWithTimeZone>,
@@ -208,12 +513,12 @@ pub struct RegisterDiscount {
 
 pub fn create_discount_model(
     input: RegisterDiscount,
-) -> Result<crate::entity::discounts::ActiveModel, Error> {
+) -> Result<crate::entity::discounts::ActiveModel, async_graphql::Error> {
     use crate::entity::discounts;
     Ok(discounts::ActiveModel {
         code: Set(input.code),
         description: Set(input.description),
-        discount_value: Set(input.discount_value.into()),
+        discount_value: Set(input.discount_value),
         discount_type: Set(input.discount_type),
         valid_from: Set(input.valid_from),
         valid_until: Set(input.valid_until),
@@ -226,6 +531,39 @@ pub fn create_discount_model(
     })
 }
 
+#[derive(InputObject)]
+pub struct UpdateDiscount {
+    pub code: MaybeUndefined<String>,
+    pub description: MaybeUndefined<String>,
+    pub discount_value: Option<Decimal>,
+    pub discount_type: Option<String>,
+    pub valid_from: MaybeUndefined<DateTimeWithTimeZone>,
+    pub valid_until: MaybeUndefined<DateTimeWithTimeZone>,
+    pub max_uses: MaybeUndefined<i32>,
+    pub product_id: Option<i32>,
+    pub category_id: MaybeUndefined<i32>,
+    pub min_quantity: MaybeUndefined<i32>,
+}
+
+pub fn update_discount_model(
+    input: UpdateDiscount,
+) -> Result<crate::entity::discounts::ActiveModel, async_graphql::Error> {
+    use crate::entity::discounts;
+    Ok(discounts::ActiveModel {
+        code: set_if_defined(input.code),
+        description: set_if_defined(input.description),
+        discount_value: set_if_some(input.discount_value),
+        discount_type: set_if_some(input.discount_type),
+        valid_from: set_if_defined(input.valid_from),
+        valid_until: set_if_defined(input.valid_until),
+        max_uses: set_if_defined(input.max_uses),
+        product_id: set_if_some(input.product_id.map(Some)),
+        category_id: set_if_defined(input.category_id),
+        min_quantity: set_if_defined(input.min_quantity),
+        ..Default::default()
+    })
+}
+
 #[derive(SimpleObject)]
 pub struct Reviews {
     pub review_id: i32,
@@ -281,3 +619,22 @@ pub fn create_review_model(
         ..Default::default()
     })
 }
+
+#[derive(InputObject)]
+pub struct UpdateReview {
+    pub rating: MaybeUndefined<i32>,
+    pub review_text: MaybeUndefined<String>,
+    pub media_paths: MaybeUndefined<Vec<String>>,
+}
+
+pub fn update_review_model(
+    input: UpdateReview,
+) -> Result<crate::entity::reviews::ActiveModel, Error> {
+    use crate::entity::reviews;
+    Ok(reviews::ActiveModel {
+        rating: set_if_defined(input.rating),
+        review_text: set_if_defined(input.review_text),
+        media_paths: set_if_defined(input.media_paths),
+        ..Default::default()
+    })
+}
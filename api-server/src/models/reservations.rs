@@ -0,0 +1,232 @@
+use crate::{
+    entity::{
+        addresses::Model as AddressesModel,
+        stock_reservations::{self, Model as StockReservationsModel},
+    },
+    models::{
+        products::{decrement_stock, increment_stock},
+        stock_movements::{
+            StockMovement, STOCK_MOVEMENT_RESERVATION, STOCK_MOVEMENT_RESERVATION_RELEASE,
+            STOCK_MOVEMENT_SALE, STOCK_REFERENCE_RESERVATION,
+        },
+    },
+};
+use async_graphql::SimpleObject;
+use chrono::{Duration, Utc};
+use sea_orm::{
+    prelude::DateTimeWithTimeZone, ActiveModelTrait, ActiveValue::Set, ColumnTrait,
+    DatabaseConnection, DatabaseTransaction, DbErr, EntityTrait, QueryFilter, TransactionTrait,
+};
+
+pub const RESERVATION_STATUS_ACTIVE: &str = "ACTIVE";
+pub const RESERVATION_STATUS_CONSUMED: &str = "CONSUMED";
+pub const RESERVATION_STATUS_RELEASED: &str = "RELEASED";
+
+// how long stock stays held for a customer during checkout
+pub const RESERVATION_MINUTES: i64 = 15;
+
+#[derive(SimpleObject)]
+pub struct StockReservations {
+    pub reservation_id: i32,
+    pub product_id: i32,
+    pub quantity: i32,
+    pub status: String,
+    pub expires_at: DateTimeWithTimeZone,
+}
+
+impl From<StockReservationsModel> for StockReservations {
+    fn from(val: StockReservationsModel) -> StockReservations {
+        StockReservations {
+            reservation_id: val.reservation_id,
+            product_id: val.product_id,
+            quantity: val.quantity,
+            status: val.status,
+            expires_at: val.expires_at,
+        }
+    }
+}
+
+// only the caller that flips the status gets to touch the stock, so a reservation
+// can't be released twice or released and consumed at the same time
+async fn claim_reservation(
+    txn: &DatabaseTransaction,
+    reservation_id: i32,
+    status: &str,
+) -> Result<bool, DbErr> {
+    let result = stock_reservations::Entity::update_many()
+        .col_expr(stock_reservations::Column::Status, status.into())
+        .filter(stock_reservations::Column::ReservationId.eq(reservation_id))
+        .filter(stock_reservations::Column::Status.eq(RESERVATION_STATUS_ACTIVE))
+        .exec(txn)
+        .await?;
+    Ok(result.rows_affected == 1)
+}
+
+pub async fn release_reservation(
+    txn: &DatabaseTransaction,
+    reservation: &StockReservationsModel,
+    actor_user_id: Option<i32>,
+) -> Result<(), DbErr> {
+    if claim_reservation(txn, reservation.reservation_id, RESERVATION_STATUS_RELEASED).await? {
+        increment_stock(
+            txn,
+            reservation.product_id,
+            reservation.quantity,
+            &StockMovement::new(STOCK_MOVEMENT_RESERVATION_RELEASE)
+                .reference(STOCK_REFERENCE_RESERVATION, reservation.reservation_id)
+                .actor(actor_user_id)
+                .warehouse(reservation.warehouse_id),
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+pub async fn reserve_stock(
+    txn: &DatabaseTransaction,
+    customer_id: i32,
+    product_id: i32,
+    quantity: i32,
+    actor_user_id: Option<i32>,
+) -> Result<StockReservationsModel, async_graphql::Error> {
+    if quantity <= 0 {
+        return Err("Quantity must be positive".into());
+    }
+
+    // reserving again replaces whatever the customer was already holding for the product
+    let active_reservations = stock_reservations::Entity::find()
+        .filter(stock_reservations::Column::CustomerId.eq(customer_id))
+        .filter(stock_reservations::Column::ProductId.eq(product_id))
+        .filter(stock_reservations::Column::Status.eq(RESERVATION_STATUS_ACTIVE))
+        .all(txn)
+        .await?;
+
+    for reservation in &active_reservations {
+        release_reservation(txn, reservation, actor_user_id).await?;
+    }
+
+    let reservation = stock_reservations::ActiveModel {
+        customer_id: Set(customer_id),
+        product_id: Set(product_id),
+        quantity: Set(quantity),
+        status: Set(RESERVATION_STATUS_ACTIVE.to_string()),
+        expires_at: Set((Utc::now() + Duration::minutes(RESERVATION_MINUTES)).fixed_offset()),
+        ..Default::default()
+    };
+
+    let reservation = stock_reservations::Entity::insert(reservation)
+        .exec_with_returning(txn)
+        .await?;
+
+    // the caller drops the transaction on an error, so the reservation goes with it
+    let stock_movement = decrement_stock(
+        txn,
+        product_id,
+        quantity,
+        &StockMovement::new(STOCK_MOVEMENT_RESERVATION)
+            .reference(STOCK_REFERENCE_RESERVATION, reservation.reservation_id)
+            .actor(actor_user_id),
+    )
+    .await?
+    .ok_or("Insufficient stock")?;
+
+    if stock_movement.warehouse_id.is_none() {
+        return Ok(reservation);
+    }
+
+    // the stock is held in the warehouse it was taken from, releasing it puts it back there
+    let mut reservation: stock_reservations::ActiveModel = reservation.into();
+    reservation.warehouse_id = Set(stock_movement.warehouse_id);
+    Ok(reservation.update(txn).await?)
+}
+
+pub struct TakenStock {
+    pub reservation_id: Option<i32>,
+    // where the line is sent from, for suppliers with warehouses
+    pub warehouse_id: Option<i32>,
+    pub stock_movement_ids: Vec<i32>,
+}
+
+// takes the stock for an order line, using the customer's reservation when there is one. A line
+// is sent from a single warehouse, the one the reservation holds the stock in or the closest to
+// the shipping address that has all of it.
+pub async fn take_stock(
+    txn: &DatabaseTransaction,
+    customer_id: i32,
+    product_id: i32,
+    quantity: i32,
+    actor_user_id: Option<i32>,
+    ship_to: &AddressesModel,
+) -> Result<TakenStock, async_graphql::Error> {
+    let movement = StockMovement::new(STOCK_MOVEMENT_SALE)
+        .actor(actor_user_id)
+        .ship_to(ship_to);
+
+    let reservation = stock_reservations::Entity::find()
+        .filter(stock_reservations::Column::CustomerId.eq(customer_id))
+        .filter(stock_reservations::Column::ProductId.eq(product_id))
+        .filter(stock_reservations::Column::Status.eq(RESERVATION_STATUS_ACTIVE))
+        .filter(stock_reservations::Column::ExpiresAt.gt(Utc::now().fixed_offset()))
+        .one(txn)
+        .await?;
+
+    if let Some(reservation) = reservation {
+        if claim_reservation(txn, reservation.reservation_id, RESERVATION_STATUS_CONSUMED).await? {
+            let movement = movement.warehouse(reservation.warehouse_id);
+            let stock_movement = if reservation.quantity > quantity {
+                // what was held but isn't ordered goes back on the shelf
+                increment_stock(
+                    txn,
+                    product_id,
+                    reservation.quantity - quantity,
+                    &StockMovement::new(STOCK_MOVEMENT_RESERVATION_RELEASE)
+                        .actor(actor_user_id)
+                        .warehouse(reservation.warehouse_id),
+                )
+                .await?
+            } else if reservation.quantity < quantity {
+                Some(
+                    decrement_stock(txn, product_id, quantity - reservation.quantity, &movement)
+                        .await?
+                        .ok_or("Insufficient stock")?,
+                )
+            } else {
+                None
+            };
+            return Ok(TakenStock {
+                reservation_id: Some(reservation.reservation_id),
+                warehouse_id: reservation.warehouse_id,
+                stock_movement_ids: stock_movement
+                    .map(|stock_movement| stock_movement.stock_movement_id)
+                    .into_iter()
+                    .collect(),
+            });
+        }
+    }
+
+    let stock_movement = decrement_stock(txn, product_id, quantity, &movement)
+        .await?
+        .ok_or("Insufficient stock")?;
+
+    Ok(TakenStock {
+        reservation_id: None,
+        warehouse_id: stock_movement.warehouse_id,
+        stock_movement_ids: vec![stock_movement.stock_movement_id],
+    })
+}
+
+pub async fn release_expired_reservations(db: &DatabaseConnection) -> Result<(), DbErr> {
+    let expired_reservations = stock_reservations::Entity::find()
+        .filter(stock_reservations::Column::Status.eq(RESERVATION_STATUS_ACTIVE))
+        .filter(stock_reservations::Column::ExpiresAt.lte(Utc::now().fixed_offset()))
+        .all(db)
+        .await?;
+
+    for reservation in &expired_reservations {
+        let txn = db.begin().await?;
+        release_reservation(&txn, reservation, None).await?;
+        txn.commit().await?;
+    }
+
+    Ok(())
+}
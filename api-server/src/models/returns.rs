@@ -0,0 +1,134 @@
+use crate::{
+    entity::{
+        order_items::Model as OrderItemsModel,
+        refunds::Model as RefundsModel,
+        return_items::Model as ReturnItemsModel,
+        return_requests::{self, Model as ReturnRequestsModel},
+    },
+    money::{prorate, Money},
+};
+use async_graphql::{InputObject, SimpleObject};
+use sea_orm::{
+    prelude::{DateTimeWithTimeZone, Decimal},
+    DatabaseTransaction, EntityTrait,
+};
+
+pub const RETURN_STATUS_REQUESTED: &str = "REQUESTED";
+pub const RETURN_STATUS_APPROVED: &str = "APPROVED";
+pub const RETURN_STATUS_REJECTED: &str = "REJECTED";
+pub const RETURN_STATUS_RECEIVED: &str = "RECEIVED";
+
+pub const REFUND_STATUS_PENDING: &str = "PENDING";
+pub const REFUND_STATUS_COMPLETED: &str = "COMPLETED";
+
+#[derive(SimpleObject)]
+pub struct ReturnRequests {
+    pub return_id: i32,
+    pub order_id: i32,
+    pub customer_id: i32,
+    pub supplier_id: Option<i32>,
+    pub status: String,
+    pub rejection_reason: Option<String>,
+    pub requested_at: Option<DateTimeWithTimeZone>,
+    pub decided_at: Option<DateTimeWithTimeZone>,
+    pub received_at: Option<DateTimeWithTimeZone>,
+}
+
+impl From<ReturnRequestsModel> for ReturnRequests {
+    fn from(val: ReturnRequestsModel) -> ReturnRequests {
+        ReturnRequests {
+            return_id: val.return_id,
+            order_id: val.order_id,
+            customer_id: val.customer_id,
+            supplier_id: val.supplier_id,
+            status: val.status,
+            rejection_reason: val.rejection_reason,
+            requested_at: val.requested_at,
+            decided_at: val.decided_at,
+            received_at: val.received_at,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct ReturnItems {
+    pub return_item_id: i32,
+    pub return_id: i32,
+    pub order_item_id: i32,
+    pub quantity: i32,
+    pub reason: String,
+}
+
+impl From<ReturnItemsModel> for ReturnItems {
+    fn from(val: ReturnItemsModel) -> ReturnItems {
+        ReturnItems {
+            return_item_id: val.return_item_id,
+            return_id: val.return_id,
+            order_item_id: val.order_item_id,
+            quantity: val.quantity,
+            reason: val.reason,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct Refunds {
+    pub refund_id: i32,
+    pub return_id: Option<i32>,
+    pub bill_id: i32,
+    pub payment_method_id: i32,
+    pub amount: Money,
+    pub status: String,
+    pub created_at: Option<DateTimeWithTimeZone>,
+}
+
+impl From<RefundsModel> for Refunds {
+    fn from(val: RefundsModel) -> Refunds {
+        Refunds {
+            refund_id: val.refund_id,
+            return_id: val.return_id,
+            bill_id: val.bill_id,
+            payment_method_id: val.payment_method_id,
+            amount: Money::new(val.amount, &val.currency),
+            status: val.status,
+            created_at: val.created_at,
+        }
+    }
+}
+
+#[derive(InputObject)]
+pub struct RegisterReturn {
+    pub order_id: i32,
+    pub items: Vec<RegisterReturnItem>,
+}
+
+#[derive(InputObject)]
+pub struct RegisterReturnItem {
+    pub order_item_id: i32,
+    pub quantity: i32,
+    pub reason: String,
+}
+
+// the line's share of the order discount and its tax are spread evenly over the units that were bought
+pub fn refund_amount(order_item: &OrderItemsModel, quantity: i32) -> Decimal {
+    order_item.unit_price * Decimal::from(quantity)
+        - prorate(order_item.discount_amount, quantity, order_item.quantity)
+        + prorate(order_item.tax_amount, quantity, order_item.quantity)
+}
+
+pub async fn find_supplier_return(
+    txn: &DatabaseTransaction,
+    supplier_id: i32,
+    return_id: i32,
+) -> Result<ReturnRequestsModel, async_graphql::Error> {
+    let return_request = return_requests::Entity::find_by_id(return_id)
+        .one(txn)
+        .await?
+        .ok_or("Return request not found")?;
+
+    if return_request.supplier_id != Some(supplier_id) {
+        return Err("Unauthorized".into());
+    }
+
+    Ok(return_request)
+}
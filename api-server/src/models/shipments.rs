@@ -0,0 +1,98 @@
+use crate::entity::{
+    shipment_events::Model as ShipmentEventsModel, shipments::Model as ShipmentsModel,
+};
+use async_graphql::{InputObject, SimpleObject};
+use sea_orm::prelude::DateTimeWithTimeZone;
+use serde::Deserialize;
+
+pub const SHIPMENT_STATUS_LABEL_CREATED: &str = "LABEL_CREATED";
+pub const SHIPMENT_STATUS_IN_TRANSIT: &str = "IN_TRANSIT";
+pub const SHIPMENT_STATUS_OUT_FOR_DELIVERY: &str = "OUT_FOR_DELIVERY";
+pub const SHIPMENT_STATUS_DELIVERED: &str = "DELIVERED";
+pub const SHIPMENT_STATUS_EXCEPTION: &str = "EXCEPTION";
+
+pub fn check_shipment_status(status: &str) -> Result<(), &'static str> {
+    match status {
+        SHIPMENT_STATUS_LABEL_CREATED
+        | SHIPMENT_STATUS_IN_TRANSIT
+        | SHIPMENT_STATUS_OUT_FOR_DELIVERY
+        | SHIPMENT_STATUS_DELIVERED
+        | SHIPMENT_STATUS_EXCEPTION => Ok(()),
+        _ => Err("Invalid shipment status"),
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct Shipments {
+    pub shipment_id: i32,
+    pub order_id: i32,
+    pub supplier_id: Option<i32>,
+    pub carrier: String,
+    pub tracking_number: String,
+    pub status: String,
+    pub shipped_at: Option<DateTimeWithTimeZone>,
+    pub delivered_at: Option<DateTimeWithTimeZone>,
+}
+
+impl From<ShipmentsModel> for Shipments {
+    fn from(val: ShipmentsModel) -> Shipments {
+        Shipments {
+            shipment_id: val.shipment_id,
+            order_id: val.order_id,
+            supplier_id: val.supplier_id,
+            carrier: val.carrier,
+            tracking_number: val.tracking_number,
+            status: val.status,
+            shipped_at: val.shipped_at,
+            delivered_at: val.delivered_at,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct ShipmentEvents {
+    pub shipment_event_id: i32,
+    pub shipment_id: i32,
+    pub status: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub occurred_at: DateTimeWithTimeZone,
+}
+
+impl From<ShipmentEventsModel> for ShipmentEvents {
+    fn from(val: ShipmentEventsModel) -> ShipmentEvents {
+        ShipmentEvents {
+            shipment_event_id: val.shipment_event_id,
+            shipment_id: val.shipment_id,
+            status: val.status,
+            description: val.description,
+            location: val.location,
+            occurred_at: val.occurred_at,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct ShipmentTracking {
+    pub shipment: Shipments,
+    pub events: Vec<ShipmentEvents>,
+}
+
+#[derive(InputObject)]
+pub struct RegisterShipment {
+    pub order_id: i32,
+    pub carrier: String,
+    pub tracking_number: String,
+}
+
+// payload pushed by carriers (or the local carrier simulator) to the webhook endpoint
+#[derive(Deserialize)]
+pub struct CarrierEvent {
+    pub event_id: Option<String>,
+    pub carrier: String,
+    pub tracking_number: String,
+    pub status: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub occurred_at: DateTimeWithTimeZone,
+}
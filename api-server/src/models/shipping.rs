@@ -0,0 +1,241 @@
+use crate::{
+    entity::{
+        addresses::Model as AddressesModel,
+        products::Model as ProductsModel,
+        shipping_profiles::{self, Model as ShippingProfilesModel},
+    },
+    models::currency::convert,
+    money::{round, Money},
+};
+use async_graphql::{InputObject, SimpleObject};
+use sea_orm::{
+    prelude::Decimal, ActiveValue::Set, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter,
+    QueryOrder,
+};
+
+pub const SHIPPING_RATE_FLAT: &str = "FLAT";
+pub const SHIPPING_RATE_WEIGHT: &str = "WEIGHT";
+
+// carriers bill bulky parcels by size, a cubic centimetre counts as 1/5000 kg (0.2 g per cm³)
+pub const VOLUMETRIC_DIVISOR: i64 = 5000;
+
+#[derive(SimpleObject)]
+pub struct ShippingProfiles {
+    pub shipping_profile_id: i32,
+    pub supplier_id: i32,
+    pub name: String,
+    pub rate_type: String,
+    pub base_rate: Money,
+    pub per_kg_rate: Money,
+    pub free_over: Option<Money>,
+    pub country: Option<String>,
+    pub postal_prefix: Option<String>,
+    pub estimated_days: Option<i32>,
+}
+
+impl From<ShippingProfilesModel> for ShippingProfiles {
+    fn from(val: ShippingProfilesModel) -> ShippingProfiles {
+        ShippingProfiles {
+            shipping_profile_id: val.shipping_profile_id,
+            supplier_id: val.supplier_id,
+            name: val.name,
+            rate_type: val.rate_type,
+            base_rate: Money::new(val.base_rate, &val.currency),
+            per_kg_rate: Money::new(val.per_kg_rate, &val.currency),
+            free_over: val
+                .free_over
+                .map(|free_over| Money::new(free_over, &val.currency)),
+            country: val.country,
+            postal_prefix: val.postal_prefix,
+            estimated_days: val.estimated_days,
+        }
+    }
+}
+
+// leaving country and postal prefix out makes the profile apply everywhere
+#[derive(InputObject)]
+pub struct RegisterShippingProfile {
+    pub name: String,
+    pub rate_type: String,
+    pub base_rate: Money,
+    pub per_kg_rate: Option<Money>,
+    pub free_over: Option<Money>,
+    pub country: Option<String>,
+    pub postal_prefix: Option<String>,
+    pub estimated_days: Option<i32>,
+}
+
+pub fn create_shipping_profile_model(
+    input: RegisterShippingProfile,
+    supplier_id: i32,
+) -> Result<shipping_profiles::ActiveModel, async_graphql::Error> {
+    if input.rate_type != SHIPPING_RATE_FLAT && input.rate_type != SHIPPING_RATE_WEIGHT {
+        return Err("Rate type must be FLAT or WEIGHT".into());
+    }
+
+    let currency = input.base_rate.currency.clone();
+    let per_kg_rate = input
+        .per_kg_rate
+        .unwrap_or_else(|| Money::new(Decimal::ZERO, &currency));
+    if per_kg_rate.currency != currency
+        || input
+            .free_over
+            .as_ref()
+            .is_some_and(|free_over| free_over.currency != currency)
+    {
+        return Err("All rates of a shipping profile must be in the same currency".into());
+    }
+
+    if input.base_rate.amount < Decimal::ZERO || per_kg_rate.amount < Decimal::ZERO {
+        return Err("Shipping rates cannot be negative".into());
+    }
+
+    if input.country.as_ref().is_some_and(|country| {
+        country.len() != 3 || !country.chars().all(|c| c.is_ascii_alphabetic())
+    }) {
+        return Err("Country must be a three letter ISO 3166 code".into());
+    }
+
+    Ok(shipping_profiles::ActiveModel {
+        supplier_id: Set(supplier_id),
+        name: Set(input.name),
+        rate_type: Set(input.rate_type),
+        base_rate: Set(input.base_rate.amount),
+        per_kg_rate: Set(per_kg_rate.amount),
+        free_over: Set(input.free_over.map(|free_over| free_over.amount)),
+        currency: Set(currency),
+        country: Set(input.country.map(|country| country.to_uppercase())),
+        postal_prefix: Set(input.postal_prefix),
+        estimated_days: Set(input.estimated_days),
+        ..Default::default()
+    })
+}
+
+#[derive(SimpleObject)]
+pub struct DeliveryOptions {
+    pub shipping_profile_id: i32,
+    pub supplier_id: i32,
+    pub name: String,
+    pub cost: Money,
+    pub estimated_days: Option<i32>,
+}
+
+// the heavier of the actual and the volumetric weight
+pub fn billable_weight_grams(product: &ProductsModel) -> i64 {
+    let weight = i64::from(product.weight_grams.unwrap_or(0));
+    let volumetric = match (product.length_mm, product.width_mm, product.height_mm) {
+        (Some(length), Some(width), Some(height)) => {
+            i64::from(length) * i64::from(width) * i64::from(height) / VOLUMETRIC_DIVISOR
+        }
+        _ => 0,
+    };
+    weight.max(volumetric)
+}
+
+pub fn shipping_cost(
+    profile: &ShippingProfilesModel,
+    subtotal: Decimal,
+    weight_grams: i64,
+) -> Decimal {
+    if profile
+        .free_over
+        .is_some_and(|free_over| subtotal >= free_over)
+    {
+        return Decimal::ZERO;
+    }
+
+    if profile.rate_type == SHIPPING_RATE_WEIGHT {
+        round(profile.base_rate + profile.per_kg_rate * Decimal::new(weight_grams, 3))
+    } else {
+        profile.base_rate
+    }
+}
+
+fn zone_matches(profile: &ShippingProfilesModel, address: &AddressesModel) -> bool {
+    profile
+        .country
+        .as_ref()
+        .is_none_or(|country| country.trim() == address.country.trim())
+        && profile
+            .postal_prefix
+            .as_ref()
+            .is_none_or(|prefix| address.postal_code.trim().starts_with(prefix.trim()))
+}
+
+// a zone with a country and a longer postal prefix is more specific than a catch-all one
+fn zone_specificity(profile: &ShippingProfilesModel) -> usize {
+    profile.country.as_ref().map_or(0, |_| 1)
+        + profile
+            .postal_prefix
+            .as_ref()
+            .map_or(0, |prefix| prefix.trim().len() + 1)
+}
+
+// the options a supplier offers for the address, with the cost of shipping their part of the order
+// in the profile's currency; for profiles with the same name only the most specific zone is kept
+pub async fn supplier_delivery_options<C: ConnectionTrait>(
+    conn: &C,
+    supplier_id: i32,
+    address: &AddressesModel,
+    lines: &[(ProductsModel, i32)],
+) -> Result<Vec<(ShippingProfilesModel, Decimal)>, async_graphql::Error> {
+    let profiles = shipping_profiles::Entity::find()
+        .filter(shipping_profiles::Column::SupplierId.eq(supplier_id))
+        .order_by_asc(shipping_profiles::Column::ShippingProfileId)
+        .all(conn)
+        .await?;
+
+    let mut matching: Vec<ShippingProfilesModel> = Vec::new();
+    for profile in profiles
+        .into_iter()
+        .filter(|profile| zone_matches(profile, address))
+    {
+        match matching.iter_mut().find(|other| other.name == profile.name) {
+            Some(other) if zone_specificity(&profile) > zone_specificity(other) => *other = profile,
+            Some(_) => {}
+            None => matching.push(profile),
+        }
+    }
+
+    let weight_grams: i64 = lines
+        .iter()
+        .map(|(product, quantity)| billable_weight_grams(product) * i64::from(*quantity))
+        .sum();
+
+    let mut options = Vec::new();
+    for profile in matching {
+        let mut subtotal = Decimal::ZERO;
+        for (product, quantity) in lines {
+            subtotal += convert(
+                conn,
+                product.base_price,
+                &product.currency,
+                &profile.currency,
+            )
+            .await?
+                * Decimal::from(*quantity);
+        }
+
+        let cost = shipping_cost(&profile, subtotal, weight_grams);
+        options.push((profile, cost));
+    }
+
+    Ok(options)
+}
+
+// groups order or cart lines by the supplier that ships them
+pub fn lines_by_supplier(
+    lines: Vec<(ProductsModel, i32)>,
+) -> Vec<(i32, Vec<(ProductsModel, i32)>)> {
+    let mut suppliers: Vec<(i32, Vec<(ProductsModel, i32)>)> = Vec::new();
+    for (product, quantity) in lines {
+        let Some(supplier_id) = product.supplier_id else {
+            continue;
+        };
+        match suppliers.iter_mut().find(|(id, _)| *id == supplier_id) {
+            Some((_, supplier_lines)) => supplier_lines.push((product, quantity)),
+            None => suppliers.push((supplier_id, vec![(product, quantity)])),
+        }
+    }
+    suppliers
+}
@@ -0,0 +1,139 @@
+use crate::{
+    entity::{
+        customers, products,
+        stock_subscriptions::{self, Model as StockSubscriptionsModel},
+        supplier_notifications::{self, Model as SupplierNotificationsModel},
+        users,
+    },
+    mailer::send_mail_in_background,
+};
+use async_graphql::SimpleObject;
+use sea_orm::{
+    prelude::DateTimeWithTimeZone, ActiveValue::Set, ColumnTrait, ConnectionTrait,
+    DatabaseConnection, DbErr, EntityTrait, QueryFilter,
+};
+
+pub const NOTIFICATION_LOW_STOCK: &str = "LOW_STOCK";
+
+#[derive(SimpleObject)]
+pub struct SupplierNotifications {
+    pub notification_id: i32,
+    pub product_id: Option<i32>,
+    pub notification_type: String,
+    pub message: String,
+    pub is_read: bool,
+    pub created_at: Option<DateTimeWithTimeZone>,
+}
+
+impl From<SupplierNotificationsModel> for SupplierNotifications {
+    fn from(val: SupplierNotificationsModel) -> SupplierNotifications {
+        SupplierNotifications {
+            notification_id: val.notification_id,
+            product_id: val.product_id,
+            notification_type: val.notification_type,
+            message: val.message,
+            is_read: val.is_read,
+            created_at: val.created_at,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct StockSubscriptions {
+    pub subscription_id: i32,
+    pub product_id: i32,
+    pub created_at: Option<DateTimeWithTimeZone>,
+}
+
+impl From<StockSubscriptionsModel> for StockSubscriptions {
+    fn from(val: StockSubscriptionsModel) -> StockSubscriptions {
+        StockSubscriptions {
+            subscription_id: val.subscription_id,
+            product_id: val.product_id,
+            created_at: val.created_at,
+        }
+    }
+}
+
+// only the movement that takes the stock below the threshold notifies, not every order after it
+pub async fn notify_low_stock<C: ConnectionTrait>(
+    conn: &C,
+    product: &products::Model,
+    quantity: i32,
+) -> Result<(), DbErr> {
+    let (Some(threshold), Some(supplier_id)) = (product.reorder_threshold, product.supplier_id)
+    else {
+        return Ok(());
+    };
+    let stock_before = product.stock_quantity - quantity;
+    if quantity >= 0 || stock_before < threshold || product.stock_quantity >= threshold {
+        return Ok(());
+    }
+
+    let notification = supplier_notifications::ActiveModel {
+        supplier_id: Set(supplier_id),
+        product_id: Set(Some(product.product_id)),
+        notification_type: Set(NOTIFICATION_LOW_STOCK.to_string()),
+        message: Set(format!(
+            "{} is down to {} in stock, below its reorder threshold of {}",
+            product.name, product.stock_quantity, threshold
+        )),
+        ..Default::default()
+    };
+    supplier_notifications::Entity::insert(notification)
+        .exec(conn)
+        .await?;
+
+    Ok(())
+}
+
+// called once a restock is committed, every subscriber gets one mail and the subscription is done
+pub async fn notify_back_in_stock(db: &DatabaseConnection, product_id: i32) -> Result<(), DbErr> {
+    let Some(product) = products::Entity::find_by_id(product_id)
+        .one(db)
+        .await?
+        .filter(|product| product.stock_quantity > 0)
+    else {
+        return Ok(());
+    };
+
+    let subscriptions = stock_subscriptions::Entity::find()
+        .filter(stock_subscriptions::Column::ProductId.eq(product_id))
+        .find_also_related(customers::Entity)
+        .all(db)
+        .await?;
+    if subscriptions.is_empty() {
+        return Ok(());
+    }
+
+    stock_subscriptions::Entity::delete_many()
+        .filter(
+            stock_subscriptions::Column::SubscriptionId.is_in(
+                subscriptions
+                    .iter()
+                    .map(|(subscription, _)| subscription.subscription_id),
+            ),
+        )
+        .exec(db)
+        .await?;
+
+    for (_, customer) in subscriptions {
+        let Some(customer) = customer else {
+            continue;
+        };
+        let Some(user) = users::Entity::find_by_id(customer.user_id).one(db).await? else {
+            continue;
+        };
+
+        send_mail_in_background(
+            user.email,
+            format!("{} is back in stock", product.name),
+            format!(
+                "<p>Hi {},</p><p>{} is available again, get yours before it sells out.</p>",
+                customer.first_name, product.name
+            ),
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,314 @@
+use crate::{
+    entity::{
+        addresses::Model as AddressesModel,
+        products,
+        stock_movements::{self, Model as StockMovementsModel},
+    },
+    models::{
+        stock_alerts::notify_low_stock,
+        warehouses::{allocate_warehouse, change_warehouse_stock, product_warehouses},
+    },
+};
+use async_graphql::SimpleObject;
+use sea_orm::{
+    prelude::{DateTimeWithTimeZone, Expr},
+    ActiveValue::Set,
+    ColumnTrait, ConnectionTrait, DatabaseTransaction, DbErr, EntityTrait, QueryFilter,
+};
+
+pub const STOCK_MOVEMENT_SALE: &str = "SALE";
+pub const STOCK_MOVEMENT_CANCELLATION: &str = "CANCELLATION";
+pub const STOCK_MOVEMENT_RETURN: &str = "RETURN";
+pub const STOCK_MOVEMENT_RESERVATION: &str = "RESERVATION";
+pub const STOCK_MOVEMENT_RESERVATION_RELEASE: &str = "RESERVATION_RELEASE";
+pub const STOCK_MOVEMENT_ADJUSTMENT: &str = "ADJUSTMENT";
+pub const STOCK_MOVEMENT_TRANSFER: &str = "TRANSFER";
+pub const STOCK_MOVEMENT_IMPORT: &str = "IMPORT";
+
+pub const STOCK_REFERENCE_ORDER: &str = "ORDER";
+pub const STOCK_REFERENCE_RETURN: &str = "RETURN";
+pub const STOCK_REFERENCE_RESERVATION: &str = "RESERVATION";
+pub const STOCK_REFERENCE_WAREHOUSE: &str = "WAREHOUSE";
+
+#[derive(SimpleObject)]
+pub struct StockMovements {
+    pub stock_movement_id: i32,
+    pub product_id: i32,
+    pub quantity: i32,
+    pub stock_after: i32,
+    pub movement_type: String,
+    pub reference_type: Option<String>,
+    pub reference_id: Option<i32>,
+    pub actor_user_id: Option<i32>,
+    pub note: Option<String>,
+    pub created_at: Option<DateTimeWithTimeZone>,
+    pub warehouse_id: Option<i32>,
+}
+
+impl From<StockMovementsModel> for StockMovements {
+    fn from(val: StockMovementsModel) -> StockMovements {
+        StockMovements {
+            stock_movement_id: val.stock_movement_id,
+            product_id: val.product_id,
+            quantity: val.quantity,
+            stock_after: val.stock_after,
+            movement_type: val.movement_type,
+            reference_type: val.reference_type,
+            reference_id: val.reference_id,
+            actor_user_id: val.actor_user_id,
+            note: val.note,
+            created_at: val.created_at,
+            warehouse_id: val.warehouse_id,
+        }
+    }
+}
+
+// why the stock changed, who changed it and what it belongs to. Without a warehouse one is picked
+// for suppliers that have them, close to `ship_to` when stock is taken for a delivery.
+pub struct StockMovement<'a> {
+    pub movement_type: &'static str,
+    pub reference_type: Option<&'static str>,
+    pub reference_id: Option<i32>,
+    pub actor_user_id: Option<i32>,
+    pub note: Option<String>,
+    pub warehouse_id: Option<i32>,
+    pub ship_to: Option<&'a AddressesModel>,
+}
+
+impl<'a> StockMovement<'a> {
+    pub fn new(movement_type: &'static str) -> Self {
+        Self {
+            movement_type,
+            reference_type: None,
+            reference_id: None,
+            actor_user_id: None,
+            note: None,
+            warehouse_id: None,
+            ship_to: None,
+        }
+    }
+
+    pub fn reference(mut self, reference_type: &'static str, reference_id: i32) -> Self {
+        self.reference_type = Some(reference_type);
+        self.reference_id = Some(reference_id);
+        self
+    }
+
+    pub fn actor(mut self, actor_user_id: Option<i32>) -> Self {
+        self.actor_user_id = actor_user_id;
+        self
+    }
+
+    pub fn note(mut self, note: Option<String>) -> Self {
+        self.note = note;
+        self
+    }
+
+    pub fn warehouse(mut self, warehouse_id: Option<i32>) -> Self {
+        self.warehouse_id = warehouse_id;
+        self
+    }
+
+    pub fn ship_to(mut self, address: &'a AddressesModel) -> Self {
+        self.ship_to = Some(address);
+        self
+    }
+}
+
+async fn insert_stock_movement<C: ConnectionTrait>(
+    conn: &C,
+    product_id: i32,
+    quantity: i32,
+    stock_after: i32,
+    warehouse_id: Option<i32>,
+    movement: &StockMovement<'_>,
+) -> Result<StockMovementsModel, DbErr> {
+    let stock_movement = stock_movements::ActiveModel {
+        product_id: Set(product_id),
+        quantity: Set(quantity),
+        stock_after: Set(stock_after),
+        movement_type: Set(movement.movement_type.to_string()),
+        reference_type: Set(movement
+            .reference_type
+            .map(|reference_type| reference_type.to_string())),
+        reference_id: Set(movement.reference_id),
+        actor_user_id: Set(movement.actor_user_id),
+        note: Set(movement.note.clone()),
+        warehouse_id: Set(warehouse_id),
+        ..Default::default()
+    };
+
+    stock_movements::Entity::insert(stock_movement)
+        .exec_with_returning(conn)
+        .await
+}
+
+// the only place `stock_quantity` changes, every change is written as a movement with the stock
+// it left behind. Taking stock is a conditional update, so concurrent orders can never take it below
+// zero, and gives back None when there isn't enough. For suppliers with warehouses the warehouse
+// changes by the same quantity, keeping `stock_quantity` their total.
+pub async fn change_stock<C: ConnectionTrait>(
+    conn: &C,
+    product_id: i32,
+    quantity: i32,
+    movement: &StockMovement<'_>,
+) -> Result<Option<StockMovementsModel>, DbErr> {
+    let warehouse_id = match movement.warehouse_id {
+        Some(warehouse_id) => Some(warehouse_id),
+        None => {
+            let warehouses = product_warehouses(conn, product_id).await?;
+            if warehouses.is_empty() {
+                None
+            } else if quantity < 0 {
+                let warehouse_id =
+                    allocate_warehouse(conn, product_id, -quantity, &warehouses, movement.ship_to)
+                        .await?;
+                if warehouse_id.is_none() {
+                    return Ok(None);
+                }
+                warehouse_id
+            } else {
+                warehouses.first().map(|warehouse| warehouse.warehouse_id)
+            }
+        }
+    };
+
+    if let Some(warehouse_id) = warehouse_id {
+        if !change_warehouse_stock(conn, warehouse_id, product_id, quantity).await? {
+            return Ok(None);
+        }
+    }
+
+    let mut update = products::Entity::update_many()
+        .col_expr(
+            products::Column::StockQuantity,
+            Expr::col(products::Column::StockQuantity).add(quantity),
+        )
+        .filter(products::Column::ProductId.eq(product_id));
+    if quantity < 0 {
+        update = update.filter(products::Column::StockQuantity.gte(-quantity));
+    }
+
+    let Some(product) = update.exec_with_returning(conn).await?.pop() else {
+        return Ok(None);
+    };
+
+    if matches!(
+        movement.movement_type,
+        STOCK_MOVEMENT_SALE | STOCK_MOVEMENT_RESERVATION
+    ) {
+        notify_low_stock(conn, &product, quantity).await?;
+    }
+
+    Ok(Some(
+        insert_stock_movement(
+            conn,
+            product_id,
+            quantity,
+            product.stock_quantity,
+            warehouse_id,
+            movement,
+        )
+        .await?,
+    ))
+}
+
+// moves stock between two warehouses of the same supplier, the total stays the same so both
+// movements leave `stock_quantity` as it was
+pub async fn transfer_stock(
+    txn: &DatabaseTransaction,
+    product: &products::Model,
+    from_warehouse_id: i32,
+    to_warehouse_id: i32,
+    quantity: i32,
+    actor_user_id: i32,
+    note: Option<String>,
+) -> Result<(), async_graphql::Error> {
+    if quantity <= 0 {
+        return Err("Quantity must be positive".into());
+    }
+    if from_warehouse_id == to_warehouse_id {
+        return Err("Stock can only be transferred to another warehouse".into());
+    }
+
+    if !change_warehouse_stock(txn, from_warehouse_id, product.product_id, -quantity).await? {
+        return Err("Insufficient stock".into());
+    }
+    change_warehouse_stock(txn, to_warehouse_id, product.product_id, quantity).await?;
+
+    let movement = StockMovement::new(STOCK_MOVEMENT_TRANSFER)
+        .actor(Some(actor_user_id))
+        .note(note.clone());
+    insert_stock_movement(
+        txn,
+        product.product_id,
+        -quantity,
+        product.stock_quantity,
+        Some(from_warehouse_id),
+        &movement.reference(STOCK_REFERENCE_WAREHOUSE, to_warehouse_id),
+    )
+    .await?;
+    let movement = StockMovement::new(STOCK_MOVEMENT_TRANSFER)
+        .actor(Some(actor_user_id))
+        .note(note);
+    insert_stock_movement(
+        txn,
+        product.product_id,
+        quantity,
+        product.stock_quantity,
+        Some(to_warehouse_id),
+        &movement.reference(STOCK_REFERENCE_WAREHOUSE, from_warehouse_id),
+    )
+    .await?;
+
+    Ok(())
+}
+
+// order lines take their stock before the order exists, their movements are pointed at it afterwards
+pub async fn link_stock_movements<C: ConnectionTrait>(
+    conn: &C,
+    stock_movement_ids: Vec<i32>,
+    reference_type: &str,
+    reference_id: i32,
+) -> Result<(), DbErr> {
+    if stock_movement_ids.is_empty() {
+        return Ok(());
+    }
+
+    stock_movements::Entity::update_many()
+        .col_expr(
+            stock_movements::Column::ReferenceType,
+            reference_type.into(),
+        )
+        .col_expr(stock_movements::Column::ReferenceId, reference_id.into())
+        .filter(stock_movements::Column::StockMovementId.is_in(stock_movement_ids))
+        .exec(conn)
+        .await?;
+
+    Ok(())
+}
+
+// a supplier setting the stock outright, by hand or through an import, is recorded by the difference
+pub async fn adjust_stock_to(
+    txn: &DatabaseTransaction,
+    product: &products::Model,
+    stock_quantity: i32,
+    movement_type: &'static str,
+    actor_user_id: i32,
+) -> Result<(), async_graphql::Error> {
+    let quantity = stock_quantity - product.stock_quantity;
+    if quantity == 0 {
+        return Ok(());
+    }
+
+    change_stock(
+        txn,
+        product.product_id,
+        quantity,
+        &StockMovement::new(movement_type).actor(Some(actor_user_id)),
+    )
+    .await?
+    .ok_or("Insufficient stock")?;
+
+    Ok(())
+}
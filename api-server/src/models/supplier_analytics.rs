@@ -0,0 +1,309 @@
+use crate::{
+    models::{
+        orders::{ORDER_STATUS_DELIVERED, ORDER_STATUS_PAID, ORDER_STATUS_SHIPPED},
+        returns::RETURN_STATUS_RECEIVED,
+    },
+    money::{round, Money, DEFAULT_CURRENCY},
+};
+use async_graphql::{Enum, SimpleObject};
+use sea_orm::{
+    prelude::{DateTimeWithTimeZone, Decimal},
+    ConnectionTrait, DbBackend, DbErr, FromQueryResult, Statement, Value,
+};
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum SalesInterval {
+    Day,
+    Week,
+    Month,
+}
+
+impl SalesInterval {
+    fn date_trunc_field(self) -> &'static str {
+        match self {
+            SalesInterval::Day => "day",
+            SalesInterval::Week => "week",
+            SalesInterval::Month => "month",
+        }
+    }
+}
+
+// only orders that were paid for count as sales, amounts are converted to the default currency
+// with the rate each order was placed at
+const SUPPLIER_SALES: &str = "
+    FROM order_items oi
+        JOIN orders o ON o.order_id = oi.order_id
+        JOIN products p ON p.product_id = oi.product_id
+    WHERE p.supplier_id = $1
+        AND o.status IN ($2, $3, $4)
+        AND ($5::timestamptz IS NULL OR o.order_date >= $5)
+        AND ($6::timestamptz IS NULL OR o.order_date < $6)";
+
+const LINE_REVENUE: &str = "(oi.unit_price * oi.quantity - oi.discount_amount) / o.exchange_rate";
+
+fn sales_values(
+    supplier_id: i32,
+    from: Option<DateTimeWithTimeZone>,
+    to: Option<DateTimeWithTimeZone>,
+) -> Vec<Value> {
+    vec![
+        supplier_id.into(),
+        ORDER_STATUS_PAID.into(),
+        ORDER_STATUS_SHIPPED.into(),
+        ORDER_STATUS_DELIVERED.into(),
+        from.into(),
+        to.into(),
+    ]
+}
+
+#[derive(FromQueryResult)]
+struct SalesPeriodRow {
+    period_start: DateTimeWithTimeZone,
+    revenue: Decimal,
+    units_sold: i64,
+    orders: i64,
+}
+
+#[derive(SimpleObject)]
+pub struct SalesPeriod {
+    pub period_start: DateTimeWithTimeZone,
+    pub revenue: Money,
+    pub units_sold: i64,
+    pub orders: i64,
+}
+
+impl From<SalesPeriodRow> for SalesPeriod {
+    fn from(val: SalesPeriodRow) -> SalesPeriod {
+        SalesPeriod {
+            period_start: val.period_start,
+            revenue: Money::new(val.revenue, DEFAULT_CURRENCY),
+            units_sold: val.units_sold,
+            orders: val.orders,
+        }
+    }
+}
+
+#[derive(FromQueryResult)]
+struct TopProductRow {
+    product_id: i32,
+    name: String,
+    revenue: Decimal,
+    units_sold: i64,
+}
+
+#[derive(SimpleObject)]
+pub struct TopProduct {
+    pub product_id: i32,
+    pub name: String,
+    pub revenue: Money,
+    pub units_sold: i64,
+}
+
+impl From<TopProductRow> for TopProduct {
+    fn from(val: TopProductRow) -> TopProduct {
+        TopProduct {
+            product_id: val.product_id,
+            name: val.name,
+            revenue: Money::new(val.revenue, DEFAULT_CURRENCY),
+            units_sold: val.units_sold,
+        }
+    }
+}
+
+#[derive(FromQueryResult)]
+struct SalesSummaryRow {
+    revenue: Decimal,
+    units_sold: i64,
+    orders: i64,
+    returned_units: i64,
+}
+
+#[derive(SimpleObject)]
+pub struct SalesSummary {
+    pub revenue: Money,
+    pub orders: i64,
+    pub units_sold: i64,
+    // the supplier's part of an order, not the whole order total
+    pub average_order_value: Money,
+    pub returned_units: i64,
+    // share of the units sold in the range that were returned and received back
+    pub return_rate: f64,
+}
+
+#[derive(FromQueryResult)]
+struct RatingPeriodRow {
+    period_start: DateTimeWithTimeZone,
+    average_rating: Decimal,
+    reviews: i64,
+}
+
+#[derive(SimpleObject)]
+pub struct RatingPeriod {
+    pub period_start: DateTimeWithTimeZone,
+    pub average_rating: Decimal,
+    pub reviews: i64,
+}
+
+impl From<RatingPeriodRow> for RatingPeriod {
+    fn from(val: RatingPeriodRow) -> RatingPeriod {
+        RatingPeriod {
+            period_start: val.period_start,
+            average_rating: val.average_rating,
+            reviews: val.reviews,
+        }
+    }
+}
+
+pub async fn sales_by_period<C: ConnectionTrait>(
+    conn: &C,
+    supplier_id: i32,
+    interval: SalesInterval,
+    from: Option<DateTimeWithTimeZone>,
+    to: Option<DateTimeWithTimeZone>,
+) -> Result<Vec<SalesPeriod>, DbErr> {
+    let sql = format!(
+        "SELECT date_trunc('{}', o.order_date) AS period_start,
+            round(sum({}), 2) AS revenue,
+            sum(oi.quantity)::bigint AS units_sold,
+            count(DISTINCT o.order_id) AS orders
+        {}
+        GROUP BY period_start
+        ORDER BY period_start",
+        interval.date_trunc_field(),
+        LINE_REVENUE,
+        SUPPLIER_SALES
+    );
+
+    let rows = SalesPeriodRow::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        sql,
+        sales_values(supplier_id, from, to),
+    ))
+    .all(conn)
+    .await?;
+
+    Ok(rows.into_iter().map(|row| row.into()).collect())
+}
+
+pub async fn top_products<C: ConnectionTrait>(
+    conn: &C,
+    supplier_id: i32,
+    from: Option<DateTimeWithTimeZone>,
+    to: Option<DateTimeWithTimeZone>,
+    limit: u64,
+) -> Result<Vec<TopProduct>, DbErr> {
+    let sql = format!(
+        "SELECT p.product_id, p.name,
+            round(sum({}), 2) AS revenue,
+            sum(oi.quantity)::bigint AS units_sold
+        {}
+        GROUP BY p.product_id, p.name
+        ORDER BY revenue DESC
+        LIMIT $7",
+        LINE_REVENUE, SUPPLIER_SALES
+    );
+
+    let mut values = sales_values(supplier_id, from, to);
+    values.push((limit as i64).into());
+
+    let rows = TopProductRow::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        sql,
+        values,
+    ))
+    .all(conn)
+    .await?;
+
+    Ok(rows.into_iter().map(|row| row.into()).collect())
+}
+
+pub async fn sales_summary<C: ConnectionTrait>(
+    conn: &C,
+    supplier_id: i32,
+    from: Option<DateTimeWithTimeZone>,
+    to: Option<DateTimeWithTimeZone>,
+) -> Result<SalesSummary, DbErr> {
+    let sql = format!(
+        "WITH sales AS (
+            SELECT oi.order_item_id, o.order_id, oi.quantity, {} AS revenue
+            {}
+        )
+        SELECT coalesce(round(sum(revenue), 2), 0) AS revenue,
+            coalesce(sum(quantity), 0)::bigint AS units_sold,
+            count(DISTINCT order_id) AS orders,
+            (
+                SELECT coalesce(sum(ri.quantity), 0)
+                FROM return_items ri
+                    JOIN return_requests rr ON rr.return_id = ri.return_id
+                WHERE rr.status = $7
+                    AND ri.order_item_id IN (SELECT order_item_id FROM sales)
+            )::bigint AS returned_units
+        FROM sales",
+        LINE_REVENUE, SUPPLIER_SALES
+    );
+
+    let mut values = sales_values(supplier_id, from, to);
+    values.push(RETURN_STATUS_RECEIVED.into());
+
+    let row = SalesSummaryRow::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        sql,
+        values,
+    ))
+    .one(conn)
+    .await?
+    .ok_or_else(|| DbErr::RecordNotFound("Sales summary".to_string()))?;
+
+    let average_order_value = if row.orders > 0 {
+        round(row.revenue / Decimal::from(row.orders))
+    } else {
+        Decimal::ZERO
+    };
+    let return_rate = if row.units_sold > 0 {
+        row.returned_units as f64 / row.units_sold as f64
+    } else {
+        0.0
+    };
+
+    Ok(SalesSummary {
+        revenue: Money::new(row.revenue, DEFAULT_CURRENCY),
+        orders: row.orders,
+        units_sold: row.units_sold,
+        average_order_value: Money::new(average_order_value, DEFAULT_CURRENCY),
+        returned_units: row.returned_units,
+        return_rate,
+    })
+}
+
+pub async fn rating_trend<C: ConnectionTrait>(
+    conn: &C,
+    supplier_id: i32,
+    interval: SalesInterval,
+    from: Option<DateTimeWithTimeZone>,
+    to: Option<DateTimeWithTimeZone>,
+) -> Result<Vec<RatingPeriod>, DbErr> {
+    let sql = format!(
+        "SELECT date_trunc('{}', r.review_date) AS period_start,
+            round(avg(r.rating), 2) AS average_rating,
+            count(*) AS reviews
+        FROM reviews r
+            JOIN products p ON p.product_id = r.product_id
+        WHERE p.supplier_id = $1
+            AND r.rating IS NOT NULL
+            AND ($2::timestamptz IS NULL OR r.review_date >= $2)
+            AND ($3::timestamptz IS NULL OR r.review_date < $3)
+        GROUP BY period_start
+        ORDER BY period_start",
+        interval.date_trunc_field()
+    );
+
+    let rows = RatingPeriodRow::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        sql,
+        vec![supplier_id.into(), from.into(), to.into()],
+    ))
+    .all(conn)
+    .await?;
+
+    Ok(rows.into_iter().map(|row| row.into()).collect())
+}
@@ -0,0 +1,489 @@
+use crate::{
+    entity::{
+        categories, order_items, order_shipping_charges,
+        orders::{self, Model as OrdersModel},
+        products,
+        supplier_ledger_entries::{self, Model as SupplierLedgerEntriesModel},
+        supplier_payouts::{self, Model as SupplierPayoutsModel},
+    },
+    money::{prorate, round, Money, DEFAULT_CURRENCY},
+};
+use async_graphql::SimpleObject;
+use sea_orm::{
+    prelude::{DateTimeWithTimeZone, Decimal},
+    ActiveValue::Set,
+    ColumnTrait, ConnectionTrait, DatabaseTransaction, DbErr, EntityTrait, QueryFilter,
+    QuerySelect,
+};
+
+pub const LEDGER_ENTRY_SALE: &str = "SALE";
+pub const LEDGER_ENTRY_SHIPPING: &str = "SHIPPING";
+pub const LEDGER_ENTRY_COMMISSION: &str = "COMMISSION";
+pub const LEDGER_ENTRY_REFUND: &str = "REFUND";
+pub const LEDGER_ENTRY_COMMISSION_REFUND: &str = "COMMISSION_REFUND";
+pub const LEDGER_ENTRY_ADJUSTMENT: &str = "ADJUSTMENT";
+pub const LEDGER_ENTRY_PAYOUT: &str = "PAYOUT";
+
+pub const PAYOUT_STATUS_PENDING: &str = "PENDING";
+pub const PAYOUT_STATUS_PAID: &str = "PAID";
+
+// percent of the item amount kept by the platform when neither the category nor its parents set one
+pub const DEFAULT_COMMISSION_RATE: Decimal = Decimal::TEN;
+
+#[derive(SimpleObject)]
+pub struct SupplierLedgerEntries {
+    pub supplier_ledger_entry_id: i32,
+    pub supplier_id: i32,
+    pub order_id: Option<i32>,
+    pub order_item_id: Option<i32>,
+    pub supplier_payout_id: Option<i32>,
+    pub entry_type: String,
+    pub amount: Money,
+    pub note: Option<String>,
+    pub created_at: Option<DateTimeWithTimeZone>,
+}
+
+impl From<SupplierLedgerEntriesModel> for SupplierLedgerEntries {
+    fn from(val: SupplierLedgerEntriesModel) -> SupplierLedgerEntries {
+        SupplierLedgerEntries {
+            supplier_ledger_entry_id: val.supplier_ledger_entry_id,
+            supplier_id: val.supplier_id,
+            order_id: val.order_id,
+            order_item_id: val.order_item_id,
+            supplier_payout_id: val.supplier_payout_id,
+            entry_type: val.entry_type,
+            amount: Money::new(val.amount, DEFAULT_CURRENCY),
+            note: val.note,
+            created_at: val.created_at,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct SupplierPayouts {
+    pub supplier_payout_id: i32,
+    pub supplier_id: i32,
+    pub period_start: DateTimeWithTimeZone,
+    pub period_end: DateTimeWithTimeZone,
+    pub amount: Money,
+    pub status: String,
+    pub created_at: Option<DateTimeWithTimeZone>,
+    pub paid_at: Option<DateTimeWithTimeZone>,
+}
+
+impl From<SupplierPayoutsModel> for SupplierPayouts {
+    fn from(val: SupplierPayoutsModel) -> SupplierPayouts {
+        SupplierPayouts {
+            supplier_payout_id: val.supplier_payout_id,
+            supplier_id: val.supplier_id,
+            period_start: val.period_start,
+            period_end: val.period_end,
+            amount: Money::new(val.amount, DEFAULT_CURRENCY),
+            status: val.status,
+            created_at: val.created_at,
+            paid_at: val.paid_at,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct SupplierBalance {
+    pub supplier_id: i32,
+    // earned and not yet put on a payout statement
+    pub balance: Money,
+    pub pending_payouts: Money,
+    pub paid_out: Money,
+}
+
+// the closest category up the tree with a rate decides, categories can't loop so this ends
+pub async fn commission_rate(
+    txn: &DatabaseTransaction,
+    category_id: Option<i32>,
+) -> Result<Decimal, DbErr> {
+    let mut category_id = category_id;
+    while let Some(id) = category_id {
+        let Some(category) = categories::Entity::find_by_id(id).one(txn).await? else {
+            break;
+        };
+        if let Some(commission_rate) = category.commission_rate {
+            return Ok(commission_rate);
+        }
+        category_id = category.parent_category_id;
+    }
+    Ok(DEFAULT_COMMISSION_RATE)
+}
+
+// order amounts are converted with the exchange rate the order was placed at
+fn to_ledger_amount(order: &OrdersModel, amount: Decimal) -> Decimal {
+    round(amount / order.exchange_rate)
+}
+
+async fn insert_ledger_entry(
+    txn: &DatabaseTransaction,
+    supplier_id: i32,
+    order: Option<&OrdersModel>,
+    order_item_id: Option<i32>,
+    entry_type: &str,
+    amount: Decimal,
+    note: Option<String>,
+) -> Result<(), DbErr> {
+    let ledger_entry = supplier_ledger_entries::ActiveModel {
+        supplier_id: Set(supplier_id),
+        order_id: Set(order.map(|order| order.order_id)),
+        order_item_id: Set(order_item_id),
+        entry_type: Set(entry_type.to_string()),
+        amount: Set(amount),
+        note: Set(note),
+        ..Default::default()
+    };
+    supplier_ledger_entries::Entity::insert(ledger_entry)
+        .exec(txn)
+        .await?;
+    Ok(())
+}
+
+// suppliers earn from an order once it is paid, the items after their discount and the shipping they
+// charged, less the platform commission on the items. Orders already recorded are skipped.
+pub async fn record_order_sales(txn: &DatabaseTransaction, order_id: i32) -> Result<(), DbErr> {
+    let Some(order) = orders::Entity::find_by_id(order_id).one(txn).await? else {
+        return Ok(());
+    };
+
+    if supplier_ledger_entries::Entity::find()
+        .filter(supplier_ledger_entries::Column::OrderId.eq(order_id))
+        .one(txn)
+        .await?
+        .is_some()
+    {
+        return Ok(());
+    }
+
+    let order_items = order_items::Entity::find()
+        .find_also_related(products::Entity)
+        .filter(order_items::Column::OrderId.eq(order_id))
+        .all(txn)
+        .await?;
+
+    for (order_item, product) in order_items {
+        let Some(product) = product else {
+            continue;
+        };
+        let Some(supplier_id) = product.supplier_id else {
+            continue;
+        };
+
+        let amount = to_ledger_amount(
+            &order,
+            order_item.unit_price * Decimal::from(order_item.quantity) - order_item.discount_amount,
+        );
+        let commission =
+            round(amount * commission_rate(txn, product.category_id).await? / Decimal::ONE_HUNDRED);
+
+        insert_ledger_entry(
+            txn,
+            supplier_id,
+            Some(&order),
+            Some(order_item.order_item_id),
+            LEDGER_ENTRY_SALE,
+            amount,
+            None,
+        )
+        .await?;
+        insert_ledger_entry(
+            txn,
+            supplier_id,
+            Some(&order),
+            Some(order_item.order_item_id),
+            LEDGER_ENTRY_COMMISSION,
+            -commission,
+            None,
+        )
+        .await?;
+    }
+
+    let shipping_charges = order_shipping_charges::Entity::find()
+        .filter(order_shipping_charges::Column::OrderId.eq(order_id))
+        .all(txn)
+        .await?;
+
+    for charge in shipping_charges {
+        let Some(supplier_id) = charge.supplier_id else {
+            continue;
+        };
+        insert_ledger_entry(
+            txn,
+            supplier_id,
+            Some(&order),
+            None,
+            LEDGER_ENTRY_SHIPPING,
+            to_ledger_amount(&order, charge.amount),
+            Some(charge.name),
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+// cancelled or returned units are taken back off the sale, with the commission the platform kept on
+// them. Must be called before the order item's quantity is reduced.
+pub async fn reverse_order_item_sale(
+    txn: &DatabaseTransaction,
+    order: &OrdersModel,
+    order_item: &order_items::Model,
+    quantity: i32,
+) -> Result<(), DbErr> {
+    let entries = supplier_ledger_entries::Entity::find()
+        .filter(supplier_ledger_entries::Column::OrderItemId.eq(order_item.order_item_id))
+        .all(txn)
+        .await?;
+
+    let Some(supplier_id) = entries.first().map(|entry| entry.supplier_id) else {
+        return Ok(());
+    };
+
+    let total = |entry_type: &str| -> Decimal {
+        entries
+            .iter()
+            .filter(|entry| entry.entry_type == entry_type)
+            .map(|entry| entry.amount)
+            .sum()
+    };
+    let sale = total(LEDGER_ENTRY_SALE);
+    if sale.is_zero() {
+        return Ok(());
+    }
+
+    let amount = to_ledger_amount(
+        order,
+        order_item.unit_price * Decimal::from(quantity)
+            - prorate(order_item.discount_amount, quantity, order_item.quantity),
+    );
+    let commission = round(amount * -total(LEDGER_ENTRY_COMMISSION) / sale);
+
+    insert_ledger_entry(
+        txn,
+        supplier_id,
+        Some(order),
+        Some(order_item.order_item_id),
+        LEDGER_ENTRY_REFUND,
+        -amount,
+        None,
+    )
+    .await?;
+    insert_ledger_entry(
+        txn,
+        supplier_id,
+        Some(order),
+        Some(order_item.order_item_id),
+        LEDGER_ENTRY_COMMISSION_REFUND,
+        commission,
+        None,
+    )
+    .await?;
+
+    Ok(())
+}
+
+// when the rest of an order stops qualifying for its discount the line's share of it is charged
+// after all, the supplier earns it less the commission rate the sale had. Must be called before the
+// order item's discount is cleared.
+pub async fn remove_order_item_discount(
+    txn: &DatabaseTransaction,
+    order: &OrdersModel,
+    order_item: &order_items::Model,
+) -> Result<(), DbErr> {
+    let entries = supplier_ledger_entries::Entity::find()
+        .filter(supplier_ledger_entries::Column::OrderItemId.eq(order_item.order_item_id))
+        .all(txn)
+        .await?;
+
+    let Some(supplier_id) = entries.first().map(|entry| entry.supplier_id) else {
+        return Ok(());
+    };
+
+    let total = |entry_type: &str| -> Decimal {
+        entries
+            .iter()
+            .filter(|entry| entry.entry_type == entry_type)
+            .map(|entry| entry.amount)
+            .sum()
+    };
+    let sale = total(LEDGER_ENTRY_SALE);
+    if sale.is_zero() {
+        return Ok(());
+    }
+
+    let amount = to_ledger_amount(order, order_item.discount_amount);
+    let commission = round(amount * -total(LEDGER_ENTRY_COMMISSION) / sale);
+
+    insert_ledger_entry(
+        txn,
+        supplier_id,
+        Some(order),
+        Some(order_item.order_item_id),
+        LEDGER_ENTRY_SALE,
+        amount,
+        None,
+    )
+    .await?;
+    insert_ledger_entry(
+        txn,
+        supplier_id,
+        Some(order),
+        Some(order_item.order_item_id),
+        LEDGER_ENTRY_COMMISSION,
+        -commission,
+        None,
+    )
+    .await?;
+
+    Ok(())
+}
+
+// shipping is only given back when nothing is left to ship on the order
+pub async fn reverse_order_shipping(
+    txn: &DatabaseTransaction,
+    order: &OrdersModel,
+) -> Result<(), DbErr> {
+    let shipping_entries = supplier_ledger_entries::Entity::find()
+        .filter(supplier_ledger_entries::Column::OrderId.eq(order.order_id))
+        .filter(supplier_ledger_entries::Column::EntryType.eq(LEDGER_ENTRY_SHIPPING))
+        .all(txn)
+        .await?;
+
+    for entry in shipping_entries {
+        insert_ledger_entry(
+            txn,
+            entry.supplier_id,
+            Some(order),
+            None,
+            LEDGER_ENTRY_REFUND,
+            -entry.amount,
+            entry.note,
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+pub async fn record_supplier_adjustment(
+    txn: &DatabaseTransaction,
+    supplier_id: i32,
+    amount: Decimal,
+    note: String,
+) -> Result<(), DbErr> {
+    insert_ledger_entry(
+        txn,
+        supplier_id,
+        None,
+        None,
+        LEDGER_ENTRY_ADJUSTMENT,
+        amount,
+        Some(note),
+    )
+    .await
+}
+
+pub async fn supplier_balance<C: ConnectionTrait>(
+    conn: &C,
+    supplier_id: i32,
+) -> Result<SupplierBalance, DbErr> {
+    let balance: Option<Decimal> = supplier_ledger_entries::Entity::find()
+        .select_only()
+        .column_as(supplier_ledger_entries::Column::Amount.sum(), "balance")
+        .filter(supplier_ledger_entries::Column::SupplierId.eq(supplier_id))
+        .into_tuple()
+        .one(conn)
+        .await?
+        .flatten();
+
+    let payouts: Vec<(String, Option<Decimal>)> = supplier_payouts::Entity::find()
+        .select_only()
+        .column(supplier_payouts::Column::Status)
+        .column_as(supplier_payouts::Column::Amount.sum(), "amount")
+        .filter(supplier_payouts::Column::SupplierId.eq(supplier_id))
+        .group_by(supplier_payouts::Column::Status)
+        .into_tuple()
+        .all(conn)
+        .await?;
+
+    let payout_total = |status: &str| -> Decimal {
+        payouts
+            .iter()
+            .filter(|(payout_status, _)| payout_status == status)
+            .filter_map(|(_, amount)| *amount)
+            .sum()
+    };
+
+    Ok(SupplierBalance {
+        supplier_id,
+        balance: Money::new(balance.unwrap_or_default(), DEFAULT_CURRENCY),
+        pending_payouts: Money::new(payout_total(PAYOUT_STATUS_PENDING), DEFAULT_CURRENCY),
+        paid_out: Money::new(payout_total(PAYOUT_STATUS_PAID), DEFAULT_CURRENCY),
+    })
+}
+
+// a statement takes everything earned up to the end of the period that isn't on an earlier one, and
+// a payout entry brings the balance back down by what it pays
+pub async fn create_payout_statement(
+    txn: &DatabaseTransaction,
+    supplier_id: i32,
+    period_end: DateTimeWithTimeZone,
+) -> Result<SupplierPayoutsModel, async_graphql::Error> {
+    let entries = supplier_ledger_entries::Entity::find()
+        .filter(supplier_ledger_entries::Column::SupplierId.eq(supplier_id))
+        .filter(supplier_ledger_entries::Column::SupplierPayoutId.is_null())
+        .filter(supplier_ledger_entries::Column::CreatedAt.lte(period_end))
+        .all(txn)
+        .await?;
+
+    let amount: Decimal = entries.iter().map(|entry| entry.amount).sum();
+    if amount <= Decimal::ZERO {
+        return Err("Nothing is owed to the supplier for this period".into());
+    }
+
+    let period_start = entries
+        .iter()
+        .filter_map(|entry| entry.created_at)
+        .min()
+        .unwrap_or(period_end);
+
+    let supplier_payout = supplier_payouts::ActiveModel {
+        supplier_id: Set(supplier_id),
+        period_start: Set(period_start),
+        period_end: Set(period_end),
+        amount: Set(amount),
+        status: Set(PAYOUT_STATUS_PENDING.to_string()),
+        ..Default::default()
+    };
+    let supplier_payout = supplier_payouts::Entity::insert(supplier_payout)
+        .exec_with_returning(txn)
+        .await?;
+
+    supplier_ledger_entries::Entity::update_many()
+        .col_expr(
+            supplier_ledger_entries::Column::SupplierPayoutId,
+            supplier_payout.supplier_payout_id.into(),
+        )
+        .filter(
+            supplier_ledger_entries::Column::SupplierLedgerEntryId
+                .is_in(entries.iter().map(|entry| entry.supplier_ledger_entry_id)),
+        )
+        .exec(txn)
+        .await?;
+
+    let payout_entry = supplier_ledger_entries::ActiveModel {
+        supplier_id: Set(supplier_id),
+        supplier_payout_id: Set(Some(supplier_payout.supplier_payout_id)),
+        entry_type: Set(LEDGER_ENTRY_PAYOUT.to_string()),
+        amount: Set(-amount),
+        ..Default::default()
+    };
+    supplier_ledger_entries::Entity::insert(payout_entry)
+        .exec(txn)
+        .await?;
+
+    Ok(supplier_payout)
+}
@@ -0,0 +1,154 @@
+use crate::{
+    entity::{
+        addresses::Model as AddressesModel,
+        categories,
+        order_item_taxes::{self, Model as OrderItemTaxesModel},
+        products::Model as ProductsModel,
+        tax_rules::{self, Model as TaxRulesModel},
+    },
+    money::{round, Money},
+};
+use async_graphql::{InputObject, SimpleObject};
+use sea_orm::{
+    prelude::Decimal, ActiveModelTrait, ActiveValue::Set, ColumnTrait, Condition,
+    DatabaseTransaction, DbErr, EntityTrait, QueryFilter,
+};
+
+// products without a category, and categories nobody has classified, are taxed at the standard rates
+pub const DEFAULT_TAX_CLASS: &str = "standard";
+
+#[derive(SimpleObject)]
+pub struct TaxRules {
+    pub tax_rule_id: i32,
+    pub country: String,
+    pub state: Option<String>,
+    pub tax_class: String,
+    pub name: String,
+    pub rate: Decimal,
+}
+
+impl From<TaxRulesModel> for TaxRules {
+    fn from(val: TaxRulesModel) -> TaxRules {
+        TaxRules {
+            tax_rule_id: val.tax_rule_id,
+            country: val.country,
+            state: val.state,
+            tax_class: val.tax_class,
+            name: val.name,
+            rate: val.rate,
+        }
+    }
+}
+
+#[derive(InputObject)]
+pub struct RegisterTaxRule {
+    pub country: String,
+    pub state: Option<String>,
+    pub tax_class: String,
+    pub name: String,
+    pub rate: Decimal,
+}
+
+pub fn create_tax_rule_model(
+    input: RegisterTaxRule,
+) -> Result<tax_rules::ActiveModel, async_graphql::Error> {
+    if input.country.len() != 3 || !input.country.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err("Country must be a three letter ISO 3166 code".into());
+    }
+
+    if input.rate < Decimal::ZERO || input.rate > Decimal::ONE_HUNDRED {
+        return Err("Tax rate must be a percentage between 0 and 100".into());
+    }
+
+    Ok(tax_rules::ActiveModel {
+        country: Set(input.country.to_uppercase()),
+        state: Set(input.state),
+        tax_class: Set(input.tax_class),
+        name: Set(input.name),
+        rate: Set(input.rate),
+        ..Default::default()
+    })
+}
+
+#[derive(SimpleObject)]
+pub struct BillTaxes {
+    pub name: String,
+    pub rate: Decimal,
+    pub amount: Money,
+}
+
+pub async fn product_tax_class(
+    txn: &DatabaseTransaction,
+    product: &ProductsModel,
+) -> Result<String, DbErr> {
+    let Some(category_id) = product.category_id else {
+        return Ok(DEFAULT_TAX_CLASS.to_string());
+    };
+
+    Ok(categories::Entity::find_by_id(category_id)
+        .one(txn)
+        .await?
+        .map(|category| category.tax_class)
+        .unwrap_or_else(|| DEFAULT_TAX_CLASS.to_string()))
+}
+
+// country wide rules and the rules for the address's state both apply, e.g. CGST and SGST. Rules
+// are saved with upper case countries, addresses are taken as typed.
+pub async fn line_taxes(
+    txn: &DatabaseTransaction,
+    address: &AddressesModel,
+    tax_class: &str,
+    taxable_amount: Decimal,
+) -> Result<Vec<OrderItemTaxesModel>, DbErr> {
+    let rules = tax_rules::Entity::find()
+        .filter(tax_rules::Column::Country.eq(address.country.trim().to_uppercase()))
+        .filter(tax_rules::Column::TaxClass.eq(tax_class))
+        .filter(
+            Condition::any()
+                .add(tax_rules::Column::State.is_null())
+                .add_option(
+                    address
+                        .state
+                        .as_ref()
+                        .map(|state| tax_rules::Column::State.eq(state)),
+                ),
+        )
+        .all(txn)
+        .await?;
+
+    Ok(rules
+        .into_iter()
+        .map(|rule| OrderItemTaxesModel {
+            order_item_tax_id: 0,
+            order_item_id: 0,
+            amount: round(taxable_amount * rule.rate / Decimal::ONE_HUNDRED),
+            name: rule.name,
+            rate: rule.rate,
+        })
+        .collect())
+}
+
+// works the taxes of an order line out again from their rates when what is paid for it changes,
+// gives back the line's new tax amount
+pub async fn retax_order_item(
+    txn: &DatabaseTransaction,
+    order_item_id: i32,
+    taxable_amount: Decimal,
+) -> Result<Decimal, DbErr> {
+    let taxes = order_item_taxes::Entity::find()
+        .filter(order_item_taxes::Column::OrderItemId.eq(order_item_id))
+        .all(txn)
+        .await?;
+
+    let mut tax_amount = Decimal::ZERO;
+    for tax in taxes {
+        let amount = round(taxable_amount * tax.rate / Decimal::ONE_HUNDRED);
+        tax_amount += amount;
+
+        let mut tax: order_item_taxes::ActiveModel = tax.into();
+        tax.amount = Set(amount);
+        tax.update(txn).await?;
+    }
+
+    Ok(tax_amount)
+}
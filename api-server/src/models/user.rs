@@ -4,6 +4,7 @@ use crate::{
         customers::Model as CustomersModel, suppliers::Model as SuppliersModel,
         users::Model as UsersModel,
     },
+    money::{Money, DEFAULT_CURRENCY},
 };
 use async_graphql::{Error, InputObject, SimpleObject};
 use sea_orm::{
@@ -60,6 +61,7 @@ pub struct Customers {
     pub last_name: String,
     pub registration_date: Option<DateTimeWithTimeZone>,
     pub user_id: i32,
+    pub wallet_balance: Money,
 }
 
 impl From<CustomersModel> for Customers {
@@ -70,6 +72,7 @@ impl From<CustomersModel> for Customers {
             last_name: val.last_name,
             registration_date: val.registration_date,
             user_id: val.user_id,
+            wallet_balance: Money::new(val.wallet_balance, DEFAULT_CURRENCY),
         }
     }
 }
@@ -86,6 +89,8 @@ pub struct Suppliers {
     pub name: String,
     pub contact_phone: Option<String>,
     pub user_id: i32,
+    pub cod_enabled: bool,
+    pub cod_limit: Option<Money>,
 }
 
 impl From<SuppliersModel> for Suppliers {
@@ -95,6 +100,10 @@ impl From<SuppliersModel> for Suppliers {
             name: val.name,
             contact_phone: val.contact_phone,
             user_id: val.user_id,
+            cod_enabled: val.cod_enabled,
+            cod_limit: val
+                .cod_limit
+                .map(|cod_limit| Money::new(cod_limit, DEFAULT_CURRENCY)),
         }
     }
 }
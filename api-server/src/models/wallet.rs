@@ -0,0 +1,86 @@
+use crate::{
+    entity::{
+        customers,
+        wallet_transactions::{self, Model as WalletTransactionsModel},
+    },
+    money::{Money, DEFAULT_CURRENCY},
+};
+use async_graphql::SimpleObject;
+use sea_orm::{
+    prelude::{DateTimeWithTimeZone, Decimal},
+    sea_query::Expr,
+    ActiveValue::Set,
+    ColumnTrait, DatabaseTransaction, EntityTrait, QueryFilter,
+};
+
+pub const WALLET_TRANSACTION_CREDIT: &str = "CREDIT";
+pub const WALLET_TRANSACTION_ORDER_PAYMENT: &str = "ORDER_PAYMENT";
+pub const WALLET_TRANSACTION_ORDER_REFUND: &str = "ORDER_REFUND";
+
+#[derive(SimpleObject)]
+pub struct WalletTransactions {
+    pub wallet_transaction_id: i32,
+    pub customer_id: i32,
+    pub order_id: Option<i32>,
+    pub amount: Money,
+    pub balance_after: Money,
+    pub reason: String,
+    pub note: Option<String>,
+    pub created_at: Option<DateTimeWithTimeZone>,
+}
+
+impl From<WalletTransactionsModel> for WalletTransactions {
+    fn from(val: WalletTransactionsModel) -> WalletTransactions {
+        WalletTransactions {
+            wallet_transaction_id: val.wallet_transaction_id,
+            customer_id: val.customer_id,
+            order_id: val.order_id,
+            amount: Money::new(val.amount, DEFAULT_CURRENCY),
+            balance_after: Money::new(val.balance_after, DEFAULT_CURRENCY),
+            reason: val.reason,
+            note: val.note,
+            created_at: val.created_at,
+        }
+    }
+}
+
+// the wallet is kept in the default currency, every change to the balance goes through here so the
+// ledger always adds up to it. A debit only goes through while the balance covers it.
+pub async fn change_wallet_balance(
+    txn: &DatabaseTransaction,
+    customer_id: i32,
+    amount: Decimal,
+    reason: &str,
+    order_id: Option<i32>,
+    note: Option<String>,
+) -> Result<WalletTransactionsModel, async_graphql::Error> {
+    let mut update = customers::Entity::update_many()
+        .col_expr(
+            customers::Column::WalletBalance,
+            Expr::col(customers::Column::WalletBalance).add(amount),
+        )
+        .filter(customers::Column::CustomerId.eq(customer_id));
+    if amount < Decimal::ZERO {
+        update = update.filter(customers::Column::WalletBalance.gte(-amount));
+    }
+
+    let customer = update
+        .exec_with_returning(txn)
+        .await?
+        .pop()
+        .ok_or("Insufficient wallet balance")?;
+
+    let wallet_transaction = wallet_transactions::ActiveModel {
+        customer_id: Set(customer_id),
+        order_id: Set(order_id),
+        amount: Set(amount),
+        balance_after: Set(customer.wallet_balance),
+        reason: Set(reason.to_string()),
+        note: Set(note),
+        ..Default::default()
+    };
+
+    Ok(wallet_transactions::Entity::insert(wallet_transaction)
+        .exec_with_returning(txn)
+        .await?)
+}
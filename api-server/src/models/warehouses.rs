@@ -0,0 +1,214 @@
+use crate::entity::{
+    addresses::Model as AddressesModel,
+    products,
+    warehouse_stock::{self, Model as WarehouseStockModel},
+    warehouses::{self, Model as WarehousesModel},
+};
+use async_graphql::{InputObject, SimpleObject};
+use sea_orm::{
+    prelude::{DateTimeWithTimeZone, Expr},
+    ActiveValue::Set,
+    ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter, QueryOrder,
+};
+
+#[derive(SimpleObject)]
+pub struct Warehouses {
+    pub warehouse_id: i32,
+    pub supplier_id: i32,
+    pub name: String,
+    pub country: String,
+    pub postal_code: String,
+    pub created_at: Option<DateTimeWithTimeZone>,
+}
+
+impl From<WarehousesModel> for Warehouses {
+    fn from(val: WarehousesModel) -> Warehouses {
+        Warehouses {
+            warehouse_id: val.warehouse_id,
+            supplier_id: val.supplier_id,
+            name: val.name,
+            country: val.country,
+            postal_code: val.postal_code,
+            created_at: val.created_at,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct WarehouseStock {
+    pub warehouse_id: i32,
+    pub product_id: i32,
+    pub quantity: i32,
+}
+
+impl From<WarehouseStockModel> for WarehouseStock {
+    fn from(val: WarehouseStockModel) -> WarehouseStock {
+        WarehouseStock {
+            warehouse_id: val.warehouse_id,
+            product_id: val.product_id,
+            quantity: val.quantity,
+        }
+    }
+}
+
+#[derive(InputObject)]
+pub struct RegisterWarehouse {
+    pub name: String,
+    pub country: String,
+    pub postal_code: String,
+}
+
+pub fn create_warehouse_model(
+    input: RegisterWarehouse,
+    supplier_id: i32,
+) -> Result<warehouses::ActiveModel, async_graphql::Error> {
+    if input.country.len() != 3 || !input.country.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err("Country must be a three letter ISO 3166 code".into());
+    }
+
+    if input.postal_code.trim().is_empty() {
+        return Err("Postal code is required".into());
+    }
+
+    Ok(warehouses::ActiveModel {
+        supplier_id: Set(supplier_id),
+        name: Set(input.name),
+        country: Set(input.country.to_uppercase()),
+        postal_code: Set(input.postal_code.trim().to_string()),
+        ..Default::default()
+    })
+}
+
+// oldest first, the first warehouse is where stock goes when no warehouse is named
+pub async fn product_warehouses<C: ConnectionTrait>(
+    conn: &C,
+    product_id: i32,
+) -> Result<Vec<WarehousesModel>, DbErr> {
+    let Some(product) = products::Entity::find_by_id(product_id).one(conn).await? else {
+        return Ok(Vec::new());
+    };
+    let Some(supplier_id) = product.supplier_id else {
+        return Ok(Vec::new());
+    };
+
+    warehouses::Entity::find()
+        .filter(warehouses::Column::SupplierId.eq(supplier_id))
+        .order_by_asc(warehouses::Column::WarehouseId)
+        .all(conn)
+        .await
+}
+
+// a warehouse in the same country beats one abroad, then the longer the shared start of the postal
+// codes the closer it is
+fn proximity(warehouse: &WarehousesModel, address: &AddressesModel) -> (bool, usize) {
+    let same_country = warehouse.country.trim() == address.country.trim();
+    let shared_prefix = warehouse
+        .postal_code
+        .trim()
+        .chars()
+        .zip(address.postal_code.trim().chars())
+        .take_while(|(a, b)| a == b)
+        .count();
+    (same_country, if same_country { shared_prefix } else { 0 })
+}
+
+// picks a warehouse that can send the whole quantity on its own, the closest to the address when
+// there is one and otherwise the one holding the most
+pub async fn allocate_warehouse<C: ConnectionTrait>(
+    conn: &C,
+    product_id: i32,
+    quantity: i32,
+    warehouses: &[WarehousesModel],
+    ship_to: Option<&AddressesModel>,
+) -> Result<Option<i32>, DbErr> {
+    let stock = warehouse_stock::Entity::find()
+        .filter(warehouse_stock::Column::ProductId.eq(product_id))
+        .filter(warehouse_stock::Column::Quantity.gte(quantity))
+        .all(conn)
+        .await?;
+
+    Ok(stock
+        .into_iter()
+        .filter_map(|stock| {
+            let warehouse = warehouses
+                .iter()
+                .find(|warehouse| warehouse.warehouse_id == stock.warehouse_id)?;
+            let proximity = ship_to.map(|address| proximity(warehouse, address));
+            Some((proximity, stock.quantity, stock.warehouse_id))
+        })
+        .max_by_key(|(proximity, quantity, warehouse_id)| (*proximity, *quantity, -warehouse_id))
+        .map(|(_, _, warehouse_id)| warehouse_id))
+}
+
+// taking stock is conditional like on products, gives back false when the warehouse doesn't hold enough
+pub async fn change_warehouse_stock<C: ConnectionTrait>(
+    conn: &C,
+    warehouse_id: i32,
+    product_id: i32,
+    quantity: i32,
+) -> Result<bool, DbErr> {
+    let mut update = warehouse_stock::Entity::update_many()
+        .col_expr(
+            warehouse_stock::Column::Quantity,
+            Expr::col(warehouse_stock::Column::Quantity).add(quantity),
+        )
+        .filter(warehouse_stock::Column::WarehouseId.eq(warehouse_id))
+        .filter(warehouse_stock::Column::ProductId.eq(product_id));
+    if quantity < 0 {
+        update = update.filter(warehouse_stock::Column::Quantity.gte(-quantity));
+    }
+
+    if update.exec(conn).await?.rows_affected > 0 {
+        return Ok(true);
+    }
+    if quantity < 0 {
+        return Ok(false);
+    }
+
+    let stock = warehouse_stock::ActiveModel {
+        warehouse_id: Set(warehouse_id),
+        product_id: Set(product_id),
+        quantity: Set(quantity),
+        ..Default::default()
+    };
+    warehouse_stock::Entity::insert(stock).exec(conn).await?;
+
+    Ok(true)
+}
+
+// the stock a supplier had before their first warehouse is all placed in it, so the warehouses
+// always add up to the products' stock
+pub async fn move_stock_into_first_warehouse<C: ConnectionTrait>(
+    conn: &C,
+    warehouse: &WarehousesModel,
+) -> Result<(), DbErr> {
+    let products = products::Entity::find()
+        .filter(products::Column::SupplierId.eq(warehouse.supplier_id))
+        .filter(products::Column::StockQuantity.gt(0))
+        .all(conn)
+        .await?;
+
+    for product in products {
+        change_warehouse_stock(
+            conn,
+            warehouse.warehouse_id,
+            product.product_id,
+            product.stock_quantity,
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+pub async fn find_supplier_warehouse<C: ConnectionTrait>(
+    conn: &C,
+    supplier_id: i32,
+    warehouse_id: i32,
+) -> Result<WarehousesModel, async_graphql::Error> {
+    Ok(warehouses::Entity::find_by_id(warehouse_id)
+        .one(conn)
+        .await?
+        .filter(|warehouse| warehouse.supplier_id == supplier_id)
+        .ok_or("Warehouse not found")?)
+}
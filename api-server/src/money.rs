@@ -0,0 +1,101 @@
+use async_graphql::{InputValueError, InputValueResult, Name, Scalar, ScalarType, Value};
+use rust_decimal::RoundingStrategy;
+use sea_orm::prelude::Decimal;
+use std::str::FromStr;
+
+pub const DEFAULT_CURRENCY: &str = "INR";
+
+// every amount is kept to the paisa/cent, anything finer is rounded half away from zero
+pub const MONEY_SCALE: u32 = 2;
+
+pub fn round(amount: Decimal) -> Decimal {
+    amount.round_dp_with_strategy(MONEY_SCALE, RoundingStrategy::MidpointAwayFromZero)
+}
+
+// the share of a line amount that belongs to `quantity` of its `of` units
+pub fn prorate(amount: Decimal, quantity: i32, of: i32) -> Decimal {
+    round(amount * Decimal::from(quantity) / Decimal::from(of))
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Money {
+    pub amount: Decimal,
+    pub currency: String,
+}
+
+impl Money {
+    pub fn new(amount: Decimal, currency: &str) -> Money {
+        Money {
+            amount: round(amount),
+            currency: currency.to_uppercase(),
+        }
+    }
+}
+
+impl From<Decimal> for Money {
+    fn from(amount: Decimal) -> Money {
+        Money::new(amount, DEFAULT_CURRENCY)
+    }
+}
+
+// ISO 4217 codes are three letters, stored upper case
+pub fn currency_code(code: &str) -> Option<String> {
+    let code = code.trim();
+    (code.len() == 3 && code.chars().all(|c| c.is_ascii_alphabetic())).then(|| code.to_uppercase())
+}
+
+fn parse_amount(value: &Value) -> Result<Decimal, InputValueError<Money>> {
+    match value {
+        Value::String(amount) => Decimal::from_str(amount)
+            .map_err(|_| InputValueError::custom(format!("Invalid amount \"{}\"", amount))),
+        // a float is read from its shortest decimal form, so 19.99 stays 19.99 instead of the
+        // binary value closest to it
+        Value::Number(amount) => Decimal::from_str(&amount.to_string())
+            .or_else(|_| Decimal::from_scientific(&amount.to_string()))
+            .map_err(|_| InputValueError::custom(format!("Invalid amount {}", amount))),
+        _ => Err(InputValueError::custom(
+            "Amount must be a string or a number",
+        )),
+    }
+}
+
+fn parse_currency(value: &Value) -> Result<String, InputValueError<Money>> {
+    match value {
+        Value::String(currency) => currency_code(currency),
+        _ => None,
+    }
+    .ok_or_else(|| InputValueError::custom("Currency must be a three letter ISO 4217 code"))
+}
+
+/// An exact amount of money with its ISO 4217 currency code, e.g. `{"amount": "199.99", "currency": "INR"}`.
+/// A bare amount is also accepted as input and is taken to be in INR.
+#[Scalar]
+impl ScalarType for Money {
+    fn parse(value: Value) -> InputValueResult<Self> {
+        match &value {
+            Value::Object(fields) => {
+                let amount = parse_amount(
+                    fields
+                        .get("amount")
+                        .ok_or_else(|| InputValueError::custom("Missing amount"))?,
+                )?;
+                let currency = match fields.get("currency") {
+                    Some(currency) => parse_currency(currency)?,
+                    None => DEFAULT_CURRENCY.to_string(),
+                };
+                Ok(Money::new(amount, &currency))
+            }
+            _ => Ok(Money::from(parse_amount(&value)?)),
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        let mut fields = async_graphql::indexmap::IndexMap::new();
+        fields.insert(
+            Name::new("amount"),
+            Value::String(round(self.amount).to_string()),
+        );
+        fields.insert(Name::new("currency"), Value::String(self.currency.clone()));
+        Value::Object(fields)
+    }
+}
@@ -0,0 +1,303 @@
+use crate::{entity::payment_methods::Model as PaymentMethodsModel, error::AppError, money::Money};
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use sea_orm::prelude::Decimal;
+use sha2::{Digest, Sha256};
+use std::{env, sync::Arc};
+
+#[derive(thiserror::Error, Debug)]
+pub enum PaymentError {
+    #[error("Payment declined: {0}")]
+    Declined(String),
+
+    #[error("Payment provider error: {0}")]
+    Provider(String),
+}
+
+pub struct AuthorizeRequest<'a> {
+    pub order_id: i32,
+    // counts the payment attempts for the order from 1, a retry after a decline or a void is a new
+    // attempt and must get a new reference
+    pub attempt: u64,
+    pub amount: &'a Money,
+    pub payment_method: &'a PaymentMethodsModel,
+}
+
+// every call returns the provider's reference for the operation, authorizing returns the
+// reference of the payment intent that capture, void and refund are made against. Those three
+// take a key that stays the same when the operation is retried, the provider carries a key out
+// once and answers repeats with the first result, so a call whose outcome couldn't be saved is
+// safe to make again
+#[async_trait]
+pub trait PaymentProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    // swaps the card number for a token that stands for the card in every later call, so the
+    // number itself never has to be stored
+    async fn tokenize_card(
+        &self,
+        card_number: &str,
+        expiration_date: NaiveDate,
+    ) -> Result<String, PaymentError>;
+
+    async fn authorize(&self, request: AuthorizeRequest<'_>) -> Result<String, PaymentError>;
+
+    async fn capture(
+        &self,
+        intent_reference: &str,
+        amount: &Money,
+        idempotency_key: &str,
+    ) -> Result<String, PaymentError>;
+
+    async fn void(
+        &self,
+        intent_reference: &str,
+        idempotency_key: &str,
+    ) -> Result<String, PaymentError>;
+
+    async fn refund(
+        &self,
+        intent_reference: &str,
+        amount: &Money,
+        idempotency_key: &str,
+    ) -> Result<String, PaymentError>;
+}
+
+pub type SharedPaymentProvider = Arc<dyn PaymentProvider>;
+
+// picks the provider named by PAYMENT_PROVIDER, the mock one when it isn't set
+pub fn payment_provider_from_env() -> Result<SharedPaymentProvider, AppError> {
+    match env::var("PAYMENT_PROVIDER").as_deref() {
+        Ok("mock") | Err(_) => Ok(Arc::new(MockPaymentProvider)),
+        Ok(provider) => Err(AppError::Internal(format!(
+            "Unknown payment provider: {}",
+            provider
+        ))),
+    }
+}
+
+// approves everything without network calls, so the same input always gives the same result,
+// except cards ending in 0002 and UPI ids starting with fail, which are declined, and amounts that
+// aren't positive, which are rejected
+pub struct MockPaymentProvider;
+
+fn check_amount(amount: &Money) -> Result<(), PaymentError> {
+    if amount.amount <= Decimal::ZERO {
+        return Err(PaymentError::Provider(
+            "Amount must be greater than zero".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl PaymentProvider for MockPaymentProvider {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    async fn tokenize_card(
+        &self,
+        card_number: &str,
+        _expiration_date: NaiveDate,
+    ) -> Result<String, PaymentError> {
+        if !(12..=19).contains(&card_number.len())
+            || !card_number.chars().all(|c| c.is_ascii_digit())
+        {
+            return Err(PaymentError::Declined("Invalid card number".to_string()));
+        }
+
+        // the same card always gets the same token
+        Ok(format!(
+            "mock_tok_{}",
+            &hex::encode(Sha256::digest(card_number.as_bytes()))[..24]
+        ))
+    }
+
+    async fn authorize(&self, request: AuthorizeRequest<'_>) -> Result<String, PaymentError> {
+        let payment_method = request.payment_method;
+
+        check_amount(request.amount)?;
+
+        if payment_method
+            .card_last4
+            .as_ref()
+            .is_some_and(|card_last4| card_last4 == "0002")
+        {
+            return Err(PaymentError::Declined("Card declined".to_string()));
+        }
+
+        if payment_method
+            .upi_id
+            .as_ref()
+            .is_some_and(|upi_id| upi_id.starts_with("fail"))
+        {
+            return Err(PaymentError::Declined(
+                "UPI collect request rejected".to_string(),
+            ));
+        }
+
+        Ok(format!(
+            "mock_pi_{}_{}_{}",
+            request.order_id, payment_method.payment_method_id, request.attempt
+        ))
+    }
+
+    // the same key always gives the same reference, like a repeat answered by a real provider
+    async fn capture(
+        &self,
+        intent_reference: &str,
+        amount: &Money,
+        idempotency_key: &str,
+    ) -> Result<String, PaymentError> {
+        check_amount(amount)?;
+        Ok(format!("{}_{}", intent_reference, idempotency_key))
+    }
+
+    async fn void(
+        &self,
+        intent_reference: &str,
+        idempotency_key: &str,
+    ) -> Result<String, PaymentError> {
+        Ok(format!("{}_{}", intent_reference, idempotency_key))
+    }
+
+    async fn refund(
+        &self,
+        intent_reference: &str,
+        amount: &Money,
+        idempotency_key: &str,
+    ) -> Result<String, PaymentError> {
+        check_amount(amount)?;
+        Ok(format!("{}_{}", intent_reference, idempotency_key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::sea_orm_active_enums::PaymentMethodType;
+
+    fn payment_method(card_last4: Option<&str>, upi_id: Option<&str>) -> PaymentMethodsModel {
+        PaymentMethodsModel {
+            payment_method_id: 1,
+            customer_id: 1,
+            payment_type: if upi_id.is_some() {
+                PaymentMethodType::Upi
+            } else {
+                PaymentMethodType::Card
+            },
+            is_default: None,
+            bank_name: None,
+            account_holder_name: None,
+            card_token: card_last4.map(|_| "mock_tok_test".to_string()),
+            card_last4: card_last4.map(String::from),
+            card_brand: None,
+            card_expiration_date: None,
+            iban: None,
+            upi_id: upi_id.map(String::from),
+            bank_account_number: None,
+            ifsc_code: None,
+            card_type_id: None,
+        }
+    }
+
+    async fn authorize(
+        payment_method: &PaymentMethodsModel,
+        amount: Decimal,
+    ) -> Result<String, PaymentError> {
+        MockPaymentProvider
+            .authorize(AuthorizeRequest {
+                order_id: 1,
+                attempt: 1,
+                amount: &Money::new(amount, "INR"),
+                payment_method,
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn approves_other_instruments() {
+        let reference = authorize(&payment_method(Some("4242"), None), Decimal::ONE_HUNDRED)
+            .await
+            .unwrap();
+        assert_eq!(reference, "mock_pi_1_1_1");
+        assert!(authorize(
+            &payment_method(None, Some("name@okaxis")),
+            Decimal::ONE_HUNDRED
+        )
+        .await
+        .is_ok());
+    }
+
+    #[tokio::test]
+    async fn declines_test_instruments() {
+        assert!(matches!(
+            authorize(&payment_method(Some("0002"), None), Decimal::ONE_HUNDRED).await,
+            Err(PaymentError::Declined(_))
+        ));
+        assert!(matches!(
+            authorize(
+                &payment_method(None, Some("fail@okaxis")),
+                Decimal::ONE_HUNDRED
+            )
+            .await,
+            Err(PaymentError::Declined(_))
+        ));
+        assert!(matches!(
+            MockPaymentProvider
+                .tokenize_card("4242", NaiveDate::default())
+                .await,
+            Err(PaymentError::Declined(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn rejects_amounts_that_arent_positive() {
+        let provider = MockPaymentProvider;
+        let card = payment_method(Some("4242"), None);
+        for amount in [Decimal::ZERO, Decimal::NEGATIVE_ONE] {
+            let amount = Money::new(amount, "INR");
+            assert!(matches!(
+                authorize(&card, amount.amount).await,
+                Err(PaymentError::Provider(_))
+            ));
+            assert!(matches!(
+                provider
+                    .capture("mock_pi_1_1_1", &amount, "capture_1")
+                    .await,
+                Err(PaymentError::Provider(_))
+            ));
+            assert!(matches!(
+                provider.refund("mock_pi_1_1_1", &amount, "refund_1").await,
+                Err(PaymentError::Provider(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn repeats_answer_with_the_same_reference() {
+        let provider = MockPaymentProvider;
+        let amount = Money::new(Decimal::ONE_HUNDRED, "INR");
+        assert_eq!(
+            provider
+                .capture("mock_pi_1_1_1", &amount, "capture_1")
+                .await
+                .unwrap(),
+            provider
+                .capture("mock_pi_1_1_1", &amount, "capture_1")
+                .await
+                .unwrap()
+        );
+        assert_eq!(
+            provider
+                .refund("mock_pi_1_1_1", &amount, "refund_1")
+                .await
+                .unwrap(),
+            provider
+                .refund("mock_pi_1_1_1", &amount, "refund_1")
+                .await
+                .unwrap()
+        );
+    }
+}
@@ -0,0 +1,219 @@
+use crate::auth::Auth;
+use crate::entity::payment_events::Model as PaymentEventsModel;
+use crate::entity::prelude::{
+    Bills as BillsEntity, Orders as OrdersEntity, PaymentEvents as PaymentEventsEntity,
+    PaymentIntents as PaymentIntentsEntity,
+};
+use crate::entity::{bills, payment_events, payment_intents};
+use crate::error::AppError;
+use crate::models::bills::BILL_STATUS_FAILED;
+use crate::models::orders::ORDER_STATUS_PENDING;
+use crate::models::payment_intents::{
+    capture_payment_intent, check_payment_event_status, PaymentEvent,
+    PAYMENT_INTENT_STATUS_AUTHORIZED, PAYMENT_INTENT_STATUS_CAPTURED, PAYMENT_INTENT_STATUS_FAILED,
+    PAYMENT_INTENT_STATUS_VOIDED,
+};
+use axum::body::Bytes;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::IntoResponse;
+use axum::Extension;
+use chrono::Utc;
+use sea_orm::prelude::Decimal;
+use sea_orm::ActiveValue::Set;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, DatabaseTransaction, EntityTrait,
+    QueryFilter, TransactionTrait,
+};
+use std::env;
+
+pub async fn payment_webhook(
+    Extension(postgres): Extension<DatabaseConnection>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> impl IntoResponse {
+    let secret = match env::var("PAYMENT_WEBHOOK_SECRET") {
+        Ok(secret) => secret,
+        Err(_) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "PAYMENT_WEBHOOK_SECRET must be set".to_string(),
+            )
+        }
+    };
+
+    let signature = headers
+        .get("x-payment-signature")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    if let Err(e) = Auth::verify_signature(&secret, &body, signature) {
+        return (StatusCode::UNAUTHORIZED, e.to_string());
+    }
+
+    let event: PaymentEvent = match serde_json::from_slice(&body) {
+        Ok(event) => event,
+        Err(e) => return (StatusCode::BAD_REQUEST, format!("Invalid payload: {}", e)),
+    };
+
+    if let Err(e) = check_payment_event_status(&event.status) {
+        return (StatusCode::BAD_REQUEST, e.to_string());
+    }
+
+    // the raw body is kept so the event can be replayed exactly as it was received
+    let payload = String::from_utf8_lossy(&body).into_owned();
+
+    match record_payment_event(&postgres, event, payload).await {
+        Ok(response) => response,
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+async fn record_payment_event(
+    postgres: &DatabaseConnection,
+    event: PaymentEvent,
+    payload: String,
+) -> Result<(StatusCode, String), AppError> {
+    // providers retry deliveries, an event that went through is only acknowledged again while
+    // one that failed is processed once more
+    let payment_event = match PaymentEventsEntity::find()
+        .filter(payment_events::Column::ProviderEventId.eq(&event.event_id))
+        .one(postgres)
+        .await?
+    {
+        Some(payment_event) if payment_event.processed_at.is_some() => {
+            return Ok((StatusCode::OK, "Event already processed".to_string()))
+        }
+        Some(payment_event) => payment_event,
+        None => {
+            let payment_event = payment_events::ActiveModel {
+                provider_event_id: Set(event.event_id),
+                provider_reference: Set(event.provider_reference),
+                status: Set(event.status),
+                payload: Set(payload),
+                ..Default::default()
+            };
+            PaymentEventsEntity::insert(payment_event)
+                .exec_with_returning(postgres)
+                .await?
+        }
+    };
+
+    let payment_event = process_payment_event(postgres, payment_event).await?;
+
+    match payment_event.error {
+        Some(error) => Ok((StatusCode::UNPROCESSABLE_ENTITY, error)),
+        None => Ok((StatusCode::OK, "Event processed".to_string())),
+    }
+}
+
+// applies a stored event, used for new deliveries and for replays, a failure is saved on the event
+pub async fn process_payment_event(
+    postgres: &DatabaseConnection,
+    payment_event: PaymentEventsModel,
+) -> Result<PaymentEventsModel, AppError> {
+    let txn = postgres.begin().await?;
+
+    match apply_payment_event(&txn, &payment_event).await {
+        Ok(()) => {
+            let mut payment_event: payment_events::ActiveModel = payment_event.into();
+            payment_event.error = Set(None);
+            payment_event.processed_at = Set(Some(Utc::now().fixed_offset()));
+            let payment_event = payment_event.update(&txn).await?;
+            txn.commit().await?;
+            Ok(payment_event)
+        }
+        Err(e) => {
+            txn.rollback().await?;
+            let mut payment_event: payment_events::ActiveModel = payment_event.into();
+            payment_event.error = Set(Some(e.to_string()));
+            Ok(payment_event.update(postgres).await?)
+        }
+    }
+}
+
+// events that repeat the current state of the payment change nothing, so replays are harmless
+async fn apply_payment_event(
+    txn: &DatabaseTransaction,
+    payment_event: &PaymentEventsModel,
+) -> Result<(), AppError> {
+    let event: PaymentEvent = serde_json::from_str(&payment_event.payload)
+        .map_err(|e| AppError::Internal(format!("Invalid payload: {}", e)))?;
+
+    let payment_intent = PaymentIntentsEntity::find()
+        .filter(payment_intents::Column::ProviderReference.eq(&event.provider_reference))
+        .one(txn)
+        .await?
+        .ok_or_else(|| AppError::Internal("No payment with this reference".to_string()))?;
+
+    if payment_intent.status == event.status {
+        return Ok(());
+    }
+
+    if payment_intent.status != PAYMENT_INTENT_STATUS_AUTHORIZED {
+        return Err(AppError::Internal(format!(
+            "Payment is already {}",
+            payment_intent.status
+        )));
+    }
+
+    match event.status.as_str() {
+        PAYMENT_INTENT_STATUS_CAPTURED => {
+            // a capture can be partial but never more than was authorized, in the same currency
+            let amount = event.amount.unwrap_or(payment_intent.amount);
+            if amount <= Decimal::ZERO || amount > payment_intent.amount {
+                return Err(AppError::Internal(
+                    "Captured amount doesn't match the payment".to_string(),
+                ));
+            }
+            if event
+                .currency
+                .is_some_and(|currency| currency != payment_intent.currency)
+            {
+                return Err(AppError::Internal(
+                    "Captured currency doesn't match the payment".to_string(),
+                ));
+            }
+            // money taken for an order that was cancelled in the meantime is kept as a failed
+            // event, for an admin to refund instead of settling the order
+            let order = OrdersEntity::find_by_id(payment_intent.order_id)
+                .one(txn)
+                .await?
+                .ok_or_else(|| AppError::Internal("Order not found".to_string()))?;
+            if order.status != ORDER_STATUS_PENDING {
+                return Err(AppError::Internal(format!(
+                    "Payment captured for an order that is {}",
+                    order.status
+                )));
+            }
+            capture_payment_intent(txn, payment_intent, amount).await?;
+        }
+        PAYMENT_INTENT_STATUS_FAILED => {
+            let bill_id = payment_intent.bill_id;
+
+            let mut payment_intent: payment_intents::ActiveModel = payment_intent.into();
+            payment_intent.status = Set(PAYMENT_INTENT_STATUS_FAILED.to_string());
+            payment_intent.failure_reason = Set(event.failure_reason);
+            payment_intent.updated_at = Set(Some(Utc::now().fixed_offset()));
+            payment_intent.update(txn).await?;
+
+            BillsEntity::update_many()
+                .col_expr(bills::Column::PaymentStatus, BILL_STATUS_FAILED.into())
+                .filter(bills::Column::BillId.eq(bill_id))
+                .exec(txn)
+                .await?;
+        }
+        PAYMENT_INTENT_STATUS_VOIDED => {
+            let mut payment_intent: payment_intents::ActiveModel = payment_intent.into();
+            payment_intent.status = Set(PAYMENT_INTENT_STATUS_VOIDED.to_string());
+            payment_intent.updated_at = Set(Some(Utc::now().fixed_offset()));
+            payment_intent.update(txn).await?;
+        }
+        _ => {
+            return Err(AppError::Internal(
+                "Invalid payment event status".to_string(),
+            ))
+        }
+    }
+
+    Ok(())
+}
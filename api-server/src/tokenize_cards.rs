@@ -0,0 +1,85 @@
+use crate::{
+    error::AppError, models::payments::detect_card_brand, payment_gateway::SharedPaymentProvider,
+};
+use sea_orm::{
+    prelude::Date, ConnectionTrait, DatabaseConnection, DbBackend, Statement, TransactionTrait,
+};
+
+// one-off migration for databases created before cards were tokenized, every stored card number
+// is swapped for a provider token, its last four digits and detected brand, then the column is dropped.
+// Rows the provider refuses to tokenize are only masked and have to be registered again.
+pub async fn tokenize_stored_cards(
+    db: &DatabaseConnection,
+    payment_provider: &SharedPaymentProvider,
+) -> Result<usize, AppError> {
+    let txn = db.begin().await?;
+
+    let card_number_column = txn
+        .query_one(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT 1 FROM information_schema.columns
+                WHERE table_name = 'payment_methods' AND column_name = 'card_number';",
+        ))
+        .await?;
+    if card_number_column.is_none() {
+        return Ok(0);
+    }
+
+    txn.execute_unprepared(
+        "ALTER TABLE payment_methods
+            ADD COLUMN IF NOT EXISTS card_token varchar(100),
+            ADD COLUMN IF NOT EXISTS card_last4 char(4),
+            ADD COLUMN IF NOT EXISTS card_brand varchar(20);",
+    )
+    .await?;
+
+    let cards = txn
+        .query_all(Statement::from_string(
+            DbBackend::Postgres,
+            "SELECT payment_method_id, card_number, card_expiration_date FROM payment_methods
+                WHERE card_number IS NOT NULL;",
+        ))
+        .await?;
+
+    for card in &cards {
+        let payment_method_id = card.try_get::<i32>("", "payment_method_id")?;
+        let card_number = card.try_get::<String>("", "card_number")?;
+        let card_number = card_number.trim();
+        let card_expiration_date = card.try_get::<Option<Date>>("", "card_expiration_date")?;
+        let card_brand = detect_card_brand(card_number);
+
+        let card_token = match card_expiration_date {
+            Some(card_expiration_date) => payment_provider
+                .tokenize_card(card_number, card_expiration_date)
+                .await
+                .ok(),
+            None => None,
+        };
+        let card_last4: String = card_number
+            .chars()
+            .skip(card_number.chars().count().saturating_sub(4))
+            .collect();
+
+        txn.execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "UPDATE payment_methods
+                SET card_token = $1, card_last4 = $2, card_brand = $3,
+                    card_type_id = (SELECT card_type_id FROM card_types WHERE name = $3)
+                WHERE payment_method_id = $4;",
+            vec![
+                card_token.into(),
+                card_last4.into(),
+                card_brand.into(),
+                payment_method_id.into(),
+            ],
+        ))
+        .await?;
+    }
+
+    txn.execute_unprepared("ALTER TABLE payment_methods DROP COLUMN card_number;")
+        .await?;
+
+    txn.commit().await?;
+
+    Ok(cards.len())
+}
@@ -1,6 +1,6 @@
-create type payment_method_type as enum ('netbanking', 'card', 'iban', 'upi');
+create type payment_method_type as enum ('netbanking', 'card', 'iban', 'upi', 'cod', 'wallet');
 
-create type user_role as enum ('customer', 'supplier');
+create type user_role as enum ('customer', 'supplier', 'admin');
 
 create table categories
 (
@@ -10,7 +10,11 @@ create table categories
     parent_category_id integer
         constraint fk_parent_category
             references categories
-            on delete set null
+            on delete set null,
+    tax_class          varchar(50) default 'standard' not null,
+    commission_rate    numeric(5, 2)
+        constraint categories_commission_rate_check
+            check (commission_rate >= 0 and commission_rate <= 100)
 );
 
 create table card_types
@@ -21,6 +25,18 @@ create table card_types
         unique
 );
 
+insert into card_types (name)
+values ('Visa'),
+       ('Mastercard'),
+       ('American Express'),
+       ('Discover'),
+       ('Diners Club'),
+       ('JCB'),
+       ('Maestro'),
+       ('RuPay'),
+       ('UnionPay'),
+       ('Unknown');
+
 create table address_types
 (
     address_type_id serial
@@ -39,7 +55,8 @@ create table users
     role           user_role    not null
         constraint users_role_check
             check ((role)::text = ANY
-                   (ARRAY [('customer'::character varying)::text, ('supplier'::character varying)::text])),
+                   (ARRAY [('customer'::character varying)::text, ('supplier'::character varying)::text,
+                       ('admin'::character varying)::text])),
     created_at     timestamp with time zone default CURRENT_TIMESTAMP,
     email_verified boolean                  default false
 );
@@ -55,7 +72,10 @@ create table customers
         unique
         constraint fk_user_customer
             references users
-            on delete cascade
+            on delete cascade,
+    wallet_balance    numeric(10, 2) default 0 not null
+        constraint customers_wallet_balance_check
+            check (wallet_balance >= (0)::numeric)
 );
 
 create table addresses
@@ -94,7 +114,9 @@ create table payment_methods
     is_default           boolean default false,
     bank_name            varchar(100),
     account_holder_name  varchar(100),
-    card_number          char(16),
+    card_token           varchar(100),
+    card_last4           char(4),
+    card_brand           varchar(20),
     card_expiration_date date,
     iban                 char(34),
     upi_id               varchar(50),
@@ -122,33 +144,57 @@ create table suppliers
         unique
         constraint fk_user_supplier
             references users
-            on delete cascade
+            on delete cascade,
+    cod_enabled   boolean      default false not null,
+    cod_limit     numeric(10, 2)
 );
 
 create table products
 (
-    product_id      serial
+    product_id        serial
         primary key,
-    name            varchar(100)      not null,
-    description     text,
-    base_price      numeric(10, 2)    not null,
-    category_id     integer
+    name              varchar(100)      not null,
+    description       text,
+    base_price        numeric(10, 2)    not null,
+    currency          char(3) default 'INR' not null,
+    category_id       integer
         constraint fk_category
             references categories
             on delete set null,
-    supplier_id     integer
+    supplier_id       integer
         constraint fk_supplier
             references suppliers
             on delete set null,
-    stock_quantity  integer default 0 not null,
-    base_product_id integer
+    stock_quantity    integer default 0 not null,
+    base_product_id   integer
         constraint fk_base_product
             references products
             on delete set null,
-    media_paths     text[],
-    created_at      timestamp with time zone
+    media_paths       text[],
+    created_at        timestamp with time zone,
+    weight_grams      integer
+        constraint products_weight_grams_check
+            check (weight_grams >= 0),
+    length_mm         integer,
+    width_mm          integer,
+    height_mm         integer,
+    is_gift_card      boolean default false not null,
+    reorder_threshold integer
+        constraint products_reorder_threshold_check
+            check (reorder_threshold >= 0),
+    status            varchar(20) default 'ACTIVE' not null
+        constraint products_status_check
+            check ((status)::text = ANY
+                   (ARRAY [('DRAFT'::character varying)::text, ('ACTIVE'::character varying)::text,
+                       ('ARCHIVED'::character varying)::text])),
+    sku               varchar(64),
+    constraint unique_supplier_sku
+        unique (supplier_id, sku)
 );
 
+create index idx_product_status
+    on products (status);
+
 create index idx_product_category
     on products (category_id);
 
@@ -158,6 +204,45 @@ create index idx_product_supplier
 create index idx_product_name
     on products (name);
 
+create table warehouses
+(
+    warehouse_id serial
+        primary key,
+    supplier_id  integer      not null
+        constraint fk_supplier_warehouse
+            references suppliers
+            on delete cascade,
+    name         varchar(100) not null,
+    country      char(3)      not null,
+    postal_code  varchar(10)  not null,
+    created_at   timestamp with time zone default CURRENT_TIMESTAMP
+);
+
+create index idx_warehouses_supplier
+    on warehouses (supplier_id);
+
+create table warehouse_stock
+(
+    warehouse_stock_id serial
+        primary key,
+    warehouse_id       integer           not null
+        constraint fk_warehouse_stock_warehouse
+            references warehouses
+            on delete cascade,
+    product_id         integer           not null
+        constraint fk_warehouse_stock_product
+            references products
+            on delete cascade,
+    quantity           integer default 0 not null
+        constraint warehouse_stock_quantity_check
+            check (quantity >= 0),
+    constraint unique_warehouse_product
+        unique (warehouse_id, product_id)
+);
+
+create index idx_warehouse_stock_product
+    on warehouse_stock (product_id);
+
 create table shopping_carts
 (
     cart_id     serial
@@ -256,6 +341,12 @@ create table orders
             on delete restrict,
     order_date          timestamp with time zone default CURRENT_TIMESTAMP,
     total_amount        numeric(10, 2) not null,
+    tax_amount          numeric(10, 2) default 0 not null,
+    shipping_amount     numeric(10, 2) default 0 not null,
+    wallet_amount       numeric(10, 2) default 0 not null,
+    gift_card_amount    numeric(10, 2) default 0 not null,
+    currency            char(3)        default 'INR' not null,
+    exchange_rate       numeric(18, 8) default 1 not null,
     status              varchar(20)    not null,
     shipping_address_id integer        not null
         constraint fk_shipping_address
@@ -268,7 +359,10 @@ create table orders
     discount_id         integer
         constraint fk_discount
             references discounts
-            on delete set null
+            on delete set null,
+    idempotency_key     varchar(255),
+    constraint unique_customer_idempotency_key
+        unique (customer_id, idempotency_key)
 );
 
 create index idx_orders_customer_date
@@ -288,7 +382,12 @@ create table order_items
             on delete restrict,
     quantity        integer                  not null,
     unit_price      numeric(10, 2)           not null,
-    discount_amount numeric(10, 2) default 0 not null
+    discount_amount numeric(10, 2) default 0 not null,
+    tax_amount      numeric(10, 2) default 0 not null,
+    warehouse_id    integer
+        constraint fk_warehouse_order_item
+            references warehouses
+            on delete set null
 );
 
 create index idx_order_items_order
@@ -299,16 +398,19 @@ create index idx_order_items_product
 
 create table bills
 (
-    bill_id        serial
+    bill_id         serial
         primary key,
-    order_id       integer        not null
+    order_id        integer        not null
         unique
         constraint fk_order
             references orders
             on delete restrict,
-    bill_date      timestamp with time zone default CURRENT_TIMESTAMP,
-    total_amount   numeric(10, 2) not null,
-    payment_status varchar(20)    not null
+    bill_date       timestamp with time zone default CURRENT_TIMESTAMP,
+    total_amount    numeric(10, 2) not null,
+    tax_amount      numeric(10, 2) default 0 not null,
+    shipping_amount numeric(10, 2) default 0 not null,
+    currency        char(3)        default 'INR' not null,
+    payment_status  varchar(20)    not null
 );
 
 create index idx_discounts_code
@@ -323,3 +425,525 @@ create index idx_discounts_category
 create index idx_discounts_validity
     on discounts (valid_from, valid_until);
 
+
+create table shipments
+(
+    shipment_id     serial
+        primary key,
+    order_id        integer     not null
+        constraint fk_order_shipment
+            references orders
+            on delete restrict,
+    supplier_id     integer
+        constraint fk_supplier_shipment
+            references suppliers
+            on delete set null,
+    carrier         varchar(50) not null,
+    tracking_number varchar(100) not null,
+    status          varchar(20) not null,
+    shipped_at      timestamp with time zone default CURRENT_TIMESTAMP,
+    delivered_at    timestamp with time zone,
+    constraint unique_carrier_tracking_number
+        unique (carrier, tracking_number)
+);
+
+create index idx_shipments_order
+    on shipments (order_id);
+
+create table shipment_events
+(
+    shipment_event_id serial
+        primary key,
+    shipment_id       integer     not null
+        constraint fk_shipment
+            references shipments
+            on delete cascade,
+    carrier_event_id  varchar(100)
+        unique,
+    status            varchar(20) not null,
+    description       text,
+    location          varchar(100),
+    occurred_at       timestamp with time zone not null,
+    received_at       timestamp with time zone default CURRENT_TIMESTAMP
+);
+
+create index idx_shipment_events_shipment
+    on shipment_events (shipment_id, occurred_at);
+
+create table return_requests
+(
+    return_id        serial
+        primary key,
+    order_id         integer     not null
+        constraint fk_order_return
+            references orders
+            on delete restrict,
+    customer_id      integer     not null
+        constraint fk_customer_return
+            references customers
+            on delete cascade,
+    supplier_id      integer
+        constraint fk_supplier_return
+            references suppliers
+            on delete set null,
+    status           varchar(20) not null,
+    rejection_reason text,
+    requested_at     timestamp with time zone default CURRENT_TIMESTAMP,
+    decided_at       timestamp with time zone,
+    received_at      timestamp with time zone
+);
+
+create index idx_return_requests_customer
+    on return_requests (customer_id);
+
+create index idx_return_requests_supplier_status
+    on return_requests (supplier_id, status);
+
+create table return_items
+(
+    return_item_id serial
+        primary key,
+    return_id      integer not null
+        constraint fk_return
+            references return_requests
+            on delete cascade,
+    order_item_id  integer not null
+        constraint fk_order_item_return
+            references order_items
+            on delete restrict,
+    quantity       integer not null
+        constraint return_items_quantity_check
+            check (quantity > 0),
+    reason         text    not null
+);
+
+create index idx_return_items_return
+    on return_items (return_id);
+
+create index idx_return_items_order_item
+    on return_items (order_item_id);
+
+create table refunds
+(
+    refund_id         serial
+        primary key,
+    return_id         integer
+        constraint fk_return_refund
+            references return_requests
+            on delete restrict,
+    bill_id           integer        not null
+        constraint fk_bill_refund
+            references bills
+            on delete restrict,
+    payment_method_id integer        not null
+        constraint fk_payment_method_refund
+            references payment_methods
+            on delete restrict,
+    amount            numeric(10, 2) not null,
+    currency          char(3)        default 'INR' not null,
+    status            varchar(20)    not null,
+    created_at        timestamp with time zone default CURRENT_TIMESTAMP
+);
+
+create index idx_refunds_bill
+    on refunds (bill_id);
+
+create table bill_adjustments
+(
+    bill_adjustment_id serial
+        primary key,
+    bill_id            integer        not null
+        constraint fk_bill_adjustment
+            references bills
+            on delete cascade,
+    amount             numeric(10, 2) not null,
+    currency           char(3)        default 'INR' not null,
+    reason             varchar(50)    not null,
+    created_at         timestamp with time zone default CURRENT_TIMESTAMP
+);
+
+create index idx_bill_adjustments_bill
+    on bill_adjustments (bill_id);
+
+create table stock_reservations
+(
+    reservation_id serial
+        primary key,
+    customer_id    integer                  not null
+        constraint fk_customer_reservation
+            references customers
+            on delete cascade,
+    product_id     integer                  not null
+        constraint fk_product_reservation
+            references products
+            on delete cascade,
+    quantity       integer                  not null
+        constraint stock_reservations_quantity_check
+            check (quantity > 0),
+    status         varchar(20)              not null,
+    expires_at     timestamp with time zone not null,
+    order_id       integer
+        constraint fk_order_reservation
+            references orders
+            on delete set null,
+    created_at     timestamp with time zone default CURRENT_TIMESTAMP,
+    warehouse_id   integer
+        constraint fk_warehouse_reservation
+            references warehouses
+            on delete set null
+);
+
+create index idx_stock_reservations_customer_product
+    on stock_reservations (customer_id, product_id, status);
+
+create index idx_stock_reservations_status_expiry
+    on stock_reservations (status, expires_at);
+
+create table exchange_rates
+(
+    currency   char(3)        not null
+        primary key,
+    rate       numeric(18, 8) not null
+        constraint exchange_rates_rate_check
+            check (rate > 0),
+    updated_at timestamp with time zone default CURRENT_TIMESTAMP
+);
+
+create table tax_rules
+(
+    tax_rule_id serial
+        primary key,
+    country     char(3)       not null,
+    state       varchar(50),
+    tax_class   varchar(50)   not null,
+    name        varchar(50)   not null,
+    rate        numeric(5, 2) not null
+        constraint tax_rules_rate_check
+            check (rate >= 0)
+);
+
+create index idx_tax_rules_country_class
+    on tax_rules (country, tax_class);
+
+create table order_item_taxes
+(
+    order_item_tax_id serial
+        primary key,
+    order_item_id     integer        not null
+        constraint fk_order_item_tax
+            references order_items
+            on delete cascade,
+    name              varchar(50)    not null,
+    rate              numeric(5, 2)  not null,
+    amount            numeric(10, 2) not null
+);
+
+create index idx_order_item_taxes_order_item
+    on order_item_taxes (order_item_id);
+
+create table shipping_profiles
+(
+    shipping_profile_id serial
+        primary key,
+    supplier_id         integer                    not null
+        constraint fk_supplier_shipping_profile
+            references suppliers
+            on delete cascade,
+    name                varchar(50)                not null,
+    rate_type           varchar(20)                not null
+        constraint shipping_profiles_rate_type_check
+            check ((rate_type)::text = ANY
+                   (ARRAY [('FLAT'::character varying)::text, ('WEIGHT'::character varying)::text])),
+    base_rate           numeric(10, 2) default 0   not null,
+    per_kg_rate         numeric(10, 2) default 0   not null,
+    free_over           numeric(10, 2),
+    currency            char(3)        default 'INR' not null,
+    country             char(3),
+    postal_prefix       varchar(10),
+    estimated_days      integer
+);
+
+create index idx_shipping_profiles_supplier
+    on shipping_profiles (supplier_id);
+
+create table order_shipping_charges
+(
+    order_shipping_charge_id serial
+        primary key,
+    order_id                 integer        not null
+        constraint fk_order_shipping_charge
+            references orders
+            on delete cascade,
+    supplier_id              integer
+        constraint fk_supplier_shipping_charge
+            references suppliers
+            on delete set null,
+    shipping_profile_id      integer
+        constraint fk_shipping_profile_charge
+            references shipping_profiles
+            on delete set null,
+    name                     varchar(50)    not null,
+    amount                   numeric(10, 2) not null
+);
+
+create index idx_order_shipping_charges_order
+    on order_shipping_charges (order_id);
+
+create table payment_intents
+(
+    payment_intent_id  serial
+        primary key,
+    order_id           integer        not null
+        constraint fk_order_payment_intent
+            references orders
+            on delete cascade,
+    bill_id            integer        not null
+        constraint fk_bill_payment_intent
+            references bills
+            on delete cascade,
+    provider           varchar(20)    not null,
+    provider_reference varchar(100),
+    amount             numeric(10, 2) not null,
+    captured_amount    numeric(10, 2) default 0 not null,
+    refunded_amount    numeric(10, 2) default 0 not null,
+    currency           char(3)        default 'INR' not null,
+    status             varchar(20)    not null
+        constraint payment_intents_status_check
+            check ((status)::text = ANY
+                   (ARRAY [('AUTHORIZED'::character varying)::text, ('CAPTURED'::character varying)::text,
+                           ('VOIDED'::character varying)::text, ('FAILED'::character varying)::text])),
+    failure_reason     text,
+    created_at         timestamp with time zone default CURRENT_TIMESTAMP,
+    updated_at         timestamp with time zone default CURRENT_TIMESTAMP
+);
+
+create index idx_payment_intents_order
+    on payment_intents (order_id);
+
+create unique index idx_unique_payment_intent_reference
+    on payment_intents (provider_reference);
+
+create table payment_events
+(
+    payment_event_id   serial
+        primary key,
+    provider_event_id  varchar(100) not null
+        unique,
+    provider_reference varchar(100) not null,
+    status             varchar(20)  not null,
+    payload            text         not null,
+    error              text,
+    received_at        timestamp with time zone default CURRENT_TIMESTAMP,
+    processed_at       timestamp with time zone
+);
+
+create index idx_payment_events_reference
+    on payment_events (provider_reference);
+
+create table wallet_transactions
+(
+    wallet_transaction_id serial
+        primary key,
+    customer_id           integer        not null
+        constraint fk_customer_wallet_transaction
+            references customers
+            on delete cascade,
+    order_id              integer
+        constraint fk_order_wallet_transaction
+            references orders
+            on delete set null,
+    amount                numeric(10, 2) not null,
+    balance_after         numeric(10, 2) not null,
+    reason                varchar(20)    not null,
+    note                  text,
+    created_at            timestamp with time zone default CURRENT_TIMESTAMP
+);
+
+create index idx_wallet_transactions_customer
+    on wallet_transactions (customer_id, created_at);
+
+create table gift_cards
+(
+    gift_card_id   serial
+        primary key,
+    code           varchar(16)    not null
+        unique,
+    initial_amount numeric(10, 2) not null,
+    balance        numeric(10, 2) not null
+        constraint gift_cards_balance_check
+            check (balance >= 0),
+    currency       char(3)        default 'INR' not null,
+    customer_id    integer
+        constraint fk_customer_gift_card
+            references customers
+            on delete set null,
+    order_item_id  integer
+        constraint fk_order_item_gift_card
+            references order_items
+            on delete set null,
+    refund_id      integer
+        constraint fk_refund_gift_card
+            references refunds
+            on delete set null,
+    note           text,
+    created_at     timestamp with time zone default CURRENT_TIMESTAMP
+);
+
+create index idx_gift_cards_customer
+    on gift_cards (customer_id);
+
+create table gift_card_transactions
+(
+    gift_card_transaction_id serial
+        primary key,
+    gift_card_id             integer        not null
+        constraint fk_gift_card_transaction
+            references gift_cards
+            on delete cascade,
+    order_id                 integer
+        constraint fk_order_gift_card_transaction
+            references orders
+            on delete set null,
+    amount                   numeric(10, 2) not null,
+    balance_after            numeric(10, 2) not null,
+    created_at               timestamp with time zone default CURRENT_TIMESTAMP
+);
+
+create index idx_gift_card_transactions_gift_card
+    on gift_card_transactions (gift_card_id, created_at);
+
+create table supplier_payouts
+(
+    supplier_payout_id serial
+        primary key,
+    supplier_id        integer                  not null
+        constraint fk_supplier_payout
+            references suppliers
+            on delete cascade,
+    period_start       timestamp with time zone not null,
+    period_end         timestamp with time zone not null,
+    amount             numeric(10, 2)           not null,
+    status             varchar(20)              not null,
+    created_at         timestamp with time zone default CURRENT_TIMESTAMP,
+    paid_at            timestamp with time zone
+);
+
+create index idx_supplier_payouts_supplier
+    on supplier_payouts (supplier_id, created_at);
+
+create table supplier_ledger_entries
+(
+    supplier_ledger_entry_id serial
+        primary key,
+    supplier_id              integer        not null
+        constraint fk_supplier_ledger_entry
+            references suppliers
+            on delete cascade,
+    order_id                 integer
+        constraint fk_order_supplier_ledger_entry
+            references orders
+            on delete set null,
+    order_item_id            integer
+        constraint fk_order_item_supplier_ledger_entry
+            references order_items
+            on delete set null,
+    supplier_payout_id       integer
+        constraint fk_payout_supplier_ledger_entry
+            references supplier_payouts
+            on delete set null,
+    entry_type               varchar(20)    not null,
+    amount                   numeric(10, 2) not null,
+    note                     text,
+    created_at               timestamp with time zone default CURRENT_TIMESTAMP
+);
+
+create index idx_supplier_ledger_entries_supplier
+    on supplier_ledger_entries (supplier_id, created_at);
+
+create index idx_supplier_ledger_entries_order
+    on supplier_ledger_entries (order_id);
+
+create table stock_movements
+(
+    stock_movement_id serial
+        primary key,
+    product_id        integer     not null
+        constraint fk_product_stock_movement
+            references products
+            on delete cascade,
+    quantity          integer     not null,
+    stock_after       integer     not null,
+    movement_type     varchar(20) not null,
+    reference_type    varchar(20),
+    reference_id      integer,
+    actor_user_id     integer
+        constraint fk_user_stock_movement
+            references users
+            on delete set null,
+    note              text,
+    created_at        timestamp with time zone default CURRENT_TIMESTAMP,
+    warehouse_id      integer
+        constraint fk_warehouse_stock_movement
+            references warehouses
+            on delete set null
+);
+
+create index idx_stock_movements_product
+    on stock_movements (product_id, created_at);
+
+create table supplier_notifications
+(
+    notification_id   serial
+        primary key,
+    supplier_id       integer               not null
+        constraint fk_supplier_notification
+            references suppliers
+            on delete cascade,
+    product_id        integer
+        constraint fk_product_notification
+            references products
+            on delete cascade,
+    notification_type varchar(20)           not null,
+    message           text                  not null,
+    is_read           boolean default false not null,
+    created_at        timestamp with time zone default CURRENT_TIMESTAMP
+);
+
+create index idx_supplier_notifications_supplier
+    on supplier_notifications (supplier_id, is_read);
+
+create table stock_subscriptions
+(
+    subscription_id serial
+        primary key,
+    customer_id     integer not null
+        constraint fk_customer_stock_subscription
+            references customers
+            on delete cascade,
+    product_id      integer not null
+        constraint fk_product_stock_subscription
+            references products
+            on delete cascade,
+    created_at      timestamp with time zone default CURRENT_TIMESTAMP,
+    constraint unique_customer_product_subscription
+        unique (customer_id, product_id)
+);
+
+create index idx_stock_subscriptions_product
+    on stock_subscriptions (product_id);
+
+create table product_variant_options
+(
+    option_id        serial
+        primary key,
+    product_id       integer      not null
+        constraint fk_product_variant_option
+            references products
+            on delete cascade,
+    option_name      varchar(50)  not null,
+    option_value     varchar(100) not null,
+    price_adjustment numeric(10, 2)
+);
+
+create index idx_product_variant_options_product
+    on product_variant_options (product_id);